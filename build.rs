@@ -1,5 +1,7 @@
 fn main() {
     let target = std::env::var("TARGET").unwrap();
+    // Shown in the About dialog so bug reports can tell the builds apart.
+    println!("cargo:rustc-env=MORSET_TARGET={}", target);
     if target.contains("windows") {
         let _ = embed_resource::compile("icon.rc", embed_resource::NONE);
     }
@@ -0,0 +1,36 @@
+use crate::settings::Settings;
+
+/// Listening speed that follows the answers, slowing down after a wrong one
+/// and speeding up after a streak of right ones
+pub struct AdaptiveSpeed {
+    /// Right answers since the last wrong one or speed up
+    streak: usize,
+}
+
+impl AdaptiveSpeed {
+    pub fn new() -> Self {
+        Self { streak: 0 }
+    }
+
+    /// Adjust the listening speed to the answer, within the adaptive bounds.
+    /// Returns whether the speed changed.
+    pub fn answer(&mut self, correct: bool, settings: &mut Settings) -> bool {
+        let before = settings.listening_wpm;
+        let step = settings.adaptive_step;
+        if correct {
+            self.streak += 1;
+            if self.streak < settings.adaptive_streak {
+                return false;
+            }
+            self.streak = 0;
+            settings.listening_wpm = before.saturating_add(step);
+        } else {
+            self.streak = 0;
+            settings.listening_wpm = before.saturating_sub(step);
+        }
+        settings.listening_wpm = settings
+            .listening_wpm
+            .clamp(settings.adaptive_min_wpm, settings.adaptive_max_wpm);
+        settings.listening_wpm != before
+    }
+}
@@ -0,0 +1,72 @@
+use clap::{Parser, Subcommand};
+
+/// Playground for practicing CW communication skills
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// Run a command without starting the GUI
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the morse code for a text
+    Encode(EncodeArgs),
+    /// Print the text for a morse code sequence
+    Decode(DecodeArgs),
+}
+
+/// Symbols used to write morse code
+#[derive(Debug, clap::Args)]
+pub struct NotationArgs {
+    /// Symbol for a dit
+    #[arg(long, default_value = ".")]
+    pub dit: String,
+    /// Symbol for a dah
+    #[arg(long, default_value = "-")]
+    pub dah: String,
+    /// Separator between elements of a character
+    #[arg(long, default_value = "")]
+    pub element_separator: String,
+    /// Separator between characters of a word
+    #[arg(long, default_value = " ")]
+    pub character_separator: String,
+    /// Separator between words
+    #[arg(long, default_value = " / ")]
+    pub word_separator: String,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct EncodeArgs {
+    /// Text to encode, read from stdin if omitted
+    pub text: Option<String>,
+    #[command(flatten)]
+    pub notation: NotationArgs,
+    /// Also print the duration of every element and gap in milliseconds at this speed
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..))]
+    pub wpm: Option<u8>,
+    /// Percentage of a dit plus its gap during which the tone is on, used with --wpm
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u8).range(25..=75))]
+    pub weight: u8,
+    /// Leave out characters that have no morse code instead of failing
+    #[arg(long)]
+    pub skip_unknown: bool,
+    /// Send digits as cut numbers, like 5NN for 599
+    #[arg(long)]
+    pub cut_numbers: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct DecodeArgs {
+    /// Morse code to decode, read from stdin if omitted
+    pub morse: Option<String>,
+    #[command(flatten)]
+    pub notation: NotationArgs,
+    /// Printed in place of sequences that aren't known
+    #[arg(long, default_value = "?")]
+    pub placeholder: String,
+    /// Read cut numbers in words with digits as digits, like 5NN as 599
+    #[arg(long)]
+    pub cut_numbers: bool,
+}
@@ -1,49 +1,730 @@
-use rodio::OutputStream;
-use rodio::Sink;
-use rodio::source::{SineWave, Source};
+use std::f32::consts::{PI, SQRT_2, TAU};
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::time::Duration;
 
-/// Simple audio manager for playing sine wave tones
+use morset::signal::{SPECTRUM_LEN, power_spectrum};
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::source::{Function, SignalGenerator};
+use rodio::{ChannelCount, OutputStream, SampleRate, Sink, Source};
+use serde::{Deserialize, Serialize};
+
+/// Sample rate used for all generated tones
+const SAMPLE_RATE: u32 = 48000;
+
+/// Frequency step between the values of the spectrum
+pub static SPECTRUM_BIN: f32 = SAMPLE_RATE as f32 / SPECTRUM_LEN as f32;
+
+/// Shape of the generated tone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square,
+    Triangle,
+    Sawtooth,
+}
+
+impl Waveform {
+    pub const ALL: [Waveform; 4] = [
+        Waveform::Sine,
+        Waveform::Square,
+        Waveform::Triangle,
+        Waveform::Sawtooth,
+    ];
+
+    /// Build an infinite source of this shape at the given frequency
+    fn source(self, frequency: f32) -> SignalGenerator {
+        let function = match self {
+            Waveform::Sine => Function::Sine,
+            Waveform::Square => Function::Square,
+            Waveform::Triangle => Function::Triangle,
+            Waveform::Sawtooth => Function::Sawtooth,
+        };
+        SignalGenerator::new(SAMPLE_RATE, frequency, function)
+    }
+}
+
+impl fmt::Display for Waveform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Waveform::Sine => write!(f, "Sine"),
+            Waveform::Square => write!(f, "Square"),
+            Waveform::Triangle => write!(f, "Triangle"),
+            Waveform::Sawtooth => write!(f, "Sawtooth"),
+        }
+    }
+}
+
+/// Slowest and fastest fading cycle, in Hz
+const FADING_RATES: (f32, f32) = (0.1, 1.0);
+
+/// Slowly fades the source in and out, like a distant station on the shortwave bands (QSB)
+pub struct Fading<S> {
+    source: S,
+    /// How far the amplitude drops at the bottom of a fade, from 0 to 1
+    depth: f32,
+    /// Speed of the current fading cycle, in Hz
+    rate: f32,
+    /// Position in the current fading cycle, from 0 to 1
+    phase: f32,
+}
+
+impl<S: Source> Fading<S> {
+    pub fn new(source: S, depth: f32) -> Self {
+        Self {
+            source,
+            depth: depth.clamp(0., 1.),
+            rate: random_fading_rate(),
+            phase: 0.,
+        }
+    }
+}
+
+/// Every cycle fades at a different speed so the fading doesn't sound mechanical.
+fn random_fading_rate() -> f32 {
+    FADING_RATES.0 + fastrand::f32() * (FADING_RATES.1 - FADING_RATES.0)
+}
+
+impl<S: Source> Iterator for Fading<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.source.next()?;
+        if self.depth <= 0. {
+            return Some(sample);
+        }
+        let gain = 1. - self.depth * (0.5 - 0.5 * (TAU * self.phase).cos());
+        self.phase +=
+            self.rate / (self.source.sample_rate() * self.source.channels() as u32) as f32;
+        if self.phase >= 1. {
+            self.phase -= 1.;
+            self.rate = random_fading_rate();
+        }
+        Some(sample * gain)
+    }
+}
+
+impl<S: Source> Source for Fading<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+/// Keying state shared between the audio manager and the envelope of the playing source
+#[derive(Debug, Default)]
+struct EnvelopeControl {
+    keyed: AtomicBool,
+    /// Length of the rise and fall ramps, in samples
+    rise: AtomicU32,
+    fall: AtomicU32,
+}
+
+impl EnvelopeControl {
+    fn set_ramp_ms(&self, rise: u32, fall: u32) {
+        let samples = |ms: u32| ms * SAMPLE_RATE / 1000;
+        self.rise.store(samples(rise), Ordering::Relaxed);
+        self.fall.store(samples(fall), Ordering::Relaxed);
+    }
+}
+
+/// Ramps the source in when keyed and out when released, so the tone doesn't click
+pub struct Envelope<S> {
+    source: S,
+    control: Arc<EnvelopeControl>,
+    /// Progress through the ramp, from 0 (silent) to 1 (full)
+    level: f32,
+}
+
+impl<S: Source> Envelope<S> {
+    fn new(source: S, control: Arc<EnvelopeControl>) -> Self {
+        let level = if control.keyed.load(Ordering::Relaxed) {
+            1.
+        } else {
+            0.
+        };
+        Self {
+            source,
+            control,
+            level,
+        }
+    }
+}
+
+impl<S: Source> Iterator for Envelope<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.source.next()?;
+        if self.control.keyed.load(Ordering::Relaxed) {
+            let rise = self.control.rise.load(Ordering::Relaxed).max(1);
+            self.level = (self.level + 1. / rise as f32).min(1.);
+        } else {
+            let fall = self.control.fall.load(Ordering::Relaxed).max(1);
+            self.level = (self.level - 1. / fall as f32).max(0.);
+        }
+        // Raised cosine, which keeps the keying sidebands narrower than a straight line
+        Some(sample * (0.5 - 0.5 * (PI * self.level).cos()))
+    }
+}
+
+impl<S: Source> Source for Envelope<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+/// Blocks of samples the scope scrolls through, about 0.7 s
+const SCOPE_BLOCKS: usize = 512;
+
+/// Samples in a block of the scope
+const SCOPE_BLOCK_LEN: usize = 64;
+
+/// Latest samples drawn to show the shape of the tone, about 10 ms
+const SCOPE_SAMPLES: usize = 512;
+
+/// Recent output of a sink for the oscilloscope and the spectrum, written by the audio thread
+/// without locking.
+/// Both rings hold f32 bits and are only written while the scope is shown.
+#[derive(Debug)]
+struct ScopeControl {
+    enabled: AtomicBool,
+    /// Peak of every block of samples
+    peaks: Vec<AtomicU32>,
+    /// Blocks written so far, the ring position being this modulo its length
+    blocks: AtomicUsize,
+    samples: Vec<AtomicU32>,
+    written: AtomicUsize,
+}
+
+impl Default for ScopeControl {
+    fn default() -> Self {
+        let ring = |len| (0..len).map(|_| AtomicU32::new(0)).collect();
+        Self {
+            enabled: AtomicBool::new(false),
+            peaks: ring(SCOPE_BLOCKS),
+            blocks: AtomicUsize::new(0),
+            samples: ring(SPECTRUM_LEN),
+            written: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl ScopeControl {
+    fn push(ring: &[AtomicU32], count: &AtomicUsize, value: f32) {
+        let index = count.fetch_add(1, Ordering::Relaxed) % ring.len();
+        ring[index].store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Values of the ring, the oldest first
+    fn read(ring: &[AtomicU32], count: &AtomicUsize) -> Vec<f32> {
+        let start = count.load(Ordering::Relaxed) % ring.len();
+        ring[start..]
+            .iter()
+            .chain(&ring[..start])
+            .map(|value| f32::from_bits(value.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    fn clear(&self) {
+        for value in self.peaks.iter().chain(&self.samples) {
+            value.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Copies the samples of a mono source to the scope while it's shown
+pub struct Tap<S> {
+    source: S,
+    control: Arc<ScopeControl>,
+    /// Peak of the block being collected and the samples in it
+    peak: f32,
+    count: usize,
+}
+
+impl<S: Source> Tap<S> {
+    fn new(source: S, control: Arc<ScopeControl>) -> Self {
+        Self {
+            source,
+            control,
+            peak: 0.,
+            count: 0,
+        }
+    }
+}
+
+impl<S: Source> Iterator for Tap<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.source.next()?;
+        let control = &self.control;
+        if control.enabled.load(Ordering::Relaxed) {
+            ScopeControl::push(&control.samples, &control.written, sample);
+            self.peak = self.peak.max(sample.abs());
+            self.count += 1;
+            if self.count == SCOPE_BLOCK_LEN {
+                ScopeControl::push(&control.peaks, &control.blocks, self.peak);
+                self.peak = 0.;
+                self.count = 0;
+            }
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Tap<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+/// Places a mono source between the left and right channel
+pub struct Pan<S> {
+    source: S,
+    /// Gain of the left and right channel
+    gains: [f32; 2],
+    /// Sample sent to the left channel, waiting to be sent to the right one
+    pending: Option<f32>,
+}
+
+impl<S: Source> Pan<S> {
+    /// Pan from -1 (left) to 1 (right). Centered, both channels play
+    /// the source as it is, so the loudness stays that of the mono tone.
+    pub fn new(source: S, pan: f32) -> Self {
+        Self {
+            source,
+            gains: pan_gains(pan),
+            pending: None,
+        }
+    }
+}
+
+/// Gains of the left and right channel at the pan position.
+///
+/// The power of both channels together stays the same across positions, so the tone
+/// doesn't get quieter in the middle. It's scaled to leave the centre at unity.
+fn pan_gains(pan: f32) -> [f32; 2] {
+    let angle = (pan.clamp(-1., 1.) + 1.) * PI / 4.;
+    [angle.cos() * SQRT_2, angle.sin() * SQRT_2]
+}
+
+impl<S: Source> Iterator for Pan<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(sample) = self.pending.take() {
+            return Some(sample * self.gains[1]);
+        }
+        let sample = self.source.next()?;
+        self.pending = Some(sample);
+        Some(sample * self.gains[0])
+    }
+}
+
+impl<S: Source> Source for Pan<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> ChannelCount {
+        2
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+/// Width of the band the noise is limited to, in Hz, about that of a CW filter
+const NOISE_BANDWIDTH: f32 = 600.;
+
+/// Loudness of the interfering signal compared to the tone
+const INTERFERENCE_LEVEL: f32 = 0.6;
+
+/// Endless white noise
+pub struct WhiteNoise {
+    /// Scale of the samples, from 0 to 1
+    amplitude: f32,
+}
+
+impl WhiteNoise {
+    pub fn new(amplitude: f32) -> Self {
+        Self { amplitude }
+    }
+
+    /// Noise limited to a band around the frequency, as heard through a receiver filter.
+    ///
+    /// The noise is amplified to make up for what the filter removes,
+    /// so the noise in the band is about as loud as a full volume tone.
+    fn around(frequency: f32) -> impl Source<Item = f32> + Send + 'static {
+        let gain = (SAMPLE_RATE as f32 / 2. / NOISE_BANDWIDTH).sqrt();
+        let low = (frequency - NOISE_BANDWIDTH / 2.).max(1.) as u32;
+        let high = (frequency + NOISE_BANDWIDTH / 2.) as u32;
+        WhiteNoise::new(gain).high_pass(low).low_pass(high)
+    }
+}
+
+impl Iterator for WhiteNoise {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        Some((fastrand::f32() * 2. - 1.) * self.amplitude)
+    }
+}
+
+impl Source for WhiteNoise {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Pitch of the metronome click, well above any sidetone
+const CLICK_FREQUENCY: f32 = 2500.;
+
+/// How quickly the click dies away, in seconds
+const CLICK_DECAY: f32 = 0.004;
+
+/// Short clicks repeating at a steady interval, for the metronome
+pub struct Clicks {
+    /// Samples between two clicks
+    interval: u32,
+    /// Samples since the last click
+    position: u32,
+}
+
+impl Clicks {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval: ((interval.as_secs_f32() * SAMPLE_RATE as f32) as u32).max(1),
+            position: 0,
+        }
+    }
+}
+
+impl Iterator for Clicks {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let time = self.position as f32 / SAMPLE_RATE as f32;
+        self.position = (self.position + 1) % self.interval;
+        // A decaying burst, silent long before the next click
+        let envelope = (-time / CLICK_DECAY).exp();
+        Some(envelope * (TAU * CLICK_FREQUENCY * time).sin())
+    }
+}
+
+impl Source for Clicks {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> ChannelCount {
+        1
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Longest vibration requested for a tone, cut short when the tone stops
+#[cfg(target_arch = "wasm32")]
+const MAX_VIBRATION_MS: u32 = 10_000;
+
+/// Start or stop vibrating the device, where the browser supports it.
+#[cfg(target_arch = "wasm32")]
+pub fn vibrate(on: bool) {
+    if let Some(window) = eframe::web_sys::window() {
+        // Unsupported browsers ignore the call.
+        window
+            .navigator()
+            .vibrate_with_duration(if on { MAX_VIBRATION_MS } else { 0 });
+    }
+}
+
+/// Only mobile browsers can vibrate.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn vibrate(_on: bool) {}
+
+/// Rise and fall time of the tone until the settings are applied, in milliseconds
+const DEFAULT_RAMP_MS: u32 = 5;
+
+/// Sine of another signal mixed with the tone, ramped in and out like it when keyed.
+fn keyed_sine(
+    frequency: f32,
+    envelope: &Arc<EnvelopeControl>,
+    scope: &Arc<ScopeControl>,
+) -> Tap<Envelope<SignalGenerator>> {
+    Tap::new(
+        Envelope::new(Waveform::Sine.source(frequency), envelope.clone()),
+        scope.clone(),
+    )
+}
+
+/// Simple audio manager for playing periodic tones
 pub struct AudioManager {
     // Keep the stream alive to maintain audio output
     _stream: OutputStream,
     sink: Sink,
+    /// Keys the tone through its envelope, the sink itself keeps playing
+    envelope: Arc<EnvelopeControl>,
+    /// Output of the tone collected for the oscilloscope and the spectrum
+    scope: Arc<ScopeControl>,
+    /// Key the interfering signal and the other station through their own envelopes
+    interference_envelope: Arc<EnvelopeControl>,
+    station_envelope: Arc<EnvelopeControl>,
+    /// Output of the other sinks collected for the spectrum
+    noise_scope: Arc<ScopeControl>,
+    interference_scope: Arc<ScopeControl>,
+    station_scope: Arc<ScopeControl>,
+    /// Length of the ramps of the tone, in milliseconds
+    rise_ms: u32,
+    fall_ms: u32,
+    /// Position of the tone from -1 (left) to 1 (right)
+    pan: f32,
+    /// Background noise, playing continuously while enabled
+    noise_sink: Sink,
+    /// Second station keyed over the tone
+    interference_sink: Sink,
+    /// Clicks to key against, independent of the tone
+    metronome_sink: Sink,
+    /// Other station of a simulated contact, keyed at its own pitch
+    station_sink: Sink,
+    device: Option<String>,
     frequency: f32,
     volume: f32,
+    waveform: Waveform,
+    /// All output is silenced, keeping the volume for when it's unmuted
+    muted: bool,
+    /// Depth of the fading applied to the tone, 0 for a clean signal
+    fading: f32,
+    /// Loudness of the noise compared to the tone, 0 for no noise
+    noise: f32,
+    /// Pitch of the interfering signal
+    interference_frequency: f32,
+    /// Pitch of the other station
+    station_frequency: f32,
+    /// Time between metronome clicks, `None` when it's off
+    metronome: Option<Duration>,
+    /// Loudness of the metronome compared to the tone
+    metronome_level: f32,
     is_playing: bool,
 }
 
 impl AudioManager {
     /// Create a new audio manager with the specified frequency and volume
     pub fn new(frequency: f32, volume: f32) -> Result<Self, String> {
-        // Get default output stream using rodio 0.21 API
-        let mut stream = rodio::OutputStreamBuilder::open_default_stream()
-            .map_err(|e| format!("Failed to create audio output stream: {}", e))?;
+        Self::open(None, frequency, volume)
+    }
 
+    /// Create a new audio manager playing through the output device with the given name
+    pub fn with_device(name: &str, frequency: f32, volume: f32) -> Result<Self, String> {
+        Self::open(Some(name), frequency, volume)
+    }
+
+    /// Names of all output devices available on the default host
+    pub fn list_output_devices() -> Vec<String> {
+        let devices = match rodio::cpal::default_host().output_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                tracing::warn!("Failed to list output devices: {}", e);
+                return Vec::new();
+            }
+        };
+        devices.filter_map(|device| device.name().ok()).collect()
+    }
+
+    fn open(device: Option<&str>, frequency: f32, volume: f32) -> Result<Self, String> {
+        let mut stream = Self::open_stream(device)?;
         stream.log_on_drop(false);
 
         // Create sink connected to the output stream's mixer
         let sink = Sink::connect_new(stream.mixer());
         sink.set_volume(volume);
 
-        // Create initial sine wave
-        let source = SineWave::new(frequency).repeat_infinite();
+        let waveform = Waveform::default();
+        let envelope = Arc::new(EnvelopeControl::default());
+        envelope.set_ramp_ms(DEFAULT_RAMP_MS, DEFAULT_RAMP_MS);
+        let scope = Arc::new(ScopeControl::default());
+        sink.append(Pan::new(
+            Tap::new(
+                Envelope::new(
+                    Fading::new(waveform.source(frequency), 0.),
+                    envelope.clone(),
+                ),
+                scope.clone(),
+            ),
+            0.,
+        ));
+
+        let noise_scope = Arc::new(ScopeControl::default());
+        let noise_sink = Sink::connect_new(stream.mixer());
+        noise_sink.append(Tap::new(WhiteNoise::around(frequency), noise_scope.clone()));
+        noise_sink.pause();
 
-        sink.append(source);
-        sink.pause(); // Start paused
+        let interference_sink = Sink::connect_new(stream.mixer());
+        interference_sink.set_volume(volume * INTERFERENCE_LEVEL);
+        let interference_envelope = Arc::new(EnvelopeControl::default());
+        interference_envelope.set_ramp_ms(DEFAULT_RAMP_MS, DEFAULT_RAMP_MS);
+        let interference_scope = Arc::new(ScopeControl::default());
+        interference_sink.append(keyed_sine(
+            frequency,
+            &interference_envelope,
+            &interference_scope,
+        ));
+
+        let metronome_sink = Sink::connect_new(stream.mixer());
+        metronome_sink.append(Clicks::new(Duration::from_secs(1)));
+        metronome_sink.pause();
+
+        let station_sink = Sink::connect_new(stream.mixer());
+        station_sink.set_volume(volume);
+        let station_envelope = Arc::new(EnvelopeControl::default());
+        station_envelope.set_ramp_ms(DEFAULT_RAMP_MS, DEFAULT_RAMP_MS);
+        let station_scope = Arc::new(ScopeControl::default());
+        station_sink.append(keyed_sine(frequency, &station_envelope, &station_scope));
 
         Ok(AudioManager {
             _stream: stream,
             sink,
+            envelope,
+            scope,
+            interference_envelope,
+            station_envelope,
+            noise_scope,
+            interference_scope,
+            station_scope,
+            rise_ms: DEFAULT_RAMP_MS,
+            fall_ms: DEFAULT_RAMP_MS,
+            pan: 0.,
+            noise_sink,
+            interference_sink,
+            metronome_sink,
+            station_sink,
+            device: device.map(str::to_string),
             frequency,
             volume,
+            waveform,
+            muted: false,
+            fading: 0.,
+            noise: 0.,
+            interference_frequency: frequency,
+            station_frequency: frequency,
+            metronome: None,
+            metronome_level: 0.,
             is_playing: false,
         })
     }
 
+    /// Open an output stream for the named device, or the default one if no name is given
+    fn open_stream(device: Option<&str>) -> Result<OutputStream, String> {
+        let Some(name) = device else {
+            // Get default output stream using rodio 0.21 API
+            return rodio::OutputStreamBuilder::open_default_stream()
+                .map_err(|e| format!("Failed to create audio output stream: {}", e));
+        };
+
+        let device = rodio::cpal::default_host()
+            .output_devices()
+            .map_err(|e| format!("Failed to list output devices: {}", e))?
+            .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+            .ok_or_else(|| format!("Output device \"{}\" is not available", name))?;
+
+        rodio::OutputStreamBuilder::from_device(device)
+            .and_then(|builder| builder.open_stream())
+            .map_err(|e| format!("Failed to open output device \"{}\": {}", name, e))
+    }
+
+    /// Switch to another output device, keeping frequency, volume,
+    /// waveform and playing state. On failure the current device stays in use.
+    pub fn set_device(&mut self, device: Option<&str>) -> Result<(), String> {
+        if self.device.as_deref() == device {
+            return Ok(());
+        }
+        tracing::debug!("Switching output device to {:?}", device);
+
+        let mut replacement = match device {
+            Some(name) => Self::with_device(name, self.frequency, self.volume)?,
+            None => Self::new(self.frequency, self.volume)?,
+        };
+        replacement.set_waveform(self.waveform);
+        replacement.set_muted(self.muted);
+        replacement.set_ramp_ms(self.rise_ms, self.fall_ms);
+        replacement.set_pan(self.pan);
+        replacement.set_fading(self.fading);
+        replacement.set_noise(self.noise);
+        replacement.set_interference_frequency(self.interference_frequency);
+        replacement.set_station_frequency(self.station_frequency);
+        replacement.set_metronome(self.metronome, self.metronome_level);
+        if self.is_playing {
+            replacement.play();
+        }
+        *self = replacement;
+        Ok(())
+    }
+
     /// Start playing the tone
     pub fn play(&mut self) {
         if !self.is_playing {
-            self.sink.play();
+            self.envelope.keyed.store(true, Ordering::Relaxed);
             self.is_playing = true;
         }
     }
@@ -51,12 +732,92 @@ impl AudioManager {
     /// Stop playing the tone
     pub fn pause(&mut self) {
         if self.is_playing {
-            self.sink.pause();
+            self.envelope.keyed.store(false, Ordering::Relaxed);
             self.is_playing = false;
         }
     }
 
-    /// Update the frequency of the sine wave
+    /// Replace the source feeding the sink with a fresh one
+    /// built from the current frequency, waveform, fading and pan.
+    fn replace_source(&mut self) {
+        self.sink.append(Pan::new(
+            Tap::new(
+                Envelope::new(
+                    Fading::new(self.waveform.source(self.frequency), self.fading),
+                    self.envelope.clone(),
+                ),
+                self.scope.clone(),
+            ),
+            self.pan,
+        ));
+        self.sink.skip_one();
+    }
+
+    /// Collect the output for the oscilloscope and the spectrum, or stop doing so
+    pub fn set_scope(&self, enabled: bool) {
+        let scopes = [
+            &self.scope,
+            &self.noise_scope,
+            &self.interference_scope,
+            &self.station_scope,
+        ];
+        for scope in scopes {
+            if scope.enabled.swap(enabled, Ordering::Relaxed) && !enabled {
+                // Start from a flat line when shown again
+                scope.clear();
+            }
+        }
+    }
+
+    /// Peaks of the tone over the last moment, the oldest first
+    pub fn scope_peaks(&self) -> Vec<f32> {
+        ScopeControl::read(&self.scope.peaks, &self.scope.blocks)
+    }
+
+    /// Latest samples of the tone, the oldest first
+    pub fn scope_samples(&self) -> Vec<f32> {
+        let samples = ScopeControl::read(&self.scope.samples, &self.scope.written);
+        samples[samples.len() - SCOPE_SAMPLES..].to_vec()
+    }
+
+    /// Power of the recent output at every step of `SPECTRUM_BIN`, up to half the sample rate.
+    ///
+    /// The sinks are analysed separately, ignoring the volume, and their power added up
+    /// at the level they're mixed at. The noise sink is paused while it's off and still holds
+    /// its last samples, so it's left out then.
+    pub fn spectrum(&self) -> Vec<f32> {
+        let sources = [
+            (&self.scope, 1.),
+            (&self.noise_scope, self.noise),
+            (&self.interference_scope, INTERFERENCE_LEVEL),
+            (&self.station_scope, 1.),
+        ];
+        let mut total = vec![0.; SPECTRUM_LEN / 2];
+        for (scope, level) in sources {
+            if level <= 0. {
+                continue;
+            }
+            let samples = ScopeControl::read(&scope.samples, &scope.written);
+            for (total, power) in total.iter_mut().zip(power_spectrum(&samples)) {
+                *total += power * level * level;
+            }
+        }
+        total
+    }
+
+    /// Update how long the tone takes to rise when keyed and to fall when released
+    pub fn set_ramp_ms(&mut self, rise: u32, fall: u32) {
+        if (self.rise_ms, self.fall_ms) == (rise, fall) {
+            return;
+        }
+        self.rise_ms = rise;
+        self.fall_ms = fall;
+        self.envelope.set_ramp_ms(rise, fall);
+        self.interference_envelope.set_ramp_ms(rise, fall);
+        self.station_envelope.set_ramp_ms(rise, fall);
+    }
+
+    /// Update the frequency of the tone
     pub fn set_frequency(&mut self, frequency: f32) {
         if (self.frequency - frequency).abs() < 0.1 {
             return; // No significant change
@@ -64,15 +825,171 @@ impl AudioManager {
         tracing::debug!("Updating frequency to {}", frequency);
 
         self.frequency = frequency;
-        self.sink.append(SineWave::new(frequency));
-        self.sink.skip_one();
+        self.replace_source();
+        // Keep the noise centered on the tone.
+        self.noise_sink.append(Tap::new(
+            WhiteNoise::around(frequency),
+            self.noise_scope.clone(),
+        ));
+        self.noise_sink.skip_one();
+    }
+
+    /// Update the shape of the tone
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        if self.waveform == waveform {
+            return;
+        }
+        tracing::debug!("Updating waveform to {}", waveform);
+
+        self.waveform = waveform;
+        self.replace_source();
+    }
 
-        // Note: Frequency changes require recreating the source, which isn't supported here
+    /// Update the fading depth, 0 turning it off
+    pub fn set_fading(&mut self, depth: f32) {
+        if (self.fading - depth).abs() < 0.01 {
+            return;
+        }
+        tracing::debug!("Updating fading depth to {}", depth);
+
+        self.fading = depth;
+        self.replace_source();
+    }
+
+    /// Update the position of the tone, from -1 (left) to 1 (right)
+    pub fn set_pan(&mut self, pan: f32) {
+        if (self.pan - pan).abs() < 0.01 {
+            return;
+        }
+        tracing::debug!("Updating pan to {}", pan);
+
+        self.pan = pan;
+        self.replace_source();
     }
 
     /// Update the volume
     pub fn set_volume(&mut self, volume: f32) {
         self.volume = volume;
+        self.apply_volume();
+    }
+
+    /// Set the volume of every sink, silencing them while muted
+    fn apply_volume(&mut self) {
+        let volume = if self.muted { 0. } else { self.volume };
         self.sink.set_volume(volume);
+        self.noise_sink.set_volume(volume * self.noise);
+        self.interference_sink
+            .set_volume(volume * INTERFERENCE_LEVEL);
+        self.metronome_sink
+            .set_volume(volume * self.metronome_level);
+        self.station_sink.set_volume(volume);
+    }
+
+    /// Silence or restore all output without losing the volume
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.apply_volume();
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Update the loudness of the background noise compared to the tone, 0 turning it off
+    pub fn set_noise(&mut self, level: f32) {
+        if self.noise == level {
+            return;
+        }
+        self.noise = level;
+        self.apply_volume();
+        if level > 0. {
+            self.noise_sink.play();
+        } else {
+            self.noise_sink.pause();
+        }
+    }
+
+    /// Click every `interval` at a level relative to the tone, `None` turning the metronome off
+    pub fn set_metronome(&mut self, interval: Option<Duration>, level: f32) {
+        if self.metronome_level != level {
+            self.metronome_level = level;
+            self.apply_volume();
+        }
+        if self.metronome == interval {
+            return;
+        }
+        self.metronome = interval;
+        match interval {
+            Some(interval) => {
+                // Restarting the clicks also lines them up with the change.
+                self.metronome_sink.append(Clicks::new(interval));
+                self.metronome_sink.skip_one();
+                self.metronome_sink.play();
+            }
+            None => self.metronome_sink.pause(),
+        }
+    }
+
+    /// Update the pitch of the interfering signal
+    pub fn set_interference_frequency(&mut self, frequency: f32) {
+        if (self.interference_frequency - frequency).abs() < 0.1 {
+            return;
+        }
+        self.interference_frequency = frequency;
+        self.interference_sink.append(keyed_sine(
+            frequency,
+            &self.interference_envelope,
+            &self.interference_scope,
+        ));
+        self.interference_sink.skip_one();
+    }
+
+    /// Start or stop the tone of the interfering signal
+    pub fn key_interference(&mut self, on: bool) {
+        self.interference_envelope
+            .keyed
+            .store(on, Ordering::Relaxed);
+    }
+
+    /// Update the pitch of the other station
+    pub fn set_station_frequency(&mut self, frequency: f32) {
+        if (self.station_frequency - frequency).abs() < 0.1 {
+            return;
+        }
+        self.station_frequency = frequency;
+        self.station_sink.append(keyed_sine(
+            frequency,
+            &self.station_envelope,
+            &self.station_scope,
+        ));
+        self.station_sink.skip_one();
+    }
+
+    /// Start or stop the tone of the other station
+    pub fn key_station(&mut self, on: bool) {
+        self.station_envelope.keyed.store(on, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pan_keeps_the_power_constant() {
+        for gain in pan_gains(0.) {
+            assert!((gain - 1.).abs() < 1e-6, "centre gain is {}", gain);
+        }
+        for step in -10..=10 {
+            let pan = step as f32 / 10.;
+            let [left, right] = pan_gains(pan);
+            assert!(
+                (left * left + right * right - 2.).abs() < 1e-5,
+                "power changes at {}",
+                pan
+            );
+        }
+        assert!(pan_gains(-1.)[1].abs() < 1e-6);
+        assert!(pan_gains(1.)[0].abs() < 1e-6);
     }
 }
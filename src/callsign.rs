@@ -0,0 +1,171 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Part of the world a callsign prefix is issued in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Region {
+    NorthAmerica,
+    SouthAmerica,
+    Europe,
+    Africa,
+    Asia,
+    Oceania,
+}
+
+impl Region {
+    pub const ALL: [Region; 6] = [
+        Region::NorthAmerica,
+        Region::SouthAmerica,
+        Region::Europe,
+        Region::Africa,
+        Region::Asia,
+        Region::Oceania,
+    ];
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Region::NorthAmerica => write!(f, "North America"),
+            Region::SouthAmerica => write!(f, "South America"),
+            Region::Europe => write!(f, "Europe"),
+            Region::Africa => write!(f, "Africa"),
+            Region::Asia => write!(f, "Asia"),
+            Region::Oceania => write!(f, "Oceania"),
+        }
+    }
+}
+
+/// Common amateur prefixes, the call area digit follows them
+pub const PREFIXES: [(&str, Region); 48] = [
+    ("K", Region::NorthAmerica),
+    ("W", Region::NorthAmerica),
+    ("N", Region::NorthAmerica),
+    ("AA", Region::NorthAmerica),
+    ("KD", Region::NorthAmerica),
+    ("WA", Region::NorthAmerica),
+    ("VE", Region::NorthAmerica),
+    ("VA", Region::NorthAmerica),
+    ("XE", Region::NorthAmerica),
+    ("PY", Region::SouthAmerica),
+    ("PU", Region::SouthAmerica),
+    ("LU", Region::SouthAmerica),
+    ("CE", Region::SouthAmerica),
+    ("CX", Region::SouthAmerica),
+    ("HK", Region::SouthAmerica),
+    ("OA", Region::SouthAmerica),
+    ("YV", Region::SouthAmerica),
+    ("G", Region::Europe),
+    ("M", Region::Europe),
+    ("F", Region::Europe),
+    ("DL", Region::Europe),
+    ("DJ", Region::Europe),
+    ("I", Region::Europe),
+    ("EA", Region::Europe),
+    ("PA", Region::Europe),
+    ("ON", Region::Europe),
+    ("OK", Region::Europe),
+    ("SP", Region::Europe),
+    ("OH", Region::Europe),
+    ("SM", Region::Europe),
+    ("LA", Region::Europe),
+    ("OZ", Region::Europe),
+    ("UA", Region::Europe),
+    ("ZS", Region::Africa),
+    ("5Z", Region::Africa),
+    ("5N", Region::Africa),
+    ("CN", Region::Africa),
+    ("SU", Region::Africa),
+    ("9J", Region::Africa),
+    ("JA", Region::Asia),
+    ("JH", Region::Asia),
+    ("BY", Region::Asia),
+    ("HL", Region::Asia),
+    ("VU", Region::Asia),
+    ("DU", Region::Asia),
+    ("VK", Region::Oceania),
+    ("ZL", Region::Oceania),
+    ("YB", Region::Oceania),
+];
+
+/// Build a plausible callsign with a prefix from one of the regions, or any region if none given.
+///
+/// Only characters from the pool are used. Returns `None` if no callsign can be
+/// built from them.
+pub fn generate_callsign(regions: &[Region], pool: &[char]) -> Option<String> {
+    let prefixes = PREFIXES
+        .iter()
+        .filter(|(_, region)| regions.is_empty() || regions.contains(region))
+        .filter(|(prefix, _)| prefix.chars().all(|ch| pool.contains(&ch)))
+        .map(|(prefix, _)| *prefix)
+        .collect::<Vec<_>>();
+    let digits = pool
+        .iter()
+        .copied()
+        .filter(char::is_ascii_digit)
+        .collect::<Vec<_>>();
+    let letters = pool
+        .iter()
+        .copied()
+        .filter(|ch| ch.is_ascii_uppercase())
+        .collect::<Vec<_>>();
+    if prefixes.is_empty() || digits.is_empty() || letters.is_empty() {
+        return None;
+    }
+
+    let mut callsign = prefixes[fastrand::usize(..prefixes.len())].to_string();
+    callsign.push(digits[fastrand::usize(..digits.len())]);
+    // Longer suffixes are the most common.
+    let suffix = match fastrand::u8(..10) {
+        0 => 1,
+        1..=3 => 2,
+        _ => 3,
+    };
+    for _ in 0..suffix {
+        callsign.push(letters[fastrand::usize(..letters.len())]);
+    }
+    Some(callsign)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use morset::decoder::decode_text;
+    use morset::encoder::{Notation, encode};
+    use morset::utils::CharacterTable;
+
+    #[test]
+    fn generated_callsigns_round_trip() {
+        let pool = ('A'..='Z').chain('0'..='9').collect::<Vec<_>>();
+        let notation = Notation::default();
+        let characters = CharacterTable::default();
+        for regions in std::iter::once(&[][..]).chain(Region::ALL.chunks(1)) {
+            for _ in 0..200 {
+                let callsign = generate_callsign(regions, &pool).unwrap();
+                let morse = encode(&callsign, &notation, false, &characters).unwrap();
+                assert_eq!(
+                    decode_text(&morse, &notation, "#", &characters),
+                    callsign,
+                    "{} sent as {}",
+                    callsign,
+                    morse
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn callsigns_only_use_the_pool() {
+        let pool = ['K', 'W', 'A', 'B', '1', '2'];
+        for _ in 0..100 {
+            let callsign = generate_callsign(&[Region::NorthAmerica], &pool).unwrap();
+            assert!(
+                callsign.chars().all(|ch| pool.contains(&ch)),
+                "{} isn't built from the pool",
+                callsign
+            );
+        }
+        assert_eq!(generate_callsign(&[Region::Europe], &pool), None);
+    }
+}
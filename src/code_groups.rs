@@ -0,0 +1,110 @@
+use std::fmt;
+
+use morset::consts::{Alphabet, NUMBERS};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    drill::Grade,
+    text_source::{CODE_GROUP_LENGTH, random_characters},
+};
+
+pub static MAX_CODE_GROUPS: usize = 50;
+pub static MIN_CODE_GROUPS: usize = 1;
+
+/// Characters code groups are made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GroupCharacters {
+    #[default]
+    Letters,
+    Digits,
+    Mixed,
+}
+
+impl GroupCharacters {
+    pub const ALL: [GroupCharacters; 3] = [
+        GroupCharacters::Letters,
+        GroupCharacters::Digits,
+        GroupCharacters::Mixed,
+    ];
+
+    /// Characters the groups are made of, with the letters of the alphabet.
+    pub fn pool(self, alphabet: Alphabet) -> Vec<char> {
+        let letters = alphabet.letters().iter();
+        let digits = NUMBERS.iter();
+        match self {
+            GroupCharacters::Letters => letters.map(|(ch, _)| *ch).collect(),
+            GroupCharacters::Digits => digits.map(|(ch, _)| *ch).collect(),
+            GroupCharacters::Mixed => letters.chain(digits).map(|(ch, _)| *ch).collect(),
+        }
+    }
+}
+
+impl fmt::Display for GroupCharacters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupCharacters::Letters => write!(f, "Letters"),
+            GroupCharacters::Digits => write!(f, "Digits"),
+            GroupCharacters::Mixed => write!(f, "Mixed"),
+        }
+    }
+}
+
+/// Group that was sent with what the user copied for it
+pub struct GroupResult {
+    pub group: String,
+    pub copy: String,
+    pub grade: Grade,
+}
+
+/// Sends random groups of characters and grades the copy group by group
+pub struct CodeGroupDrill {
+    /// Groups of the run waiting to be copied
+    groups: Vec<String>,
+    /// Every group of the last run with its copy
+    results: Vec<GroupResult>,
+}
+
+impl CodeGroupDrill {
+    pub fn new() -> Self {
+        Self {
+            groups: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Whether a run was sent and is waiting to be copied.
+    pub fn is_running(&self) -> bool {
+        !self.groups.is_empty()
+    }
+
+    /// Start a run of random groups of the characters.
+    pub fn start(&mut self, count: usize, pool: &[char]) {
+        self.groups = (0..count.clamp(MIN_CODE_GROUPS, MAX_CODE_GROUPS))
+            .map(|_| random_characters(pool, CODE_GROUP_LENGTH))
+            .collect();
+        self.results.clear();
+    }
+
+    /// Groups of the run separated by spaces.
+    pub fn text(&self) -> String {
+        self.groups.join(" ")
+    }
+
+    /// Grade the copy, a group per word, ending the run.
+    pub fn submit(&mut self, copy: &str) -> &[GroupResult] {
+        let mut copied = copy.split_whitespace();
+        self.results = std::mem::take(&mut self.groups)
+            .into_iter()
+            .map(|group| {
+                let copy = copied.next().unwrap_or_default().to_uppercase();
+                let grade = Grade::new(&group, &copy, None);
+                GroupResult { group, copy, grade }
+            })
+            .collect();
+        &self.results
+    }
+
+    pub fn results(&self) -> &[GroupResult] {
+        &self.results
+    }
+}
@@ -0,0 +1,191 @@
+use std::io::Read;
+
+use morset::{
+    decoder,
+    encoder::{self, Notation},
+    utils::{CharacterTable, cut_numbers, uncut_numbers, wpm_to_dit_duration},
+};
+
+use crate::args::{Command, DecodeArgs, EncodeArgs, NotationArgs};
+
+/// Run a command line command, printing its result to stdout.
+pub fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Encode(args) => encode(args),
+        Command::Decode(args) => decode(args),
+    }
+}
+
+/// Take the input from the argument or, if it's missing, from stdin.
+fn read_input(input: Option<String>) -> Result<String, String> {
+    if let Some(input) = input {
+        return Ok(input);
+    }
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| format!("Failed to read stdin: {}", e))?;
+    Ok(input)
+}
+
+impl TryFrom<NotationArgs> for Notation {
+    type Error = String;
+
+    fn try_from(args: NotationArgs) -> Result<Self, Self::Error> {
+        if args.dit.is_empty() || args.dah.is_empty() {
+            return Err("The dit and dah symbols can't be empty".to_string());
+        }
+        if args.dit == args.dah {
+            return Err("The dit and dah symbols must differ".to_string());
+        }
+        Ok(Self {
+            dit: args.dit,
+            dah: args.dah,
+            element_separator: args.element_separator,
+            character_separator: args.character_separator,
+            word_separator: args.word_separator,
+        })
+    }
+}
+
+fn encode(args: EncodeArgs) -> Result<(), String> {
+    let notation = Notation::try_from(args.notation)?;
+    let mut text = read_input(args.text)?;
+    if args.cut_numbers {
+        text = cut_numbers(&text);
+    }
+    let characters = CharacterTable::default();
+    let unknown = |ch| format!("Character {:?} has no morse code", ch);
+
+    let encoded =
+        encoder::encode(&text, &notation, args.skip_unknown, &characters).map_err(unknown)?;
+    println!("{}", encoded);
+
+    if let Some(wpm) = args.wpm {
+        let dit_duration = wpm_to_dit_duration(wpm);
+        let weight = args.weight as f32 * 0.01;
+        for timing in encoder::timings(&text, args.skip_unknown, &characters).map_err(unknown)? {
+            let state = if timing.on { "on" } else { "off" };
+            println!(
+                "{} {}",
+                state,
+                timing.duration(dit_duration, weight).as_millis()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn decode(args: DecodeArgs) -> Result<(), String> {
+    let notation = Notation::try_from(args.notation)?;
+    let morse = read_input(args.morse)?;
+    println!(
+        "{}",
+        decoded_text(&morse, &notation, &args.placeholder, args.cut_numbers)
+    );
+    Ok(())
+}
+
+/// Text printed by the decode command.
+fn decoded_text(morse: &str, notation: &Notation, placeholder: &str, cut_numbers: bool) -> String {
+    let text = decoder::decode_text(morse, notation, placeholder, &CharacterTable::default());
+    if cut_numbers {
+        uncut_numbers(&text)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::Args;
+
+    /// Arguments of `morset decode` for the options and the morse after them.
+    fn parse_decode(options: &[&str], morse: &str) -> DecodeArgs {
+        let args = ["morset", "decode"]
+            .into_iter()
+            .chain(options.iter().copied())
+            .chain(["--", morse]);
+        let Some(Command::Decode(args)) = Args::try_parse_from(args).unwrap().command else {
+            panic!("not a decode command");
+        };
+        args
+    }
+
+    /// Text printed by `morset decode` for the options and the morse after them.
+    fn run_decode(options: &[&str], morse: &str) -> String {
+        let args = parse_decode(options, morse);
+        let notation = Notation::try_from(args.notation).unwrap();
+        decoded_text(
+            args.morse.as_deref().unwrap_or_default(),
+            &notation,
+            &args.placeholder,
+            args.cut_numbers,
+        )
+    }
+
+    #[test]
+    fn decodes_words() {
+        assert_eq!(
+            run_decode(&[], ".... . .-.. .-.. --- / .-- --- .-. .-.. -.."),
+            "HELLO WORLD"
+        );
+    }
+
+    #[test]
+    fn decodes_punctuation() {
+        assert_eq!(
+            run_decode(&[], "-.-. --.- --..-- / -.. . / ..--.. / .-.-.-"),
+            "CQ, DE ? ."
+        );
+        assert_eq!(run_decode(&[], ".-- ..--- -..-. .-.."), "W2/L");
+    }
+
+    #[test]
+    fn decodes_prosigns() {
+        assert_eq!(
+            run_decode(&[], "-.-. --.- / -.-.- / ...-.- / ...---..."),
+            "CQ <CT> <SK> SOS"
+        );
+    }
+
+    #[test]
+    fn decodes_with_the_notation_and_placeholder() {
+        let options = [
+            "--dit",
+            ".",
+            "--dah",
+            "_",
+            "--word-separator",
+            "|",
+            "--placeholder",
+            "#",
+        ];
+        assert_eq!(run_decode(&options, "._ __. | ..__..__"), "AG #");
+    }
+
+    #[test]
+    fn decodes_cut_numbers_on_request() {
+        assert_eq!(run_decode(&[], "..... -. -."), "5NN");
+        assert_eq!(run_decode(&["--cut-numbers"], "..... -. -."), "599");
+    }
+
+    #[test]
+    fn rejects_empty_or_equal_symbols() {
+        for options in [
+            ["--dit", "", "--dah", "-"],
+            ["--dit", ".", "--dah", ""],
+            ["--dit", "x", "--dah", "x"],
+        ] {
+            let args = parse_decode(&options, ".-");
+            assert!(
+                Notation::try_from(args.notation).is_err(),
+                "{:?} should be rejected",
+                options
+            );
+        }
+    }
+}
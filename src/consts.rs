@@ -1,5 +1,7 @@
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
 pub const ABC: [(char, &str); 26] = [
     ('A', ".-"),
     ('B', "-..."),
@@ -35,7 +37,7 @@ pub const NUMBERS: [(char, &str); 10] = [
     ('3', "...--"),
     ('4', "....-"),
     ('5', "....."),
-    ('6', "_...."),
+    ('6', "-...."),
     ('7', "--..."),
     ('8', "---.."),
     ('9', "----."),
@@ -56,9 +58,306 @@ pub const SIGNS: [(char, &str); 11] = [
     ('&', ".-..."),
 ];
 
+/// Digits with the letters sent in their place in cut numbers, like 5NN for 599.
+/// Cutting is ambiguous, so it's only done on request.
+pub const CUT_NUMBERS: [(char, char); 8] = [
+    ('0', 'T'),
+    ('1', 'A'),
+    ('2', 'U'),
+    ('3', 'V'),
+    ('5', 'E'),
+    ('7', 'B'),
+    ('8', 'D'),
+    ('9', 'N'),
+];
+
+/// Common abbreviations and Q-codes with their meaning and their sequences,
+/// characters separated by spaces. They are for reference only and aren't decoded.
+pub const ABBREVIATIONS: [(&str, &str, &str); 28] = [
+    ("CQ", "Calling any station", "-.-. --.-"),
+    ("DE", "From, this is", "-.. ."),
+    ("K", "Go ahead", "-.-"),
+    ("73", "Best regards", "--... ...--"),
+    ("88", "Love and kisses", "---.. ---.."),
+    ("QTH", "My location is", "--.- - ...."),
+    ("QRZ", "Who is calling me?", "--.- .-. --.."),
+    ("QSL", "I acknowledge receipt", "--.- ... .-.."),
+    ("QRM", "Interference", "--.- .-. --"),
+    ("QRN", "Static noise", "--.- .-. -."),
+    ("QRS", "Send more slowly", "--.- .-. ..."),
+    ("QRQ", "Send faster", "--.- .-. --.-"),
+    ("QSB", "Your signal is fading", "--.- ... -..."),
+    ("QSY", "Change frequency", "--.- ... -.--"),
+    ("QRT", "Stop sending", "--.- .-. -"),
+    ("QRP", "Low power", "--.- .-. .--."),
+    ("RST", "Signal report", ".-. ... -"),
+    ("UR", "Your", "..- .-."),
+    ("TNX", "Thanks", "- -. -..-"),
+    ("FB", "Fine business, excellent", "..-. -..."),
+    ("OM", "Old man, fellow operator", "--- --"),
+    ("WX", "Weather", ".-- -..-"),
+    ("HW", "How do you copy?", ".... .--"),
+    ("BK", "Break", "-... -.-"),
+    ("ES", "And", ". ..."),
+    ("GM", "Good morning", "--. --"),
+    ("GE", "Good evening", "--. ."),
+    ("CUL", "See you later", "-.-. ..- .-.."),
+];
+
+/// Common words, abbreviations and phrases heard on the air, for drills
+pub const WORD_LIST: [&str; 169] = [
+    "THE",
+    "AND",
+    "FOR",
+    "ARE",
+    "BUT",
+    "NOT",
+    "YOU",
+    "ALL",
+    "ANY",
+    "CAN",
+    "HER",
+    "WAS",
+    "ONE",
+    "OUR",
+    "OUT",
+    "DAY",
+    "GET",
+    "HAS",
+    "HIM",
+    "HIS",
+    "HOW",
+    "MAN",
+    "NEW",
+    "NOW",
+    "OLD",
+    "SEE",
+    "TWO",
+    "WAY",
+    "WHO",
+    "BOY",
+    "DID",
+    "ITS",
+    "LET",
+    "PUT",
+    "SAY",
+    "SHE",
+    "TOO",
+    "USE",
+    "THAT",
+    "WITH",
+    "HAVE",
+    "THIS",
+    "WILL",
+    "YOUR",
+    "FROM",
+    "THEY",
+    "KNOW",
+    "WANT",
+    "BEEN",
+    "GOOD",
+    "MUCH",
+    "SOME",
+    "TIME",
+    "VERY",
+    "WHEN",
+    "COME",
+    "HERE",
+    "JUST",
+    "LIKE",
+    "LONG",
+    "MAKE",
+    "MANY",
+    "OVER",
+    "SUCH",
+    "TAKE",
+    "THAN",
+    "THEM",
+    "WELL",
+    "WERE",
+    "ABOUT",
+    "AFTER",
+    "AGAIN",
+    "COULD",
+    "EVERY",
+    "FIRST",
+    "FOUND",
+    "GREAT",
+    "HOUSE",
+    "LARGE",
+    "LEARN",
+    "NEVER",
+    "OTHER",
+    "PLACE",
+    "PLANT",
+    "POINT",
+    "RIGHT",
+    "SMALL",
+    "SOUND",
+    "SPELL",
+    "STILL",
+    "STUDY",
+    "THEIR",
+    "THERE",
+    "THESE",
+    "THING",
+    "THINK",
+    "THREE",
+    "WATER",
+    "WHERE",
+    "WHICH",
+    "WORLD",
+    "WOULD",
+    "WRITE",
+    "CQ",
+    "DE",
+    "TEST",
+    "NAME",
+    "RST",
+    "QTH",
+    "RIG",
+    "ANT",
+    "WX",
+    "HELLO",
+    "MORSE",
+    "CODE",
+    "RADIO",
+    "PARIS",
+    "KEY",
+    "SEND",
+    "COPY",
+    "ROGER",
+    "SOLID",
+    "SIGNAL",
+    "POWER",
+    "BAND",
+    "CALL",
+    "REPORT",
+    "STATION",
+    "ANTENNA",
+    "DIPOLE",
+    "LICENSE",
+    "TNX",
+    "FB",
+    "OM",
+    "UR",
+    "HR",
+    "ES",
+    "GM",
+    "GE",
+    "GA",
+    "CUL",
+    "AGN",
+    "PSE",
+    "QSL",
+    "QRZ",
+    "QRM",
+    "QRN",
+    "QSB",
+    "QSY",
+    "QRT",
+    "QRP",
+    "73",
+    "88",
+    "599",
+    "5NN",
+    "CQ CQ CQ",
+    "TNX FER CALL",
+    "UR RST 599",
+    "NAME HR IS",
+    "QTH IS",
+    "RIG HR IS",
+    "WX HR IS",
+    "HW CPY",
+    "TNX FER QSO",
+    "73 ES GL",
+    "GUD DX",
+    "PSE QSL",
+    "QRZ DE",
+    "CUL OM",
+];
+
+/// Russian letters, used instead of the latin ones when the Cyrillic alphabet is active
+pub const CYRILLIC: [(char, &str); 32] = [
+    ('А', ".-"),
+    ('Б', "-..."),
+    ('В', ".--"),
+    ('Г', "--."),
+    ('Д', "-.."),
+    ('Е', "."),
+    ('Ж', "...-"),
+    ('З', "--.."),
+    ('И', ".."),
+    ('Й', ".---"),
+    ('К', "-.-"),
+    ('Л', ".-.."),
+    ('М', "--"),
+    ('Н', "-."),
+    ('О', "---"),
+    ('П', ".--."),
+    ('Р', ".-."),
+    ('С', "..."),
+    ('Т', "-"),
+    ('У', "..-"),
+    ('Ф', "..-."),
+    ('Х', "...."),
+    ('Ц', "-.-."),
+    ('Ч', "---."),
+    ('Ш', "----"),
+    ('Щ', "--.-"),
+    ('Ъ', "--.--"),
+    ('Ы', "-.--"),
+    ('Ь', "-..-"),
+    ('Э', "..-.."),
+    ('Ю', "..--"),
+    ('Я', ".-.-"),
+];
+
+/// Set of letters used for encoding and decoding.
+/// Only one is active at a time, since their sequences overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Alphabet {
+    #[default]
+    Latin,
+    Cyrillic,
+}
+
+impl Alphabet {
+    pub const ALL: [Alphabet; 2] = [Alphabet::Latin, Alphabet::Cyrillic];
+
+    pub fn letters(self) -> &'static [(char, &'static str)] {
+        match self {
+            Alphabet::Latin => &ABC,
+            Alphabet::Cyrillic => &CYRILLIC,
+        }
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alphabet::Latin => write!(f, "Latin"),
+            Alphabet::Cyrillic => write!(f, "Cyrillic"),
+        }
+    }
+}
+
+/// Accented letters used by operators in many countries.
+/// Ä shares its sequence with <AA>, which is decoded instead.
+pub const EXTENDED: [(char, &str); 8] = [
+    ('À', ".--.-"),
+    ('Ä', ".-.-"),
+    ('Ç', "-.-.."),
+    ('È', ".-..-"),
+    ('É', "..-.."),
+    ('Ñ', "--.--"),
+    ('Ö', "---."),
+    ('Ü', "..--"),
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[allow(clippy::upper_case_acronyms)]
-pub(crate) enum ProSign {
+pub enum ProSign {
     AA,  // New line
     AR,  // End of message
     CT,  // Start copying
@@ -71,6 +370,24 @@ pub(crate) enum ProSign {
     ERR, // Errorneous Transmission
 }
 
+impl ProSign {
+    /// Short notation of the prosign, without its meaning
+    pub fn tag(&self) -> &'static str {
+        match self {
+            ProSign::AA => "<AA>",
+            ProSign::AR => "<AR>",
+            ProSign::CT => "<CT>",
+            ProSign::DO => "<DO>",
+            ProSign::KA => "<KA>",
+            ProSign::KN => "<KN>",
+            ProSign::SK => "<SK>",
+            ProSign::SN => "<SN>",
+            ProSign::SOS => "SOS",
+            ProSign::ERR => "<ERR>",
+        }
+    }
+}
+
 impl fmt::Display for ProSign {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -100,3 +417,103 @@ pub const PROSIGNS: [(ProSign, &str); 10] = [
     (ProSign::SOS, "...---..."),
     (ProSign::ERR, "........"),
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::decode_text;
+    use crate::decoder::{Decoded, Decoder};
+    use crate::encoder::{Notation, encode};
+    use crate::utils::CharacterTable;
+
+    #[test]
+    fn extended_letters_leave_ascii_decoding_unchanged() {
+        let table = CharacterTable::default();
+        let ascii = ABC.iter().chain(&NUMBERS).chain(&SIGNS);
+        let before = ascii
+            .clone()
+            .map(|(_, seq)| table.morse_to_char(seq))
+            .collect::<Vec<_>>();
+        let sentence = encode("CQ DE W1AW/3, 599?", &Notation::default(), false, &table).unwrap();
+        let decoded = decode_text(&sentence, &Notation::default(), "?", &table);
+
+        let table = CharacterTable::new(Alphabet::Latin, true, &[], false);
+        for ((ch, seq), before) in ascii.zip(before) {
+            assert_eq!(table.morse_to_char(seq), before, "{}", seq);
+            assert_eq!(table.morse_to_char(seq), Some(*ch));
+            assert_eq!(table.char_to_morse(*ch), Some(*seq));
+        }
+        assert_eq!(
+            decode_text(&sentence, &Notation::default(), "?", &table),
+            decoded
+        );
+        assert_eq!(decoded, "CQ DE W1AW/3, 599?");
+    }
+
+    #[test]
+    fn extended_letters_decode_only_when_enabled() {
+        assert_eq!(CharacterTable::default().morse_to_char("..--"), None);
+        let table = CharacterTable::new(Alphabet::Latin, true, &[], false);
+        for (ch, seq) in EXTENDED {
+            assert_eq!(table.morse_to_char(seq), Some(ch));
+        }
+        // Only the latin alphabet has them.
+        assert_eq!(
+            CharacterTable::new(Alphabet::Cyrillic, true, &[], false),
+            CharacterTable::new(Alphabet::Cyrillic, false, &[], false)
+        );
+    }
+
+    #[test]
+    fn decodes_a_cyrillic_word() {
+        let table = CharacterTable::new(Alphabet::Cyrillic, false, &[], false);
+        assert_eq!(
+            decode_text("-- .. .-.", &Notation::default(), "?", &table),
+            "МИР"
+        );
+
+        let mut decoder = Decoder::new(0, table);
+        let mut word = String::new();
+        for seq in [".--.", ".-.", "..", ".--", ".", "-"] {
+            for element in seq.chars() {
+                decoder.on_element(element == '-');
+                decoder.on_gap(1.);
+            }
+            decoder.on_gap(3.);
+            if let Some(Decoded::Char(ch)) = decoder.take_decoded() {
+                word.push(ch);
+            }
+        }
+        assert_eq!(word, "ПРИВЕТ");
+    }
+
+    #[test]
+    fn cyrillic_words_round_trip() {
+        let table = CharacterTable::new(Alphabet::Cyrillic, false, &[], false);
+        let notation = Notation::default();
+        let text = "ПРИВЕТ МИР";
+        let morse = encode(text, &notation, false, &table).unwrap();
+        assert_eq!(decode_text(&morse, &notation, "?", &table), text);
+        // Latin letters aren't looked up while the cyrillic alphabet is in use.
+        assert_eq!(encode("HELLO", &notation, false, &table), Err('H'));
+    }
+
+    #[test]
+    fn custom_characters_extend_or_replace_the_builtin_ones() {
+        let custom = [('A', "..".to_string()), ('Ñ', "--.--".to_string())];
+        let table = CharacterTable::new(Alphabet::Latin, false, &custom, false);
+        // Custom characters are looked up first.
+        assert_eq!(table.char_to_morse('a'), Some(".."));
+        assert_eq!(table.morse_to_char(".."), Some('A'));
+        assert_eq!(table.morse_to_char(".-"), None);
+        assert_eq!(table.morse_to_char("--.--"), Some('Ñ'));
+        assert_eq!(table.morse_to_char("-..."), Some('B'));
+
+        let table = CharacterTable::new(Alphabet::Latin, false, &custom, true);
+        assert_eq!(table.iter().count(), 2);
+        assert_eq!(table.morse_to_char("-..."), None);
+        // An empty table can't replace the built-in ones.
+        let table = CharacterTable::new(Alphabet::Latin, false, &[], true);
+        assert_eq!(table, CharacterTable::default());
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+/// Characters loaded from a user file, extending or replacing the built-in tables
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomAlphabet {
+    /// Name of the file it was loaded from
+    pub name: String,
+    pub characters: Vec<(char, String)>,
+    /// Use only these characters instead of looking them up before the built-in ones
+    pub replace: bool,
+}
+
+impl CustomAlphabet {
+    /// Read a JSON object mapping every character to its sequence, like `{"A": ".-"}`.
+    pub fn from_json(name: &str, bytes: &[u8]) -> Result<Self, String> {
+        let entries = serde_json::from_slice::<BTreeMap<String, String>>(bytes)
+            .map_err(|err| err.to_string())?;
+        if entries.is_empty() {
+            return Err("The file doesn't map any characters".to_string());
+        }
+        let mut characters = Vec::with_capacity(entries.len());
+        let mut keys = HashMap::<char, &str>::new();
+        for (key, sequence) in &entries {
+            let mut chars = key.trim().chars().flat_map(char::to_uppercase);
+            let (Some(ch), None) = (chars.next(), chars.next()) else {
+                return Err(format!("\"{}\" isn't a single character", key));
+            };
+            // Characters are looked up ignoring case, so only one of them could ever be used.
+            if let Some(other) = keys.insert(ch, key) {
+                return Err(format!(
+                    "\"{}\" and \"{}\" are the same character",
+                    other, key
+                ));
+            }
+            let sequence = sequence.trim();
+            if sequence.is_empty() || !sequence.chars().all(|el| el == '.' || el == '-') {
+                return Err(format!(
+                    "The sequence of {} can only contain dots (.) and dashes (-)",
+                    ch
+                ));
+            }
+            characters.push((ch, sequence.to_string()));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            characters,
+            replace: false,
+        })
+    }
+
+    /// Sequences shared by several characters, only the first of which gets decoded.
+    pub fn duplicates(&self) -> Vec<String> {
+        let mut owners = HashMap::<&str, Vec<char>>::new();
+        for (ch, sequence) in &self.characters {
+            owners.entry(sequence).or_default().push(*ch);
+        }
+        let mut duplicates = owners
+            .into_iter()
+            .filter(|(_, chars)| chars.len() > 1)
+            .map(|(sequence, chars)| {
+                let chars = chars.iter().map(char::to_string).collect::<Vec<_>>();
+                format!("{} is shared by {}", sequence, chars.join(", "))
+            })
+            .collect::<Vec<_>>();
+        duplicates.sort();
+        duplicates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_characters_in_upper_case() {
+        let alphabet =
+            CustomAlphabet::from_json("test", r#"{"a": ".-", "Ñ": "--.--"}"#.as_bytes()).unwrap();
+        assert_eq!(
+            alphabet.characters,
+            [('A', ".-".to_string()), ('Ñ', "--.--".to_string())]
+        );
+        assert!(alphabet.duplicates().is_empty());
+    }
+
+    #[test]
+    fn rejects_keys_differing_only_in_case() {
+        let err =
+            CustomAlphabet::from_json("test", r#"{"a": ".-", "A": ".-"}"#.as_bytes()).unwrap_err();
+        assert_eq!(err, "\"A\" and \"a\" are the same character");
+    }
+}
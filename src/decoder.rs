@@ -0,0 +1,452 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{self, ProSign};
+use crate::encoder::Notation;
+use crate::utils::{CHARACTER_GAP, CharacterTable, WORD_GAP};
+
+/// Result of decoding one character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoded {
+    /// Exact match of a character
+    Char(char),
+    /// Character with the closest sequence, which may be wrong
+    Guess(char),
+    ProSign(ProSign),
+    /// Custom prosign of the decoder, by its index
+    Custom(usize),
+    /// Elements that match nothing, even with the tolerance
+    Unknown,
+    /// Gap between two words
+    Space,
+}
+
+/// Prosign added by the user, decoded when no character or built-in prosign matches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomProSign {
+    /// Short notation written when it's decoded, like `<BT>`
+    pub tag: String,
+    pub sequence: String,
+}
+
+impl CustomProSign {
+    /// Check the tag and the sequence of dots and dashes typed by the user.
+    pub fn new(tag: &str, sequence: &str) -> Result<Self, String> {
+        let tag = tag.trim().to_uppercase();
+        let sequence = sequence.trim();
+        if tag.is_empty() {
+            return Err("The prosign needs a name".to_string());
+        }
+        if sequence.is_empty() || !sequence.chars().all(|ch| ch == '.' || ch == '-') {
+            return Err("The sequence can only contain dots (.) and dashes (-)".to_string());
+        }
+        Ok(Self {
+            tag,
+            sequence: sequence.to_string(),
+        })
+    }
+
+    /// Character or built-in prosign decoded from the same sequence instead, if any.
+    pub fn shadowed_by(&self, characters: &CharacterTable) -> Option<String> {
+        if let Some(ch) = characters.morse_to_char(&self.sequence) {
+            return Some(ch.to_string());
+        }
+        consts::PROSIGNS
+            .iter()
+            .find(|(_, seq)| *seq == self.sequence)
+            .map(|(prosign, _)| prosign.tag().to_string())
+    }
+}
+
+/// Decode a sequence of dots and dashes, allowing up to `tolerance`
+/// wrong elements if there's no exact match.
+pub fn decode(sequence: &str, tolerance: usize, characters: &CharacterTable) -> Option<Decoded> {
+    decode_with(sequence, tolerance, &[], characters)
+}
+
+/// Decode a sequence, looking through the custom prosigns before guessing.
+fn decode_with(
+    sequence: &str,
+    tolerance: usize,
+    custom: &[CustomProSign],
+    characters: &CharacterTable,
+) -> Option<Decoded> {
+    let prosign = consts::PROSIGNS.iter().find(|(_, seq)| *seq == sequence);
+    if let Some(ch) = characters.morse_to_char(sequence)
+        // Prosigns come before the accented letters sharing their sequences, like <AA> and Ä.
+        && (prosign.is_none() || consts::EXTENDED.iter().all(|(other, _)| *other != ch))
+    {
+        return Some(Decoded::Char(ch));
+    }
+    if let Some((prosign, _)) = prosign {
+        return Some(Decoded::ProSign(*prosign));
+    }
+    if let Some(index) = custom
+        .iter()
+        .position(|prosign| prosign.sequence == sequence)
+    {
+        return Some(Decoded::Custom(index));
+    }
+    characters
+        .closest_char(sequence, tolerance)
+        .map(Decoded::Guess)
+}
+
+/// Turn a group written in the given notation into dots and dashes.
+fn normalize_group(group: &str, notation: &Notation) -> Option<String> {
+    let mut rest = group;
+    let mut sequence = String::new();
+    while !rest.is_empty() {
+        if !notation.element_separator.is_empty()
+            && let Some(tail) = rest.strip_prefix(notation.element_separator.as_str())
+        {
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix(notation.dit.as_str()) {
+            sequence.push('.');
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix(notation.dah.as_str()) {
+            sequence.push('-');
+            rest = tail;
+        } else {
+            return None;
+        }
+    }
+    Some(sequence)
+}
+
+/// Split `text` by the separator, treating a blank one as any whitespace.
+fn split_by<'a>(text: &'a str, separator: &str) -> Vec<&'a str> {
+    let separator = separator.trim();
+    if separator.is_empty() {
+        text.split_whitespace().collect()
+    } else {
+        text.split(separator)
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect()
+    }
+}
+
+/// Decode morse code written in the given notation back into text.
+///
+/// Groups that don't match any character or prosign are
+/// replaced with `placeholder`.
+pub fn decode_text(
+    morse: &str,
+    notation: &Notation,
+    placeholder: &str,
+    characters: &CharacterTable,
+) -> String {
+    split_by(morse, &notation.word_separator)
+        .into_iter()
+        .map(|word| {
+            split_by(word, &notation.character_separator)
+                .into_iter()
+                .map(|group| {
+                    match normalize_group(group, notation)
+                        .and_then(|seq| decode(&seq, 0, characters))
+                    {
+                        Some(Decoded::Char(ch) | Decoded::Guess(ch)) => ch.to_string(),
+                        Some(Decoded::ProSign(prosign)) => prosign.tag().to_string(),
+                        Some(Decoded::Custom(_) | Decoded::Unknown | Decoded::Space) | None => {
+                            placeholder.to_string()
+                        }
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Collects keyed elements and decodes them once the character is complete
+///
+/// Any input can drive it with [`Decoder::on_element`] and [`Decoder::on_gap`],
+/// taking what was completed with [`Decoder::take_decoded`].
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    buffer: String,
+    tolerance: usize,
+    characters: CharacterTable,
+    custom_prosigns: Vec<CustomProSign>,
+    /// Silence in dits after which a character is complete
+    character_gap: f32,
+    /// Silence in dits after which a word is complete
+    word_gap: f32,
+    /// Characters and spaces completed but not taken yet
+    decoded: VecDeque<Decoded>,
+    /// A character was completed since the last space
+    in_word: bool,
+}
+
+impl Decoder {
+    pub fn new(tolerance: usize, characters: CharacterTable) -> Self {
+        Self {
+            buffer: String::new(),
+            tolerance,
+            characters,
+            custom_prosigns: Vec::new(),
+            character_gap: CHARACTER_GAP,
+            word_gap: WORD_GAP,
+            decoded: VecDeque::new(),
+            in_word: false,
+        }
+    }
+
+    /// Set the silences in dits after which characters and words are complete.
+    pub fn set_gaps(&mut self, character_gap: f32, word_gap: f32) {
+        self.character_gap = character_gap;
+        self.word_gap = word_gap.max(character_gap);
+    }
+
+    /// Silence in dits after which a character is complete.
+    pub fn character_gap(&self) -> f32 {
+        self.character_gap
+    }
+
+    /// Silence in dits after which a word is complete.
+    pub fn word_gap(&self) -> f32 {
+        self.word_gap
+    }
+
+    /// Decode the characters of this table from now on.
+    pub fn set_characters(&mut self, characters: CharacterTable) {
+        self.characters = characters;
+    }
+
+    /// Decode these prosigns too, reported as [`Decoded::Custom`].
+    pub fn set_custom_prosigns(&mut self, prosigns: Vec<CustomProSign>) {
+        self.custom_prosigns = prosigns;
+    }
+
+    pub fn custom_prosign(&self, index: usize) -> Option<&CustomProSign> {
+        self.custom_prosigns.get(index)
+    }
+
+    /// Set how many wrong elements are tolerated, 0 being strict.
+    pub fn set_tolerance(&mut self, tolerance: usize) {
+        self.tolerance = tolerance;
+    }
+
+    /// Add a dot (`.`) or a dash (`-`) to the current character.
+    pub fn push(&mut self, element: char) {
+        self.buffer.push(element);
+    }
+
+    /// Elements of the character keyed so far.
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Drop the elements keyed so far.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Add a dot or a dash to the current character.
+    pub fn on_element(&mut self, is_dash: bool) {
+        self.push(if is_dash { '-' } else { '.' });
+    }
+
+    /// Report how long it's been silent since the last element, in dits.
+    ///
+    /// The silence can be reported again as it grows,
+    /// every character and space is only completed once.
+    pub fn on_gap(&mut self, dit_units: f32) {
+        if !self.buffer.is_empty() && dit_units >= self.character_gap {
+            let decoded = self.flush().unwrap_or(Decoded::Unknown);
+            self.decoded.push_back(decoded);
+            self.in_word = true;
+        }
+        if self.in_word && self.buffer.is_empty() && dit_units >= self.word_gap {
+            self.decoded.push_back(Decoded::Space);
+            self.in_word = false;
+        }
+    }
+
+    /// Take the oldest character, prosign or space completed by the silences.
+    pub fn take_decoded(&mut self) -> Option<Decoded> {
+        self.decoded.pop_front()
+    }
+
+    /// Decode the current character and start a new one.
+    pub fn flush(&mut self) -> Option<Decoded> {
+        let decoded = decode_with(
+            &self.buffer,
+            self.tolerance,
+            &self.custom_prosigns,
+            &self.characters,
+        );
+        // No matter if we found a value or not,
+        // we need to clear up the buffer anyways.
+        self.buffer.clear();
+        decoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::Alphabet;
+
+    /// Key the elements with standard gaps: one dit between elements, a character gap
+    /// for a space and a word gap for a slash. Returns everything decoded on the way.
+    fn key(decoder: &mut Decoder, morse: &str) -> Vec<Decoded> {
+        let mut decoded = Vec::new();
+        let mut gap = |decoder: &mut Decoder, units: f32| {
+            decoder.on_gap(units);
+            decoded.extend(std::iter::from_fn(|| decoder.take_decoded()));
+        };
+        for element in morse.chars() {
+            match element {
+                ' ' => gap(decoder, CHARACTER_GAP),
+                '/' => gap(decoder, WORD_GAP),
+                _ => {
+                    decoder.on_element(element == '-');
+                    gap(decoder, 1.);
+                }
+            }
+        }
+        decoded
+    }
+
+    #[test]
+    fn keys_sos_as_letters() {
+        let mut decoder = Decoder::new(0, CharacterTable::default());
+        assert_eq!(
+            key(&mut decoder, "... --- .../"),
+            [
+                Decoded::Char('S'),
+                Decoded::Char('O'),
+                Decoded::Char('S'),
+                Decoded::Space
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_sos_without_gaps_as_the_prosign() {
+        let mut decoder = Decoder::new(0, CharacterTable::default());
+        assert_eq!(
+            key(&mut decoder, "...---.../"),
+            [Decoded::ProSign(ProSign::SOS), Decoded::Space]
+        );
+    }
+
+    #[test]
+    fn keys_aa_as_the_prosign_with_or_without_accented_letters() {
+        for extended in [false, true] {
+            let characters = CharacterTable::new(Alphabet::Latin, extended, &[], false);
+            let mut decoder = Decoder::new(0, characters.clone());
+            assert_eq!(
+                key(&mut decoder, ".-.-/"),
+                [Decoded::ProSign(ProSign::AA), Decoded::Space],
+                "accented letters: {}",
+                extended
+            );
+            assert_eq!(
+                decode(".-.-", 0, &characters),
+                Some(Decoded::ProSign(ProSign::AA))
+            );
+        }
+        // The other accented letters still decode.
+        let characters = CharacterTable::new(Alphabet::Latin, true, &[], false);
+        assert_eq!(decode("..--", 0, &characters), Some(Decoded::Char('Ü')));
+    }
+
+    #[test]
+    fn keys_do_as_letters_or_as_the_prosign() {
+        let mut decoder = Decoder::new(0, CharacterTable::default());
+        assert_eq!(
+            key(&mut decoder, "-.. ---/"),
+            [Decoded::Char('D'), Decoded::Char('O'), Decoded::Space]
+        );
+        assert_eq!(
+            key(&mut decoder, "-..---/"),
+            [Decoded::ProSign(ProSign::DO), Decoded::Space]
+        );
+    }
+
+    fn taken(decoder: &mut Decoder) -> Vec<Decoded> {
+        std::iter::from_fn(|| decoder.take_decoded()).collect()
+    }
+
+    #[test]
+    fn completes_a_character_from_the_character_gap() {
+        for (units, expected) in [
+            (CHARACTER_GAP - 0.01, vec![]),
+            (CHARACTER_GAP, vec![Decoded::Char('E')]),
+            (CHARACTER_GAP + 0.01, vec![Decoded::Char('E')]),
+        ] {
+            let mut decoder = Decoder::new(0, CharacterTable::default());
+            decoder.on_element(false);
+            decoder.on_gap(units);
+            assert_eq!(taken(&mut decoder), expected, "after {} dits", units);
+            assert_eq!(
+                decoder.is_empty(),
+                !expected.is_empty(),
+                "after {} dits",
+                units
+            );
+        }
+    }
+
+    #[test]
+    fn completes_a_word_from_the_word_gap() {
+        for (units, expected) in [
+            (WORD_GAP - 0.01, vec![]),
+            (WORD_GAP, vec![Decoded::Space]),
+            (WORD_GAP + 0.01, vec![Decoded::Space]),
+        ] {
+            let mut decoder = Decoder::new(0, CharacterTable::default());
+            decoder.on_element(false);
+            decoder.on_gap(CHARACTER_GAP);
+            assert_eq!(taken(&mut decoder), [Decoded::Char('E')]);
+            decoder.on_gap(units);
+            assert_eq!(taken(&mut decoder), expected, "after {} dits", units);
+        }
+    }
+
+    #[test]
+    fn long_silence_completes_the_character_and_the_word_once() {
+        let mut decoder = Decoder::new(0, CharacterTable::default());
+        // Nothing to complete before any character.
+        decoder.on_gap(WORD_GAP * 2.);
+        assert_eq!(taken(&mut decoder), []);
+
+        decoder.on_element(true);
+        decoder.on_gap(WORD_GAP + 1.);
+        assert_eq!(taken(&mut decoder), [Decoded::Char('T'), Decoded::Space]);
+        decoder.on_gap(WORD_GAP * 2.);
+        assert_eq!(taken(&mut decoder), []);
+    }
+
+    #[test]
+    fn follows_custom_gaps() {
+        let mut decoder = Decoder::new(0, CharacterTable::default());
+        decoder.set_gaps(2., 5.);
+        decoder.on_element(false);
+        decoder.on_gap(1.99);
+        assert_eq!(taken(&mut decoder), []);
+        decoder.on_gap(2.);
+        assert_eq!(taken(&mut decoder), [Decoded::Char('E')]);
+        decoder.on_gap(4.99);
+        assert_eq!(taken(&mut decoder), []);
+        decoder.on_gap(5.);
+        assert_eq!(taken(&mut decoder), [Decoded::Space]);
+
+        // A word gap shorter than the character gap is raised to it.
+        decoder.set_gaps(4., 3.);
+        assert_eq!(decoder.word_gap(), 4.);
+        decoder.on_element(true);
+        decoder.on_gap(3.5);
+        assert_eq!(taken(&mut decoder), []);
+        decoder.on_gap(4.);
+        assert_eq!(taken(&mut decoder), [Decoded::Char('T'), Decoded::Space]);
+    }
+}
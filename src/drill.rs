@@ -0,0 +1,186 @@
+use crate::text_source::TextGenerator;
+
+/// How a character of the target word was sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Correct(char),
+    /// Another character was sent in place of the expected one
+    Substituted {
+        expected: char,
+        sent: char,
+    },
+    /// The character was left out
+    Missed(char),
+    /// A character that isn't part of the target was sent
+    Extra(char),
+}
+
+/// Result of sending a word
+#[derive(Debug, Clone)]
+pub struct Grade {
+    pub marks: Vec<Mark>,
+    /// Part of the target characters sent correctly
+    pub accuracy: f32,
+    /// Timing quality from 0 to 1, if any elements were timed
+    pub timing: Option<f32>,
+}
+
+impl Grade {
+    /// Grade the sent text against the target.
+    pub fn new(target: &str, sent: &str, timing: Option<f32>) -> Self {
+        let marks = align(target, sent);
+        let correct = marks
+            .iter()
+            .filter(|mark| matches!(mark, Mark::Correct(_)))
+            .count();
+        Self {
+            marks,
+            accuracy: correct as f32 / target.chars().count().max(1) as f32,
+            timing,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.marks
+            .iter()
+            .all(|mark| matches!(mark, Mark::Correct(_)))
+    }
+}
+
+/// Match the sent characters against the target with the fewest edits.
+pub fn align(target: &str, sent: &str) -> Vec<Mark> {
+    let target = target.chars().collect::<Vec<_>>();
+    let sent = sent.chars().collect::<Vec<_>>();
+
+    // Edit distances between every prefix of the target and of the sent text
+    let mut distances = vec![vec![0; sent.len() + 1]; target.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in distances[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=target.len() {
+        for j in 1..=sent.len() {
+            let cost = usize::from(target[i - 1] != sent[j - 1]);
+            distances[i][j] = (distances[i - 1][j - 1] + cost)
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+        }
+    }
+
+    // Walk back from the full strings to recover the edits
+    let mut marks = Vec::new();
+    let (mut i, mut j) = (target.len(), sent.len());
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let cost = usize::from(target[i - 1] != sent[j - 1]);
+            if distances[i][j] == distances[i - 1][j - 1] + cost {
+                marks.push(if cost == 0 {
+                    Mark::Correct(target[i - 1])
+                } else {
+                    Mark::Substituted {
+                        expected: target[i - 1],
+                        sent: sent[j - 1],
+                    }
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && distances[i][j] == distances[i - 1][j] + 1 {
+            marks.push(Mark::Missed(target[i - 1]));
+            i -= 1;
+        } else {
+            marks.push(Mark::Extra(sent[j - 1]));
+            j -= 1;
+        }
+    }
+    marks.reverse();
+    marks
+}
+
+/// Shows a word for the user to key and grades what was decoded
+pub struct SendingDrill {
+    generator: TextGenerator,
+    target: String,
+    sent: String,
+    /// Timing error of every character sent for this attempt
+    timing_errors: Vec<f32>,
+    last_grade: Option<Grade>,
+}
+
+impl SendingDrill {
+    pub fn new(generator: TextGenerator) -> Self {
+        Self {
+            target: generator.next(""),
+            generator,
+            sent: String::new(),
+            timing_errors: Vec::new(),
+            last_grade: None,
+        }
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn sent(&self) -> &str {
+        &self.sent
+    }
+
+    pub fn last_grade(&self) -> Option<&Grade> {
+        self.last_grade.as_ref()
+    }
+
+    /// Add a decoded character to the attempt.
+    pub fn push(&mut self, ch: char, timing_error: Option<f32>) {
+        self.sent.push(ch);
+        if let Some(error) = timing_error {
+            self.timing_errors.push(error);
+        }
+    }
+
+    /// Remove the last character of the attempt.
+    pub fn pop(&mut self) {
+        self.sent.pop();
+    }
+
+    /// Start the attempt over.
+    pub fn clear(&mut self) {
+        self.sent.clear();
+        self.timing_errors.clear();
+    }
+
+    /// Grade the attempt, moving on to a new word if it was sent correctly.
+    /// Returns the grade, if there was anything to grade.
+    pub fn finish(&mut self) -> Option<&Grade> {
+        if self.sent.is_empty() {
+            return None;
+        }
+        let timing = (!self.timing_errors.is_empty()).then(|| {
+            let error = self.timing_errors.iter().sum::<f32>() / self.timing_errors.len() as f32;
+            (1. - error).clamp(0., 1.)
+        });
+        let grade = Grade::new(&self.target, &self.sent, timing);
+        if grade.passed() {
+            self.target = self.generator.next(&self.target);
+        }
+        self.clear();
+        Some(self.last_grade.insert(grade))
+    }
+
+    /// Move on to another word without grading.
+    pub fn skip(&mut self) {
+        self.target = self.generator.next(&self.target);
+        self.last_grade = None;
+        self.clear();
+    }
+
+    /// Take words from another generator, starting with a new one.
+    pub fn set_generator(&mut self, generator: TextGenerator) {
+        self.generator = generator;
+        self.skip();
+    }
+}
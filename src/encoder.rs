@@ -0,0 +1,222 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+use crate::utils::CharacterTable;
+
+/// Standard weight, where a dit is exactly as long as the gap after it
+pub const STANDARD_WEIGHT: f32 = 0.5;
+
+/// Symbols used to write morse code as text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notation {
+    pub dit: String,
+    pub dah: String,
+    /// Put between elements of one character
+    pub element_separator: String,
+    /// Put between characters of one word
+    pub character_separator: String,
+    /// Put between words
+    pub word_separator: String,
+}
+
+impl Default for Notation {
+    fn default() -> Self {
+        Self {
+            dit: ".".to_string(),
+            dah: "-".to_string(),
+            element_separator: String::new(),
+            character_separator: " ".to_string(),
+            word_separator: " / ".to_string(),
+        }
+    }
+}
+
+/// State of the signal for a number of dit units
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub on: bool,
+    pub units: u32,
+}
+
+impl Timing {
+    /// How long the signal stays in this state at the given dit length and weight.
+    ///
+    /// Weight is the part of a dit plus the gap after it during which the tone is on.
+    /// At the standard 50% dits, dahs and element gaps keep the 1:3:1 ratio.
+    /// A heavier weight lengthens every element and shortens every gap by the same
+    /// amount, a lighter one does the opposite, so the overall speed stays the same.
+    pub fn duration(&self, dit_duration: Duration, weight: f32) -> Duration {
+        let base = dit_duration * self.units;
+        let shift = dit_duration.mul_f32(((weight - STANDARD_WEIGHT) * 2.).abs());
+        match (self.on, weight >= STANDARD_WEIGHT) {
+            (true, true) | (false, false) => base + shift,
+            (true, false) | (false, true) => base.saturating_sub(shift),
+        }
+    }
+}
+
+/// Look up the sequence of every character, word by word.
+///
+/// Characters without a morse mapping are skipped if `skip_unknown` is set,
+/// otherwise the first of them is returned as an error.
+pub fn sequences<'a>(
+    text: &str,
+    skip_unknown: bool,
+    characters: &'a CharacterTable,
+) -> Result<Vec<Vec<&'a str>>, char> {
+    text.split_whitespace()
+        .map(|word| {
+            let mut codes = Vec::new();
+            for ch in word.chars() {
+                match characters.char_to_morse(ch) {
+                    Some(code) => codes.push(code),
+                    None if skip_unknown => {}
+                    None => return Err(ch),
+                }
+            }
+            Ok(codes)
+        })
+        .filter(|word| !matches!(word, Ok(codes) if codes.is_empty()))
+        .collect()
+}
+
+/// Write the text as morse code using the given notation.
+pub fn encode(
+    text: &str,
+    notation: &Notation,
+    skip_unknown: bool,
+    characters: &CharacterTable,
+) -> Result<String, char> {
+    let words = sequences(text, skip_unknown, characters)?;
+    let encoded = words
+        .iter()
+        .map(|word| {
+            word.iter()
+                .map(|code| {
+                    code.chars()
+                        .map(|element| match element {
+                            '.' => notation.dit.as_str(),
+                            _ => notation.dah.as_str(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&notation.element_separator)
+                })
+                .collect::<Vec<_>>()
+                .join(&notation.character_separator)
+        })
+        .collect::<Vec<_>>()
+        .join(&notation.word_separator);
+    Ok(encoded)
+}
+
+/// Signal timings for sending the text with standard spacing:
+/// a dit is one unit, a dah three, elements are separated by one unit,
+/// characters by three and words by seven.
+pub fn timings(
+    text: &str,
+    skip_unknown: bool,
+    characters: &CharacterTable,
+) -> Result<Vec<Timing>, char> {
+    let timings = character_timings(text, skip_unknown, characters)?;
+    Ok(timings.into_iter().map(|(_, timing)| timing).collect())
+}
+
+/// Same as [`timings`], along with the byte index in the text of the character
+/// every timing sends. Gaps belong to the character after them.
+pub fn character_timings(
+    text: &str,
+    skip_unknown: bool,
+    characters: &CharacterTable,
+) -> Result<Vec<(usize, Timing)>, char> {
+    let mut timings = Vec::new();
+    let mut word_break = false;
+    for (index, ch) in text.char_indices() {
+        if ch.is_whitespace() {
+            word_break = true;
+            continue;
+        }
+        let code = match characters.char_to_morse(ch) {
+            Some(code) => code,
+            None if skip_unknown => continue,
+            None => return Err(ch),
+        };
+        if !timings.is_empty() {
+            let units = if word_break { 7 } else { 3 };
+            timings.push((index, Timing { on: false, units }));
+        }
+        word_break = false;
+        for (element_idx, element) in code.chars().enumerate() {
+            if element_idx > 0 {
+                timings.push((
+                    index,
+                    Timing {
+                        on: false,
+                        units: 1,
+                    },
+                ));
+            }
+            let units = if element == '.' { 1 } else { 3 };
+            timings.push((index, Timing { on: true, units }));
+        }
+    }
+    Ok(timings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(timings: &[Timing], dit: Duration, weight: f32) -> Duration {
+        timings
+            .iter()
+            .map(|timing| timing.duration(dit, weight))
+            .sum()
+    }
+
+    #[test]
+    fn word_duration_stays_the_same_across_weights() {
+        let dit = Duration::from_millis(60);
+        let mut paris = timings("PARIS", false, &CharacterTable::default()).unwrap();
+        paris.push(Timing {
+            on: false,
+            units: 7,
+        });
+        for percent in (25..=75).step_by(5) {
+            let weight = percent as f32 * 0.01;
+            let duration = total(&paris, dit, weight);
+            assert!(
+                duration.abs_diff(dit * 50) <= Duration::from_millis(1),
+                "PARIS lasts {:?} at weight {}",
+                duration,
+                weight
+            );
+            for timing in &paris {
+                assert!(
+                    !timing.duration(dit, weight).is_zero(),
+                    "{:?} vanishes at weight {}",
+                    timing,
+                    weight
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn word_duration_without_trailing_gap_drifts_by_one_shift() {
+        let dit = Duration::from_millis(60);
+        let paris = timings("PARIS", false, &CharacterTable::default()).unwrap();
+        for percent in (25..=75).step_by(5) {
+            let weight = percent as f32 * 0.01;
+            let shift = dit.mul_f32(((weight - STANDARD_WEIGHT) * 2.).abs());
+            let duration = total(&paris, dit, weight);
+            assert!(
+                duration.abs_diff(dit * 43) <= shift + Duration::from_millis(1),
+                "PARIS lasts {:?} at weight {}",
+                duration,
+                weight
+            );
+        }
+    }
+}
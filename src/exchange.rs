@@ -0,0 +1,188 @@
+use std::fmt;
+
+use morset::{
+    consts::NUMBERS,
+    utils::{cut_digit, uncut_letter},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::drill::Grade;
+
+/// Signal reports sent in contests, nearly always 599
+static REPORTS: [&str; 5] = ["599", "599", "599", "579", "559"];
+
+/// Letters the fields of grid squares use
+static GRID_LETTERS: std::ops::RangeInclusive<char> = 'A'..='R';
+
+/// Digits contesters send as cut numbers, T, N and A
+static CUT_DIGITS: [char; 3] = ['0', '9', '1'];
+
+/// What follows the signal report in a contest exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExchangeFormat {
+    ReportOnly,
+    #[default]
+    Serial,
+    Zone,
+    Grid,
+}
+
+impl ExchangeFormat {
+    pub const ALL: [ExchangeFormat; 4] = [
+        ExchangeFormat::ReportOnly,
+        ExchangeFormat::Serial,
+        ExchangeFormat::Zone,
+        ExchangeFormat::Grid,
+    ];
+}
+
+impl fmt::Display for ExchangeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExchangeFormat::ReportOnly => write!(f, "RST"),
+            ExchangeFormat::Serial => write!(f, "RST + serial number"),
+            ExchangeFormat::Zone => write!(f, "RST + CQ zone"),
+            ExchangeFormat::Grid => write!(f, "RST + grid square"),
+        }
+    }
+}
+
+fn digit() -> char {
+    NUMBERS[fastrand::usize(..NUMBERS.len())].0
+}
+
+/// Replace the digits contesters cut with their letter.
+fn cut(number: &str) -> String {
+    number
+        .chars()
+        .map(|ch| {
+            CUT_DIGITS
+                .contains(&ch)
+                .then(|| cut_digit(ch))
+                .flatten()
+                .unwrap_or(ch)
+        })
+        .collect()
+}
+
+/// Replace cut numbers with the digits they stand for.
+fn uncut(number: &str) -> String {
+    number
+        .chars()
+        .map(|ch| uncut_letter(ch).unwrap_or(ch))
+        .collect()
+}
+
+/// Fields of an exchange, with whether each is a number
+fn fields(format: ExchangeFormat) -> Vec<(String, bool)> {
+    let mut fields = vec![(REPORTS[fastrand::usize(..REPORTS.len())].to_string(), true)];
+    match format {
+        ExchangeFormat::ReportOnly => {}
+        ExchangeFormat::Serial => fields.push((format!("{:03}", fastrand::u16(1..=999)), true)),
+        ExchangeFormat::Zone => fields.push((format!("{:02}", fastrand::u8(1..=40)), true)),
+        ExchangeFormat::Grid => {
+            let grid = [
+                fastrand::char(GRID_LETTERS.clone()),
+                fastrand::char(GRID_LETTERS.clone()),
+                digit(),
+                digit(),
+            ];
+            fields.push((grid.iter().collect(), false));
+        }
+    }
+    fields
+}
+
+/// Exchange waiting to be copied
+struct Exchange {
+    /// Text as it's sent, with cut numbers if enabled
+    sent: String,
+    /// Text with every number in digits
+    full: String,
+    /// Whether every field is a number, which may be copied with cut numbers
+    numbers: Vec<bool>,
+}
+
+/// Sends contest exchanges, the user types what they copied
+pub struct ExchangeDrill {
+    current: Option<Exchange>,
+    /// The last exchange as sent with the grade of the copy
+    last: Option<(String, Grade)>,
+    /// Characters copied correctly and sent, over all exchanges
+    correct: usize,
+    total: usize,
+}
+
+impl ExchangeDrill {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            last: None,
+            correct: 0,
+            total: 0,
+        }
+    }
+
+    /// Text of the exchange waiting to be copied, as it's sent.
+    pub fn sent(&self) -> Option<&str> {
+        self.current.as_ref().map(|exchange| exchange.sent.as_str())
+    }
+
+    pub fn last(&self) -> Option<&(String, Grade)> {
+        self.last.as_ref()
+    }
+
+    /// Pick a random exchange to send, returning it as it's sent.
+    pub fn next(&mut self, format: ExchangeFormat, cut_numbers: bool) -> &str {
+        let fields = fields(format);
+        let sent = fields
+            .iter()
+            .map(|(field, number)| {
+                if cut_numbers && *number {
+                    cut(field)
+                } else {
+                    field.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let exchange = self.current.insert(Exchange {
+            sent: sent.join(" "),
+            full: fields
+                .iter()
+                .map(|(field, _)| field.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            numbers: fields.iter().map(|(_, number)| *number).collect(),
+        });
+        &exchange.sent
+    }
+
+    /// Grade the copy, accepting numbers both in digits and cut,
+    /// and reveal the exchange, returning it with the grade.
+    pub fn submit(&mut self, copy: &str) -> Option<&(String, Grade)> {
+        let exchange = self.current.take()?;
+        let copy = copy
+            .to_uppercase()
+            .split_whitespace()
+            .enumerate()
+            .map(|(index, field)| {
+                if exchange.numbers.get(index) == Some(&true) {
+                    uncut(field)
+                } else {
+                    field.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let grade = Grade::new(&exchange.full, &copy, None);
+        let characters = exchange.full.chars().count();
+        self.correct += (grade.accuracy * characters as f32).round() as usize;
+        self.total += characters;
+        Some(self.last.insert((exchange.sent, grade)))
+    }
+
+    /// Accuracy over all exchanges.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.total > 0).then(|| self.correct as f32 / self.total as f32)
+    }
+}
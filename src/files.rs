@@ -0,0 +1,111 @@
+use std::sync::{Arc, Mutex};
+
+/// File chosen by the user
+pub struct LoadedFile {
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// File dialog running in the background.
+///
+/// Dialogs can't block the frame loop on the web,
+/// so the result is polled every frame instead.
+pub struct PendingFile {
+    result: Arc<Mutex<Option<Option<LoadedFile>>>>,
+}
+
+impl PendingFile {
+    /// Returns `Some` once the dialog is closed, holding `None` if it was cancelled.
+    pub fn poll(&self) -> Option<Option<LoadedFile>> {
+        self.result.lock().ok()?.take()
+    }
+}
+
+/// Ask the user for a file with one of the given extensions and read it.
+pub fn open_file(ctx: &egui::Context, filter_name: &str, extensions: &[&str]) -> PendingFile {
+    let result = Arc::new(Mutex::new(None));
+    let dialog = rfd::AsyncFileDialog::new().add_filter(filter_name, extensions);
+    let task = {
+        let result = result.clone();
+        let ctx = ctx.clone();
+        async move {
+            let file = match dialog.pick_file().await {
+                Some(handle) => Some(LoadedFile {
+                    name: handle.file_name(),
+                    bytes: handle.read().await,
+                }),
+                None => None,
+            };
+            if let Ok(mut result) = result.lock() {
+                *result = Some(file);
+            }
+            ctx.request_repaint();
+        }
+    };
+    spawn(task);
+    PendingFile { result }
+}
+
+/// Name of the file written, `None` if the dialog was cancelled
+pub type SaveResult = Result<Option<String>, String>;
+
+/// Save dialog running in the background, polled like [`PendingFile`].
+pub struct PendingSave {
+    result: Arc<Mutex<Option<SaveResult>>>,
+}
+
+impl PendingSave {
+    /// Returns `Some` once the dialog is closed and the file written.
+    pub fn poll(&self) -> Option<SaveResult> {
+        self.result.lock().ok()?.take()
+    }
+}
+
+/// Ask the user where to save the bytes and write them there.
+///
+/// On the web the file is downloaded instead.
+pub fn save_file(
+    ctx: &egui::Context,
+    file_name: &str,
+    filter_name: &str,
+    extensions: &[&str],
+    bytes: Vec<u8>,
+) -> PendingSave {
+    let result = Arc::new(Mutex::new(None));
+    let dialog = rfd::AsyncFileDialog::new()
+        .add_filter(filter_name, extensions)
+        .set_file_name(file_name);
+    let task = {
+        let result = result.clone();
+        let ctx = ctx.clone();
+        async move {
+            let saved = match dialog.save_file().await {
+                Some(handle) => match handle.write(&bytes).await {
+                    Ok(()) => Ok(Some(handle.file_name())),
+                    Err(err) => {
+                        let err = format!("Failed to save {}: {}", handle.file_name(), err);
+                        tracing::error!("{}", err);
+                        Err(err)
+                    }
+                },
+                None => Ok(None),
+            };
+            if let Ok(mut result) = result.lock() {
+                *result = Some(saved);
+            }
+            ctx.request_repaint();
+        }
+    };
+    spawn(task);
+    PendingSave { result }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn(task: impl Future<Output = ()> + Send + 'static) {
+    std::thread::spawn(move || pollster::block_on(task));
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn(task: impl Future<Output = ()> + 'static) {
+    wasm_bindgen_futures::spawn_local(task);
+}
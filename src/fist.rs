@@ -0,0 +1,191 @@
+use std::collections::{HashMap, VecDeque};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+/// Number of recent durations of every kind kept for the statistics
+pub static FIST_WINDOW: usize = 100;
+
+/// Dash to dit ratios further than this from 3:1 are reported
+pub static RATIO_TOLERANCE: f32 = 0.4;
+
+/// Character gaps longer than this (in dits) are reported
+pub static MAX_CHARACTER_GAP: f32 = 4.0;
+
+/// Mean and standard deviation of a set of durations, in seconds
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub mean: f32,
+    pub deviation: f32,
+}
+
+impl Stats {
+    fn of(values: &VecDeque<f32>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let count = values.len() as f32;
+        let mean = values.iter().sum::<f32>() / count;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / count;
+        Some(Self {
+            mean,
+            deviation: variance.sqrt(),
+        })
+    }
+
+    /// Deviation relative to the mean.
+    pub fn variation(&self) -> f32 {
+        if self.mean > 0. {
+            self.deviation / self.mean
+        } else {
+            0.
+        }
+    }
+}
+
+/// Records how evenly the user keys dits, dashes and gaps
+pub struct FistAnalysis {
+    dits: VecDeque<f32>,
+    dashes: VecDeque<f32>,
+    element_gaps: VecDeque<f32>,
+    character_gaps: VecDeque<f32>,
+    /// Elements and gaps of the character being keyed, with their ideal length in dits
+    current: Vec<(f32, u32)>,
+    /// Summed timing error and number of times every character was keyed
+    characters: HashMap<char, (f32, u32)>,
+}
+
+fn push_bounded(values: &mut VecDeque<f32>, value: f32) {
+    values.push_back(value);
+    while values.len() > FIST_WINDOW {
+        values.pop_front();
+    }
+}
+
+impl FistAnalysis {
+    pub fn new() -> Self {
+        Self {
+            dits: VecDeque::new(),
+            dashes: VecDeque::new(),
+            element_gaps: VecDeque::new(),
+            character_gaps: VecDeque::new(),
+            current: Vec::new(),
+            characters: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn record_element(&mut self, element: char, duration: Duration) {
+        let duration = duration.as_secs_f32();
+        if element == '.' {
+            push_bounded(&mut self.dits, duration);
+            self.current.push((duration, 1));
+        } else {
+            push_bounded(&mut self.dashes, duration);
+            self.current.push((duration, 3));
+        }
+    }
+
+    /// Record the gap between two elements of one character.
+    pub fn record_element_gap(&mut self, duration: Duration) {
+        let duration = duration.as_secs_f32();
+        push_bounded(&mut self.element_gaps, duration);
+        self.current.push((duration, 1));
+    }
+
+    /// Record the gap between two characters of one word.
+    pub fn record_character_gap(&mut self, duration: Duration) {
+        push_bounded(&mut self.character_gaps, duration.as_secs_f32());
+    }
+
+    /// Attribute the timing of the elements keyed since the last call to the character.
+    ///
+    /// `dit` is used as the reference when no dits have been keyed yet.
+    /// Returns the average timing error of the character's elements,
+    /// relative to their ideal length.
+    pub fn finish_character(&mut self, ch: char, dit: Duration) -> Option<f32> {
+        let dit = self
+            .dits()
+            .map_or(dit.as_secs_f32(), |stats| stats.mean)
+            .max(f32::EPSILON);
+        let current = std::mem::take(&mut self.current);
+        if current.is_empty() {
+            return None;
+        }
+        let error = current
+            .iter()
+            .map(|(duration, units)| (duration / dit - *units as f32).abs() / *units as f32)
+            .sum::<f32>()
+            / current.len() as f32;
+        let entry = self.characters.entry(ch).or_insert((0., 0));
+        entry.0 += error;
+        entry.1 += 1;
+        Some(error)
+    }
+
+    /// Forget the character being keyed without attributing it.
+    pub fn discard_character(&mut self) {
+        self.current.clear();
+    }
+
+    pub fn dits(&self) -> Option<Stats> {
+        Stats::of(&self.dits)
+    }
+
+    pub fn dashes(&self) -> Option<Stats> {
+        Stats::of(&self.dashes)
+    }
+
+    pub fn element_gaps(&self) -> Option<Stats> {
+        Stats::of(&self.element_gaps)
+    }
+
+    pub fn character_gaps(&self) -> Option<Stats> {
+        Stats::of(&self.character_gaps)
+    }
+
+    /// Average dash length compared to the average dit, ideally 3.
+    pub fn ratio(&self) -> Option<f32> {
+        let dits = self.dits()?;
+        let dashes = self.dashes()?;
+        (dits.mean > 0.).then(|| dashes.mean / dits.mean)
+    }
+
+    /// Average character gap in dits, ideally 3.
+    pub fn character_gap_units(&self) -> Option<f32> {
+        let dits = self.dits()?;
+        let gaps = self.character_gaps()?;
+        (dits.mean > 0.).then(|| gaps.mean / dits.mean)
+    }
+
+    /// Consistency of the timing from 0 to 100 percent.
+    pub fn consistency(&self) -> Option<f32> {
+        let variations = [self.dits(), self.dashes(), self.element_gaps()]
+            .into_iter()
+            .flatten()
+            .map(|stats| stats.variation())
+            .collect::<Vec<_>>();
+        if variations.is_empty() {
+            return None;
+        }
+        let variation = variations.iter().sum::<f32>() / variations.len() as f32;
+        Some(((1. - variation) * 100.).clamp(0., 100.))
+    }
+
+    /// Characters with the largest average timing error, worst first.
+    pub fn worst_characters(&self, count: usize) -> Vec<(char, f32)> {
+        let mut characters = self
+            .characters
+            .iter()
+            .map(|(ch, (error, times))| (*ch, error / *times as f32))
+            .collect::<Vec<_>>();
+        characters.sort_by(|a, b| b.1.total_cmp(&a.1));
+        characters.truncate(count);
+        characters
+    }
+}
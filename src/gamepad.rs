@@ -0,0 +1,158 @@
+use std::fmt;
+use std::time::Duration;
+
+use gilrs::{Button, EventType, Gilrs};
+use serde::{Deserialize, Serialize};
+
+use crate::inputs::{Action, KeyBindings, KeyEvents};
+
+/// How often gamepads are read while one is connected
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Gamepad button that can key the straight key or a paddle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 12] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::North,
+        GamepadButton::West,
+        GamepadButton::LeftBumper,
+        GamepadButton::RightBumper,
+        GamepadButton::LeftTrigger,
+        GamepadButton::RightTrigger,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+    ];
+
+    fn button(self) -> Button {
+        match self {
+            GamepadButton::South => Button::South,
+            GamepadButton::East => Button::East,
+            GamepadButton::North => Button::North,
+            GamepadButton::West => Button::West,
+            GamepadButton::LeftBumper => Button::LeftTrigger,
+            GamepadButton::RightBumper => Button::RightTrigger,
+            GamepadButton::LeftTrigger => Button::LeftTrigger2,
+            GamepadButton::RightTrigger => Button::RightTrigger2,
+            GamepadButton::DPadUp => Button::DPadUp,
+            GamepadButton::DPadDown => Button::DPadDown,
+            GamepadButton::DPadLeft => Button::DPadLeft,
+            GamepadButton::DPadRight => Button::DPadRight,
+        }
+    }
+}
+
+impl fmt::Display for GamepadButton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GamepadButton::South => write!(f, "South (A)"),
+            GamepadButton::East => write!(f, "East (B)"),
+            GamepadButton::North => write!(f, "North (Y)"),
+            GamepadButton::West => write!(f, "West (X)"),
+            GamepadButton::LeftBumper => write!(f, "Left bumper"),
+            GamepadButton::RightBumper => write!(f, "Right bumper"),
+            GamepadButton::LeftTrigger => write!(f, "Left trigger"),
+            GamepadButton::RightTrigger => write!(f, "Right trigger"),
+            GamepadButton::DPadUp => write!(f, "D-pad up"),
+            GamepadButton::DPadDown => write!(f, "D-pad down"),
+            GamepadButton::DPadLeft => write!(f, "D-pad left"),
+            GamepadButton::DPadRight => write!(f, "D-pad right"),
+        }
+    }
+}
+
+/// Buttons that key the straight key and the paddles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GamepadMapping {
+    pub straight_key: GamepadButton,
+    pub dot_paddle: GamepadButton,
+    pub dash_paddle: GamepadButton,
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        Self {
+            straight_key: GamepadButton::South,
+            dot_paddle: GamepadButton::LeftBumper,
+            dash_paddle: GamepadButton::RightBumper,
+        }
+    }
+}
+
+impl GamepadMapping {
+    fn actions(&self) -> [(Action, GamepadButton); 3] {
+        [
+            (Action::StraightKey, self.straight_key),
+            (Action::DotPaddle, self.dot_paddle),
+            (Action::DashPaddle, self.dash_paddle),
+        ]
+    }
+}
+
+/// Keying from the buttons of any connected gamepad
+pub struct GamepadKeying {
+    /// `None` when gamepads can't be read on this system
+    gilrs: Option<Gilrs>,
+}
+
+impl GamepadKeying {
+    pub fn new() -> Self {
+        let gilrs = Gilrs::new()
+            .inspect_err(|e| tracing::warn!("Gamepads are unavailable: {}", e))
+            .ok();
+        Self { gilrs }
+    }
+
+    /// Add the button presses and releases since the last call as presses
+    /// and releases of the keys bound to the actions they're mapped to.
+    pub fn key_events(
+        &mut self,
+        ctx: &egui::Context,
+        mapping: &GamepadMapping,
+        bindings: &KeyBindings,
+        events: &mut KeyEvents,
+    ) {
+        let Some(gilrs) = &mut self.gilrs else {
+            return;
+        };
+        while let Some(event) = gilrs.next_event() {
+            let (button, on) = match event.event {
+                EventType::ButtonPressed(button, _) => (button, true),
+                EventType::ButtonReleased(button, _) => (button, false),
+                _ => continue,
+            };
+            for (action, mapped) in mapping.actions() {
+                if mapped.button() != button {
+                    continue;
+                }
+                if on {
+                    events.pressed.push(bindings.key(action));
+                } else {
+                    events.released.push(bindings.key(action));
+                }
+            }
+        }
+        // The app idles between inputs and egui doesn't see gamepads.
+        if gilrs.gamepads().next().is_some() {
+            ctx.request_repaint_after(POLL_INTERVAL);
+        }
+    }
+}
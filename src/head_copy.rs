@@ -0,0 +1,62 @@
+use crate::{drill::Grade, text_source::TextGenerator};
+
+/// Most words sent at once
+pub static MAX_HEAD_COPY_WORDS: usize = 5;
+
+/// Sends words without showing them, the user types what they understood
+pub struct HeadCopy {
+    /// Text that was sent and is waiting to be copied
+    answer: Option<String>,
+    /// The last answer with the grade of the attempt at it
+    last: Option<(String, Grade)>,
+    /// Characters copied correctly and sent, over all attempts
+    correct: usize,
+    total: usize,
+}
+
+impl HeadCopy {
+    pub fn new() -> Self {
+        Self {
+            answer: None,
+            last: None,
+            correct: 0,
+            total: 0,
+        }
+    }
+
+    pub fn answer(&self) -> Option<&str> {
+        self.answer.as_deref()
+    }
+
+    pub fn last(&self) -> Option<&(String, Grade)> {
+        self.last.as_ref()
+    }
+
+    /// Pick random words to send, returning them.
+    pub fn next(&mut self, words: usize, generator: &TextGenerator) -> &str {
+        let mut picked: Vec<String> = Vec::new();
+        for _ in 0..words.clamp(1, MAX_HEAD_COPY_WORDS) {
+            let previous = picked.last().map_or("", String::as_str);
+            let word = generator.next(previous);
+            picked.push(word);
+        }
+        let text = picked.join(" ");
+        self.answer.insert(text)
+    }
+
+    /// Grade the copied text and reveal the answer, returning it with the grade.
+    pub fn submit(&mut self, attempt: &str) -> Option<&(String, Grade)> {
+        let answer = self.answer.take()?;
+        let attempt = attempt.split_whitespace().collect::<Vec<_>>().join(" ");
+        let grade = Grade::new(&answer, &attempt.to_uppercase(), None);
+        let characters = answer.chars().count();
+        self.correct += (grade.accuracy * characters as f32).round() as usize;
+        self.total += characters;
+        Some(self.last.insert((answer, grade)))
+    }
+
+    /// Accuracy over all attempts.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.total > 0).then(|| self.correct as f32 / self.total as f32)
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use morset::consts::Alphabet;
+use serde::{Deserialize, Serialize};
+
+/// Language of the interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Russian,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Russian];
+
+    /// Alphabet the speakers of the language send in
+    pub fn alphabet(self) -> Alphabet {
+        match self {
+            Language::English => Alphabet::Latin,
+            Language::Russian => Alphabet::Cyrillic,
+        }
+    }
+
+    /// Translation of the English text, if there's one
+    fn translate(self, text: &str) -> Option<&'static str> {
+        match self {
+            Language::English => None,
+            Language::Russian => russian(text),
+        }
+    }
+}
+
+/// Shown in the language itself, so it can be found without reading the current one
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::English => write!(f, "English"),
+            Language::Russian => write!(f, "Русский"),
+        }
+    }
+}
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+/// Switch the language the interface is shown in.
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    Language::ALL
+        .into_iter()
+        .find(|language| *language as u8 == LANGUAGE.load(Ordering::Relaxed))
+        .unwrap_or_default()
+}
+
+/// The English text in the current language, left in English when it has no translation.
+pub fn tr(text: &str) -> &str {
+    language().translate(text).unwrap_or(text)
+}
+
+fn russian(text: &str) -> Option<&'static str> {
+    Some(match text {
+        // Main menu
+        "Morse Code Practice" => "Тренажёр азбуки Морзе",
+        "↑↓ and Enter, or 1-4" => "↑↓ и Enter, или 1-4",
+        "Writing" => "Передача",
+        "Listening" => "Приём",
+        "Settings" => "Настройки",
+        "About" => "О программе",
+        "Exit" => "Выход",
+        "License:" => "Лицензия:",
+        "Target:" => "Платформа:",
+        "Source:" => "Исходный код:",
+        "Retry audio" => "Повторить запуск звука",
+        "Audio unavailable — practice will be silent" => {
+            "Звук недоступен — тренировка пройдёт без звука"
+        }
+        "Session summary" => "Итоги занятия",
+
+        // Settings
+        "Language:" => "Язык:",
+        "WPM:" => "Скорость (WPM):",
+        "Quick speeds:" => "Быстрые скорости:",
+        "Weight:" => "Вес:",
+        "Frequency:" => "Частота:",
+        "Volume:" => "Громкость:",
+        "Waveform:" => "Форма сигнала:",
+        "Pan:" => "Панорама:",
+        "Rise time:" => "Время нарастания:",
+        "Fall time:" => "Время спада:",
+        "Sound:" => "Звук:",
+        "Lamp:" => "Лампа:",
+        "Session goal:" => "Цель занятия:",
+        "Vibration:" => "Вибрация:",
+        "Drill text:" => "Текст упражнений:",
+        "Callsigns from:" => "Позывные из:",
+        "Keyer:" => "Ключ:",
+        "MIDI:" => "MIDI:",
+        "Serial key:" => "Ключ на COM-порту:",
+        "WinKeyer:" => "WinKeyer:",
+        "Gamepad:" => "Геймпад:",
+        "Theme:" => "Тема:",
+        "Interface scale:" => "Масштаб интерфейса:",
+        "Transcript size:" => "Размер текста:",
+        "Tick indicator:" => "Индикатор тактов:",
+        "Alphabet:" => "Алфавит:",
+        "Characters:" => "Символы:",
+        "Custom alphabet:" => "Свой алфавит:",
+        "Reset to defaults" => "Сбросить настройки",
+        "Your key bindings were changed. Reset them too?" => {
+            "Назначения клавиш были изменены. Сбросить и их?"
+        }
+        "Reset everything" => "Сбросить всё",
+        "Keep key bindings" => "Оставить клавиши",
+        "Cancel" => "Отмена",
+        "QRS:" => "QRS:",
+        "QRQ:" => "QRQ:",
+        "Lit:" => "Горит:",
+        "Dark:" => "Погашена:",
+        "Replace built-in" => "Вместо встроенного",
+        "Silent mode" => "Без звука",
+        "Vibrate with the tone" => "Вибрировать с тоном",
+        "High contrast" => "Высокий контраст",
+        "Flash with the tone" => "Мигать с тоном",
+        "Koch characters only" => "Только символы Коха",
+        "Accented letters" => "Буквы с диакритикой",
+        "Back" => "Назад",
+        "Load alphabet" => "Загрузить алфавит",
+        "Save" => "Сохранить",
+        "Refresh" => "Обновить",
+        "Remove" => "Удалить",
+        "Keys a transmitter with what's keyed and typed on the Writing screen" => {
+            "Манипулирует передатчиком тем, что передаётся и набирается на экране передачи"
+        }
+        "DTR and RTS are held high to supply the key" => {
+            "DTR и RTS держатся в высоком уровне, чтобы питать ключ"
+        }
+        "JSON object mapping characters to sequences, like {\"A\": \".-\"}" => {
+            "JSON-объект, сопоставляющий символам коды, например {\"A\": \".-\"}"
+        }
+        "Use only these characters instead of adding them" => {
+            "Использовать только эти символы, а не добавлять их"
+        }
+        "Practice keying and decoding without any sound" => {
+            "Тренироваться в передаче и приёме без звука"
+        }
+        "Show the session summary after practicing this long" => {
+            "Показать итоги занятия после стольких минут тренировки"
+        }
+        "Only works in mobile browsers that support it" => {
+            "Работает только в мобильных браузерах, которые это поддерживают"
+        }
+        "Double click to center" => "Двойной щелчок — по центру",
+        "Right click to remove" => "Правый щелчок — удалить",
+        "Save the current frequency as a preset" => "Сохранить текущую частоту как предустановку",
+        "Mute the sound to practice with the lamp alone" => {
+            "Выключите звук, чтобы тренироваться только с лампой"
+        }
+        "Only use the characters unlocked in the Koch trainer" => {
+            "Использовать только символы, открытые в тренажёре Коха"
+        }
+
+        // Writing
+        "Controls:" => "Управление:",
+        "Settings:" => "Настройки:",
+        "Dash threshold:" => "Порог тире:",
+        "Decode tolerance:" => "Допуск декодера:",
+        "History:" => "История:",
+        "Clear all" => "Очистить всё",
+        "Replay:" => "Повтор:",
+        "Stop" => "Стоп",
+        "As keyed" => "Как передано",
+        "Perfect timing" => "С идеальным ритмом",
+        "Metronome:" => "Метроном:",
+        "Output:" => "Вывод:",
+        "Timestamps" => "Время",
+        "Timing analysis" => "Анализ ритма",
+        "Timeline" => "Шкала времени",
+        "Stats" => "Статистика",
+        "Shortcuts" => "Клавиши",
+        "Scope" => "Осциллограф",
+        "Spectrum" => "Спектр",
+        "Partner" => "Партнёр",
+        "Sending drill" => "Упражнение на передачу",
+        "Type to hear" => "Набор со звуком",
+        "Touch keys" => "Сенсорные ключи",
+        "With header" => "С заголовком",
+        "Swap paddles" => "Поменять лепестки",
+        "Live" => "Сразу",
+        "Play" => "Играть",
+        "Send every character as it's typed" => "Передавать каждый символ по мере набора",
+        "Paused" => "Пауза",
+        "Resume" => "Продолжить",
+        "Main Menu" => "Главное меню",
+        "Clear all text?" => "Очистить весь текст?",
+        "The text can be brought back with Undo (Ctrl+Z)." => {
+            "Текст можно вернуть отменой (Ctrl+Z)."
+        }
+        "Clear" => "Очистить",
+        "Session stats" => "Статистика занятия",
+        "Keyboard shortcuts" => "Сочетания клавиш",
+        "Press Escape to close." => "Нажмите Escape, чтобы закрыть.",
+        "Cheatsheet" => "Шпаргалка",
+        "Nothing matches the search." => "Ничего не найдено.",
+        "Prosigns" => "Процедурные знаки",
+        "Undo" => "Отменить",
+        "Redo" => "Повторить",
+        "Save session" => "Сохранить сеанс",
+        "Save transcript" => "Сохранить текст",
+        "Key some characters to see how even your timing is." => {
+            "Передайте несколько символов, чтобы увидеть, насколько ровный у вас ритм."
+        }
+        "Dash:dit ratio" => "Отношение тире к точке",
+        "Least accurate characters:" => "Наименее точные символы:",
+        "Reset" => "Сбросить",
+        "Check" => "Проверить",
+        "Skip" => "Пропустить",
+        "Relay:" => "Ретранслятор:",
+        "Connect" => "Подключиться",
+        "Disconnect" => "Отключиться",
+        "Not connected" => "Не подключено",
+        "Connecting..." => "Подключение...",
+        "Connected, your keying is sent to the partner" => {
+            "Подключено, ваша передача отправляется партнёру"
+        }
+        "Partner sent:" => "Партнёр передал:",
+        "Add" => "Добавить",
+        "Envelope" => "Огибающая",
+        "Waveform" => "Форма сигнала",
+        "No audio output" => "Нет вывода звука",
+        "Press a key..." => "Нажмите клавишу...",
+        "Tune" => "Подстройка",
+        "Default" => "По умолчанию",
+        "Type text to hear it" => "Наберите текст, чтобы услышать его",
+        "Dits" => "Точки",
+        "Dashes" => "Тире",
+        "Element gaps" => "Паузы между элементами",
+        "Character gaps" => "Паузы между символами",
+        "ideal 3:1" => "идеально 3:1",
+        "Dashes are too short" => "Тире слишком короткие",
+        "Dashes are too long" => "Тире слишком длинные",
+        "Gaps between characters are too long" => "Паузы между символами слишком длинные",
+        "Character or pattern, e.g. -." => "Символ или код, например -.",
+        "Custom prosigns" => "Свои процедурные знаки",
+        "Abbreviations" => "Сокращения",
+        "Letters sent in place of digits in fast exchanges, like 5NN for 599." => {
+            "Буквы, которые передают вместо цифр в быстром обмене, например 5NN вместо 599."
+        }
+        "Saved" => "Сохранено",
+        "Saving was cancelled" => "Сохранение отменено",
+        "Failed to serialize the session" => "Не удалось записать занятие",
+        "Key" => "Ключ",
+        "Dit" => "Точка",
+        "Dah" => "Тире",
+
+        // Listening
+        "Listening Mode" => "Режим приёма",
+        "Fading depth:" => "Глубина замираний:",
+        "Signal to noise:" => "Сигнал/шум:",
+        "Speed:" => "Скорость:",
+        "Overall:" => "Общая:",
+        "Step:" => "Шаг:",
+        "Streak:" => "Серия:",
+        "Range:" => "Диапазон:",
+        "to" => "до",
+        "Load WAV" => "Загрузить WAV",
+        "No signal found" => "Сигнал не найден",
+        "Character accuracy" => "Точность по символам",
+        "Back to Menu" => "В меню",
+        "Playback:" => "Воспроизведение:",
+        "Session replay" => "Повтор сеанса",
+        "Load session" => "Загрузить сеанс",
+        "Pause" => "Пауза",
+        "Restart" => "Сначала",
+        "Stop replay" => "Остановить повтор",
+        "Replay as keyed" => "Повторить как передано",
+        "Replay with perfect timing" => "Повторить с идеальным ритмом",
+        "Text reading" => "Чтение текста",
+        "Load text" => "Загрузить текст",
+        "Reaction test" => "Тест реакции",
+        "Start run" => "Начать серию",
+        "Koch trainer" => "Тренажёр Коха",
+        "Lesson:" => "Урок:",
+        "Name the characters you hear by typing them." => {
+            "Называйте услышанные символы, набирая их."
+        }
+        "Start drill" => "Начать упражнение",
+        "Head copy" => "Приём на слух",
+        "Words:" => "Слова:",
+        "Copy the words you hear in your head, then type them." => {
+            "Принимайте слова в уме, а затем наберите их."
+        }
+        "Reveal" => "Показать",
+        "Play words" => "Играть слова",
+        "Code groups" => "Кодовые группы",
+        "Groups:" => "Группы:",
+        "Contest exchanges" => "Контрольные номера",
+        "Copy signal reports and exchanges, in digits or cut numbers." => {
+            "Принимайте рапорты и контрольные номера цифрами или сокращёнными цифрами."
+        }
+        "Play exchange" => "Играть номер",
+        "QSO" => "QSO",
+        "Call CQ and copy the station that answers." => "Дайте CQ и примите ответившую станцию.",
+        "Reply" => "Ответить",
+        "Microphone" => "Микрофон",
+        "From the microphone" => "С микрофона",
+        "Noise gate:" => "Шумовой порог:",
+        "Expected WPM:" => "Ожидаемая скорость:",
+        "Fading (QSB)" => "Замирания (QSB)",
+        "Noise (QRN)" => "Помехи (QRN)",
+        "Interference (QRM)" => "Мешающая станция (QRM)",
+        "Farnsworth" => "Фарнсворт",
+        "Adaptive speed" => "Адаптивная скорость",
+        "Say the character" => "Называть символ",
+        "Random pitch" => "Случайный тон",
+        "Cut numbers" => "Сокращённые цифры",
+        "🏆 New record!" => "🏆 Новый рекорд!",
+        "Copied correctly" => "Принято верно",
+        "Replay last" => "Повторить последнее",
+        "Repeat" => "Повторить",
+        "Slow down after a wrong answer, speed up after a streak of right ones" => {
+            "Замедляться после ошибки и ускоряться после серии верных ответов"
+        }
+        "Said a moment after its morse, answer first to skip it" => {
+            "Произносится вскоре после кода, ответьте раньше, чтобы пропустить"
+        }
+        "Send T for 0, N for 9 and A for 1" => "Передавать T вместо 0, N вместо 9 и A вместо 1",
+        "What did you hear?" => "Что вы услышали?",
+        "Type the groups you copy, separated by spaces" => "Вводите принятые группы через пробел",
+        "Copy the reply" => "Запишите ответ",
+
+        // Stats
+        "Send or copy some characters to see how you did." => {
+            "Передайте или примите несколько символов, чтобы увидеть результат."
+        }
+        "Duration" => "Длительность",
+        "Active" => "Активно",
+        "Idle" => "Простой",
+        "Characters sent" => "Передано символов",
+        "Characters received" => "Принято символов",
+        "Effective speed" => "Реальная скорость",
+        "Accuracy" => "Точность",
+        "Speed" => "Скорость",
+        "Most missed characters:" => "Чаще всего пропущены:",
+        "Export CSV" => "Экспорт CSV",
+        "Copy or send some graded characters to see which ones need work." => {
+            "Примите или передайте несколько символов, чтобы увидеть, над какими стоит поработать."
+        }
+        "No character stands out as weak yet." => "Слабых символов пока нет.",
+        "Weakest characters:" => "Самые слабые символы:",
+        "Reset stats" => "Сбросить статистику",
+        "Session goal reached, nice work!" => "Цель занятия достигнута, отличная работа!",
+        "Send code groups made of these characters" => "Передать кодовые группы из этих символов",
+        "Drill them" => "Тренировать",
+        _ => return None,
+    })
+}
@@ -1,12 +1,18 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use egui::{InputState, Key};
+use serde::{Deserialize, Serialize};
 
 pub trait InputStateExt {
     fn key_just_pressed(&self, desired_key: Key) -> bool;
+    fn key_just_released(&self, desired_key: Key) -> bool;
+    fn any_key_just_pressed(&self) -> Option<Key>;
 }
 
 impl InputStateExt for InputState {
     // This function checks if a specific key was just pressed (not held down)
-    // by filtering the events for key press events with repeat field set to true.
+    // by looking for key press events that aren't repeats of a held key.
     fn key_just_pressed(&self, desired_key: Key) -> bool {
         self.events
             .iter()
@@ -20,4 +26,334 @@ impl InputStateExt for InputState {
             .count()
             > 0
     }
+
+    // Checks if a specific key was released this frame, going by the events
+    // alone so it pairs with `key_just_pressed` whatever the key was doing before.
+    fn key_just_released(&self, desired_key: Key) -> bool {
+        self.events.iter().any(|event| {
+            matches!(
+                event,
+                egui::Event::Key { key, pressed: false, .. }
+                if *key == desired_key
+            )
+        })
+    }
+
+    // Returns the first key that was just pressed (not held down) this frame.
+    fn any_key_just_pressed(&self) -> Option<Key> {
+        self.events.iter().find_map(|event| match event {
+            egui::Event::Key {
+                key,
+                pressed: true,
+                repeat: false,
+                ..
+            } => Some(*key),
+            _ => None,
+        })
+    }
+}
+
+/// Keys pressed and released during a frame, kept apart from egui so keying can be scripted
+#[derive(Debug, Clone, Default)]
+pub struct KeyEvents {
+    pub pressed: Vec<Key>,
+    pub released: Vec<Key>,
+}
+
+impl KeyEvents {
+    /// Collect the key events of the frame, leaving out repeats of held keys.
+    pub fn from_input(input: &InputState) -> Self {
+        let mut events = Self::default();
+        for event in &input.events {
+            match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    repeat: false,
+                    ..
+                } => events.pressed.push(*key),
+                egui::Event::Key {
+                    key,
+                    pressed: false,
+                    ..
+                } => events.released.push(*key),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+impl InputStateExt for KeyEvents {
+    fn key_just_pressed(&self, desired_key: Key) -> bool {
+        self.pressed.contains(&desired_key)
+    }
+
+    fn key_just_released(&self, desired_key: Key) -> bool {
+        self.released.contains(&desired_key)
+    }
+
+    fn any_key_just_pressed(&self) -> Option<Key> {
+        self.pressed.first().copied()
+    }
+}
+
+/// Actions of the writing screen that can be bound to a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    ReturnToMenu,
+    Backspace,
+    ClearAll,
+    DecreaseWpm,
+    IncreaseWpm,
+    SlowSpeed,
+    FastSpeed,
+    DecreaseFrequency,
+    IncreaseFrequency,
+    DecreaseVolume,
+    IncreaseVolume,
+    ToggleCheatSheet,
+    ToggleMute,
+    ToggleHelp,
+    StraightKey,
+    DotPaddle,
+    DashPaddle,
+}
+
+impl Action {
+    pub const ALL: [Action; 17] = [
+        Action::ReturnToMenu,
+        Action::Backspace,
+        Action::ClearAll,
+        Action::DecreaseWpm,
+        Action::IncreaseWpm,
+        Action::SlowSpeed,
+        Action::FastSpeed,
+        Action::DecreaseFrequency,
+        Action::IncreaseFrequency,
+        Action::DecreaseVolume,
+        Action::IncreaseVolume,
+        Action::ToggleCheatSheet,
+        Action::ToggleMute,
+        Action::ToggleHelp,
+        Action::StraightKey,
+        Action::DotPaddle,
+        Action::DashPaddle,
+    ];
+
+    pub fn default_key(self) -> Key {
+        match self {
+            Action::ReturnToMenu => Key::Escape,
+            Action::Backspace => Key::Backspace,
+            Action::ClearAll => Key::Delete,
+            Action::DecreaseWpm => Key::F1,
+            Action::IncreaseWpm => Key::F2,
+            Action::SlowSpeed => Key::F7,
+            Action::FastSpeed => Key::F8,
+            Action::DecreaseFrequency => Key::F3,
+            Action::IncreaseFrequency => Key::F4,
+            Action::DecreaseVolume => Key::F5,
+            Action::IncreaseVolume => Key::F6,
+            Action::ToggleCheatSheet => Key::C,
+            Action::ToggleMute => Key::N,
+            Action::ToggleHelp => Key::Questionmark,
+            Action::StraightKey => Key::Space,
+            Action::DotPaddle => Key::OpenBracket,
+            Action::DashPaddle => Key::CloseBracket,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::ReturnToMenu => "Pause",
+            Action::Backspace => "Delete last character",
+            Action::ClearAll => "Clear all text",
+            Action::DecreaseWpm => "Decrease WPM",
+            Action::IncreaseWpm => "Increase WPM",
+            Action::SlowSpeed => "Slow down to the QRS speed",
+            Action::FastSpeed => "Speed up to the QRQ speed",
+            Action::DecreaseFrequency => "Decrease frequency",
+            Action::IncreaseFrequency => "Increase frequency",
+            Action::DecreaseVolume => "Decrease volume",
+            Action::IncreaseVolume => "Increase volume",
+            Action::ToggleCheatSheet => "Toggle cheat sheet",
+            Action::ToggleMute => "Mute sound",
+            Action::ToggleHelp => "Show keyboard shortcuts",
+            Action::StraightKey => "Emit a signal",
+            Action::DotPaddle => "Dot paddle",
+            Action::DashPaddle => "Dash paddle",
+        }
+    }
+
+    pub fn category(self) -> ActionCategory {
+        match self {
+            Action::StraightKey | Action::DotPaddle | Action::DashPaddle => ActionCategory::Keying,
+            Action::Backspace | Action::ClearAll => ActionCategory::Text,
+            Action::DecreaseWpm | Action::IncreaseWpm | Action::SlowSpeed | Action::FastSpeed => {
+                ActionCategory::Speed
+            }
+            Action::DecreaseFrequency
+            | Action::IncreaseFrequency
+            | Action::DecreaseVolume
+            | Action::IncreaseVolume
+            | Action::ToggleMute => ActionCategory::Sound,
+            Action::ReturnToMenu | Action::ToggleCheatSheet | Action::ToggleHelp => {
+                ActionCategory::General
+            }
+        }
+    }
+}
+
+/// Keys assigned to every action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    keys: HashMap<Action, Key>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: Action) -> Key {
+        self.keys
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    /// Find the action bound to the given key.
+    pub fn action(&self, key: Key) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| self.key(*action) == key)
+    }
+
+    /// Bind the key to the action.
+    /// If the key is already used by another action,
+    /// nothing is changed and the conflicting action is returned.
+    pub fn bind(&mut self, action: Action, key: Key) -> Result<(), Action> {
+        match self.action(key) {
+            Some(other) if other != action => Err(other),
+            _ => {
+                self.keys.insert(action, key);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Groups the shortcuts are listed under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionCategory {
+    Keying,
+    Text,
+    Speed,
+    Sound,
+    General,
+}
+
+impl ActionCategory {
+    pub const ALL: [ActionCategory; 5] = [
+        ActionCategory::Keying,
+        ActionCategory::Text,
+        ActionCategory::Speed,
+        ActionCategory::Sound,
+        ActionCategory::General,
+    ];
+}
+
+impl fmt::Display for ActionCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionCategory::Keying => write!(f, "Keying"),
+            ActionCategory::Text => write!(f, "Text"),
+            ActionCategory::Speed => write!(f, "Speed"),
+            ActionCategory::Sound => write!(f, "Sound"),
+            ActionCategory::General => write!(f, "General"),
+        }
+    }
+}
+
+/// Shortcuts that can't be rebound, with their category, keys and description
+pub const FIXED_SHORTCUTS: [(ActionCategory, &str, &str); 3] = [
+    (ActionCategory::Text, "Ctrl+Z", "Undo"),
+    (ActionCategory::Text, "Ctrl+Y", "Redo"),
+    (ActionCategory::Text, "Ctrl+Shift+Z", "Redo"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_event(key: Key, pressed: bool, repeat: bool) -> egui::Event {
+        egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed,
+            repeat,
+            modifiers: egui::Modifiers::NONE,
+        }
+    }
+
+    fn input(events: Vec<egui::Event>) -> InputState {
+        let mut input = InputState::default();
+        input.events = events;
+        input
+    }
+
+    #[test]
+    fn repeats_are_not_presses() {
+        let held = input(vec![key_event(Key::Space, true, true)]);
+        assert!(!held.key_just_pressed(Key::Space));
+        assert!(!held.key_just_released(Key::Space));
+        assert_eq!(held.any_key_just_pressed(), None);
+
+        let pressed = input(vec![
+            key_event(Key::Space, true, true),
+            key_event(Key::A, true, false),
+        ]);
+        assert!(pressed.key_just_pressed(Key::A));
+        assert!(!pressed.key_just_pressed(Key::Space));
+        assert_eq!(pressed.any_key_just_pressed(), Some(Key::A));
+    }
+
+    #[test]
+    fn press_and_release_in_the_same_frame() {
+        let input = input(vec![
+            key_event(Key::Space, true, false),
+            key_event(Key::Space, false, false),
+        ]);
+        assert!(input.key_just_pressed(Key::Space));
+        assert!(input.key_just_released(Key::Space));
+
+        let events = KeyEvents::from_input(&input);
+        assert_eq!(events.pressed, vec![Key::Space]);
+        assert_eq!(events.released, vec![Key::Space]);
+        assert!(events.key_just_pressed(Key::Space));
+        assert!(events.key_just_released(Key::Space));
+        assert!(!events.key_just_pressed(Key::A));
+    }
+
+    #[test]
+    fn key_events_leave_out_repeats_and_other_events() {
+        let input = input(vec![
+            key_event(Key::Period, true, false),
+            key_event(Key::Period, true, true),
+            egui::Event::Text(".".to_string()),
+            key_event(Key::Minus, true, false),
+            key_event(Key::Period, false, false),
+        ]);
+        let events = KeyEvents::from_input(&input);
+        assert_eq!(events.pressed, vec![Key::Period, Key::Minus]);
+        assert_eq!(events.released, vec![Key::Period]);
+        assert_eq!(events.any_key_just_pressed(), Some(Key::Period));
+    }
 }
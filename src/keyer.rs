@@ -0,0 +1,440 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+use morset::encoder::Timing;
+use serde::{Deserialize, Serialize};
+
+/// How the user's key presses are turned into elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KeyerMode {
+    /// Tone lasts as long as the key is held
+    #[default]
+    Straight,
+    /// Paddles generate timed elements, only held paddles are considered
+    IambicA,
+    /// Like Iambic A, but a paddle pressed during an element is remembered,
+    /// so releasing a squeeze sends one more alternate element
+    IambicB,
+}
+
+impl KeyerMode {
+    pub const ALL: [KeyerMode; 3] = [KeyerMode::Straight, KeyerMode::IambicA, KeyerMode::IambicB];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyerMode::Straight => {
+                "A single key, the tone lasts as long as it's held so you time every dit and dah"
+            }
+            KeyerMode::IambicA => {
+                "Two paddles, one sends dits and the other dahs at the right length. \
+                 Squeezing both alternates them, letting go stops after the element being sent"
+            }
+            KeyerMode::IambicB => {
+                "Like Iambic A, but letting go of a squeeze sends one more alternate element, \
+                 which saves a squeeze in letters like C and Y"
+            }
+        }
+    }
+}
+
+impl fmt::Display for KeyerMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyerMode::Straight => write!(f, "Straight"),
+            KeyerMode::IambicA => write!(f, "Iambic A"),
+            KeyerMode::IambicB => write!(f, "Iambic B"),
+        }
+    }
+}
+
+/// Paddle of an iambic key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IambicKey {
+    Dot,
+    Dash,
+}
+
+impl IambicKey {
+    pub fn opposite(self) -> Self {
+        match self {
+            IambicKey::Dot => IambicKey::Dash,
+            IambicKey::Dash => IambicKey::Dot,
+        }
+    }
+
+    /// Length of the element in dit units
+    pub fn units(self) -> u32 {
+        match self {
+            IambicKey::Dot => 1,
+            IambicKey::Dash => 3,
+        }
+    }
+
+    fn timing(self) -> Timing {
+        Timing {
+            on: true,
+            units: self.units(),
+        }
+    }
+
+    pub fn symbol(self) -> char {
+        match self {
+            IambicKey::Dot => '.',
+            IambicKey::Dash => '-',
+        }
+    }
+}
+
+/// Events produced by the scheduler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyerEvent {
+    /// An element has started, the tone should be on
+    ToneOn,
+    /// The element has been sent completely, the tone should be off
+    ToneOff(IambicKey),
+}
+
+/// Gap of one dit unit after every element
+const ELEMENT_GAP: Timing = Timing {
+    on: false,
+    units: 1,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Idle,
+    /// Element is being sent
+    Element(IambicKey),
+    /// Gap after an element
+    Gap(IambicKey),
+}
+
+/// Turns paddle presses into a stream of correctly timed elements
+///
+/// Timing comes from the element lengths alone: a dit is 1 unit on, a dash 3,
+/// each followed by 1 unit off, whenever during a frame the paddles were pressed.
+pub struct IambicScheduler {
+    mode: KeyerMode,
+    state: State,
+    /// Time spent in the current state
+    elapsed: Duration,
+    dot_held: bool,
+    dash_held: bool,
+    dot_memory: bool,
+    dash_memory: bool,
+    events: VecDeque<KeyerEvent>,
+}
+
+impl IambicScheduler {
+    pub fn new(mode: KeyerMode) -> Self {
+        Self {
+            mode,
+            state: State::Idle,
+            elapsed: Duration::ZERO,
+            dot_held: false,
+            dash_held: false,
+            dot_memory: false,
+            dash_memory: false,
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: KeyerMode) {
+        self.mode = mode;
+    }
+
+    /// Whether the paddle is held down.
+    pub fn held(&self, key: IambicKey) -> bool {
+        match key {
+            IambicKey::Dot => self.dot_held,
+            IambicKey::Dash => self.dash_held,
+        }
+    }
+
+    fn memory_mut(&mut self, key: IambicKey) -> &mut bool {
+        match key {
+            IambicKey::Dot => &mut self.dot_memory,
+            IambicKey::Dash => &mut self.dash_memory,
+        }
+    }
+
+    pub fn press_key(&mut self, key: IambicKey) {
+        match key {
+            IambicKey::Dot => self.dot_held = true,
+            IambicKey::Dash => self.dash_held = true,
+        }
+        match self.state {
+            State::Idle => {
+                self.elapsed = Duration::ZERO;
+                self.start_element(key);
+            }
+            State::Element(current) | State::Gap(current) => {
+                if self.mode == KeyerMode::IambicB && key != current {
+                    *self.memory_mut(key) = true;
+                }
+            }
+        }
+    }
+
+    pub fn release_key(&mut self, key: IambicKey) {
+        match key {
+            IambicKey::Dot => self.dot_held = false,
+            IambicKey::Dash => self.dash_held = false,
+        }
+    }
+
+    /// Forget which paddles are held, letting the current element finish.
+    pub fn release_all(&mut self) {
+        self.dot_held = false;
+        self.dash_held = false;
+        self.dot_memory = false;
+        self.dash_memory = false;
+    }
+
+    /// Stop sending immediately and forget all paddle state.
+    pub fn reset(&mut self) {
+        self.release_all();
+        self.state = State::Idle;
+        self.elapsed = Duration::ZERO;
+        self.events.clear();
+    }
+
+    fn start_element(&mut self, key: IambicKey) {
+        *self.memory_mut(key) = false;
+        self.state = State::Element(key);
+        self.events.push_back(KeyerEvent::ToneOn);
+    }
+
+    /// Choose the element to send after `last`, if any.
+    fn next_element(&self, last: IambicKey) -> Option<IambicKey> {
+        let opposite = last.opposite();
+        let opposite_memory = match opposite {
+            IambicKey::Dot => self.dot_memory,
+            IambicKey::Dash => self.dash_memory,
+        };
+        if self.held(opposite) || opposite_memory {
+            Some(opposite)
+        } else if self.held(last) {
+            Some(last)
+        } else {
+            None
+        }
+    }
+
+    /// How long the current state lasts, `None` when idle.
+    fn state_duration(&self, dit_duration: Duration, weight: f32) -> Option<Duration> {
+        match self.state {
+            State::Idle => None,
+            State::Element(key) => Some(key.timing().duration(dit_duration, weight)),
+            State::Gap(_) => Some(ELEMENT_GAP.duration(dit_duration, weight)),
+        }
+    }
+
+    /// Move on from a state that has lasted its full duration.
+    fn step(&mut self) {
+        match self.state {
+            State::Idle => {}
+            State::Element(key) => {
+                self.events.push_back(KeyerEvent::ToneOff(key));
+                self.state = State::Gap(key);
+            }
+            State::Gap(last) => match self.next_element(last) {
+                Some(key) => self.start_element(key),
+                None => self.state = State::Idle,
+            },
+        }
+    }
+
+    /// Progress the schedule by the time passed since the last update.
+    ///
+    /// Element and gap lengths follow the dit length and weight, see [`Timing::duration`].
+    pub fn update(&mut self, delta: Duration, dit_duration: Duration, weight: f32) {
+        if self.state == State::Idle {
+            return;
+        }
+        self.elapsed += delta;
+        while let Some(duration) = self.state_duration(dit_duration, weight)
+            && self.elapsed >= duration
+        {
+            self.elapsed -= duration;
+            self.step();
+        }
+    }
+
+    /// Time left until the current element or gap ends, `None` when idle.
+    pub fn next_change(&self, dit_duration: Duration, weight: f32) -> Option<Duration> {
+        self.state_duration(dit_duration, weight)
+            .map(|duration| duration.saturating_sub(self.elapsed))
+    }
+
+    /// Take the oldest event that hasn't been handled yet.
+    pub fn next_event(&mut self) -> Option<KeyerEvent> {
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use morset::encoder::STANDARD_WEIGHT;
+
+    const DIT: Duration = Duration::from_millis(100);
+    /// The scheduler is driven a quarter of a dit at a time
+    const TICKS_PER_DIT: usize = 4;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Paddle {
+        Press(IambicKey),
+        Release(IambicKey),
+    }
+
+    /// Drive the scheduler for `ticks`, applying the paddle actions at the start of their tick.
+    /// Returns every element sent with the tick its tone started on.
+    fn run(mode: KeyerMode, actions: &[(usize, Paddle)], ticks: usize) -> Vec<(usize, char)> {
+        let mut keyer = IambicScheduler::new(mode);
+        let mut started = None;
+        let mut sent = Vec::new();
+        let mut collect = |keyer: &mut IambicScheduler, tick: usize| {
+            while let Some(event) = keyer.next_event() {
+                match event {
+                    KeyerEvent::ToneOn => started = Some(tick),
+                    KeyerEvent::ToneOff(key) => {
+                        sent.push((started.take().unwrap(), key.symbol()));
+                    }
+                }
+            }
+        };
+        for tick in 0..ticks {
+            for (_, action) in actions.iter().filter(|(at, _)| *at == tick) {
+                match *action {
+                    Paddle::Press(key) => keyer.press_key(key),
+                    Paddle::Release(key) => keyer.release_key(key),
+                }
+            }
+            collect(&mut keyer, tick);
+            keyer.update(DIT / TICKS_PER_DIT as u32, DIT, STANDARD_WEIGHT);
+            collect(&mut keyer, tick + 1);
+        }
+        sent
+    }
+
+    #[test]
+    fn tap_sends_one_element() {
+        for mode in [KeyerMode::IambicA, KeyerMode::IambicB] {
+            for key in [IambicKey::Dot, IambicKey::Dash] {
+                let actions = [(0, Paddle::Press(key)), (0, Paddle::Release(key))];
+                assert_eq!(run(mode, &actions, 40), [(0, key.symbol())], "{:?}", mode);
+            }
+        }
+    }
+
+    #[test]
+    fn held_paddle_repeats_its_element() {
+        let dot = IambicKey::Dot;
+        let actions = [(0, Paddle::Press(dot)), (18, Paddle::Release(dot))];
+        assert_eq!(
+            run(KeyerMode::IambicA, &actions, 60),
+            [(0, '.'), (8, '.'), (16, '.')]
+        );
+
+        let dash = IambicKey::Dash;
+        let actions = [(0, Paddle::Press(dash)), (34, Paddle::Release(dash))];
+        assert_eq!(
+            run(KeyerMode::IambicA, &actions, 80),
+            [(0, '-'), (16, '-'), (32, '-')]
+        );
+    }
+
+    #[test]
+    fn paddle_pressed_during_an_element_is_remembered_only_in_mode_b() {
+        let (dot, dash) = (IambicKey::Dot, IambicKey::Dash);
+        // The dot paddle is tapped while the dash is sent, both are up before it ends.
+        let actions = [
+            (0, Paddle::Press(dash)),
+            (4, Paddle::Press(dot)),
+            (6, Paddle::Release(dot)),
+            (6, Paddle::Release(dash)),
+        ];
+        assert_eq!(run(KeyerMode::IambicA, &actions, 60), [(0, '-')]);
+        assert_eq!(run(KeyerMode::IambicB, &actions, 60), [(0, '-'), (16, '.')]);
+    }
+
+    #[test]
+    fn paddle_pressed_while_idle_starts_at_once() {
+        let dash = IambicKey::Dash;
+        let actions = [
+            (0, Paddle::Press(IambicKey::Dot)),
+            (0, Paddle::Release(IambicKey::Dot)),
+            (13, Paddle::Press(dash)),
+            (14, Paddle::Release(dash)),
+        ];
+        assert_eq!(run(KeyerMode::IambicA, &actions, 60), [(0, '.'), (13, '-')]);
+    }
+
+    /// Ticks from the start of an element to the start of the next one
+    fn period(symbol: char) -> usize {
+        let key = if symbol == '.' {
+            IambicKey::Dot
+        } else {
+            IambicKey::Dash
+        };
+        (key.units() as usize + 1) * TICKS_PER_DIT
+    }
+
+    #[test]
+    fn squeeze_alternates_from_any_offset() {
+        for mode in [KeyerMode::IambicA, KeyerMode::IambicB] {
+            for first in [IambicKey::Dot, IambicKey::Dash] {
+                let second = first.opposite();
+                // The second paddle joins during the first element, its gap or exactly as it starts.
+                let joins = 0..period(first.symbol());
+                for offset in joins {
+                    let actions = [
+                        (0, Paddle::Press(first)),
+                        (offset, Paddle::Press(second)),
+                        (100, Paddle::Release(first)),
+                        (100, Paddle::Release(second)),
+                    ];
+                    let sent = run(mode, &actions, 200);
+                    let symbols = sent.iter().map(|(_, symbol)| *symbol).collect::<String>();
+                    let expected = [first.symbol(), second.symbol()]
+                        .into_iter()
+                        .cycle()
+                        .take(symbols.len())
+                        .collect::<String>();
+                    assert_eq!(
+                        symbols, expected,
+                        "{:?} squeezed {} ticks after {:?}",
+                        mode, offset, first
+                    );
+                    assert!(
+                        symbols.len() >= 8,
+                        "{:?} squeezed {} ticks after {:?} sent {}",
+                        mode,
+                        offset,
+                        first,
+                        symbols
+                    );
+                    // Every element starts right after the gap of the one before.
+                    assert_eq!(sent[0].0, 0);
+                    for pair in sent.windows(2) {
+                        assert_eq!(
+                            pair[1].0,
+                            pair[0].0 + period(pair[0].1),
+                            "{:?} squeezed {} ticks after {:?} sent {:?}",
+                            mode,
+                            offset,
+                            first,
+                            sent
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Order in which the Koch method introduces characters
+pub static KOCH_ORDER: [char; 40] = [
+    'K', 'M', 'R', 'S', 'U', 'A', 'P', 'T', 'L', 'O', 'W', 'I', '.', 'N', 'J', 'E', 'F', '0', 'Y',
+    ',', 'V', 'G', '5', '/', 'Q', '9', 'Z', 'H', '3', '8', 'B', '?', '4', '2', '7', 'C', '1', 'D',
+    '6', 'X',
+];
+
+/// Accuracy needed to unlock the next character
+pub static ADVANCE_ACCURACY: f32 = 0.9;
+
+/// Number of recent answers the accuracy is measured over
+pub static ACCURACY_WINDOW: usize = 20;
+
+/// Last lesson, in which all characters are unlocked
+pub static MAX_LESSON: usize = KOCH_ORDER.len() - 1;
+
+/// Teaches characters at full speed, unlocking them one by one
+pub struct KochTrainer {
+    /// Lesson 1 has the first two characters, every further lesson adds one
+    lesson: usize,
+    /// Whether the recent answers of this lesson were correct
+    recent: VecDeque<bool>,
+    /// Correct answers and attempts for every character this session
+    characters: HashMap<char, (u32, u32)>,
+    /// Accuracy over the full window of answers up to the last one, before unlocking
+    window_accuracy: Option<f32>,
+}
+
+/// Characters unlocked in the lesson.
+pub fn unlocked(lesson: usize) -> &'static [char] {
+    &KOCH_ORDER[..=lesson.clamp(1, MAX_LESSON)]
+}
+
+/// Name the records of a lesson are kept under.
+pub fn record_name(lesson: usize) -> String {
+    format!("koch-{}", lesson)
+}
+
+impl KochTrainer {
+    pub fn new(lesson: usize) -> Self {
+        Self {
+            lesson: lesson.clamp(1, MAX_LESSON),
+            recent: VecDeque::new(),
+            characters: HashMap::new(),
+            window_accuracy: None,
+        }
+    }
+
+    pub fn lesson(&self) -> usize {
+        self.lesson
+    }
+
+    /// Jump to another lesson, starting its accuracy over.
+    pub fn set_lesson(&mut self, lesson: usize) {
+        self.lesson = lesson.clamp(1, MAX_LESSON);
+        self.recent.clear();
+    }
+
+    /// Characters unlocked in the current lesson.
+    pub fn characters(&self) -> &'static [char] {
+        unlocked(self.lesson)
+    }
+
+    /// Pick a random unlocked character, the newest one twice as often.
+    pub fn pick(&self) -> char {
+        let characters = self.characters();
+        let index = fastrand::usize(..=characters.len());
+        characters[index.min(characters.len() - 1)]
+    }
+
+    /// Grade the answer, unlocking the next character once the accuracy is high enough.
+    /// Returns whether the answer was correct.
+    pub fn answer(&mut self, expected: char, given: char) -> bool {
+        let correct = expected.eq_ignore_ascii_case(&given);
+        let entry = self.characters.entry(expected).or_insert((0, 0));
+        entry.1 += 1;
+        if correct {
+            entry.0 += 1;
+        }
+
+        self.recent.push_back(correct);
+        while self.recent.len() > ACCURACY_WINDOW {
+            self.recent.pop_front();
+        }
+        self.window_accuracy = if self.recent.len() == ACCURACY_WINDOW {
+            self.accuracy()
+        } else {
+            None
+        };
+        if self
+            .window_accuracy
+            .is_some_and(|accuracy| accuracy >= ADVANCE_ACCURACY)
+            && self.lesson < MAX_LESSON
+        {
+            self.set_lesson(self.lesson + 1);
+        }
+        correct
+    }
+
+    /// Accuracy over the recent answers of this lesson.
+    pub fn accuracy(&self) -> Option<f32> {
+        if self.recent.is_empty() {
+            return None;
+        }
+        let correct = self.recent.iter().filter(|correct| **correct).count();
+        Some(correct as f32 / self.recent.len() as f32)
+    }
+
+    /// Accuracy over a full window of answers ending with the last one, if there were enough.
+    pub fn window_accuracy(&self) -> Option<f32> {
+        self.window_accuracy
+    }
+
+    /// Accuracy for the character over the whole session.
+    pub fn character_accuracy(&self, ch: char) -> Option<f32> {
+        self.characters
+            .get(&ch)
+            .map(|(correct, attempts)| *correct as f32 / *attempts as f32)
+    }
+}
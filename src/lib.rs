@@ -0,0 +1,11 @@
+//! Morse code engine used by the MORSET practice app.
+//!
+//! Contains the character tables, text <-> morse conversion,
+//! timing helpers and a decoder for keyed elements.
+//! The user interface lives in the `morset` binary.
+
+pub mod consts;
+pub mod decoder;
+pub mod encoder;
+pub mod signal;
+pub mod utils;
@@ -6,89 +6,352 @@ use std::time::Instant;
 #[cfg(target_arch = "wasm32")]
 use web_time::Instant;
 
+mod adaptive;
+#[cfg(not(target_arch = "wasm32"))]
+mod args;
 mod audio;
-mod consts;
+mod callsign;
+mod code_groups;
+#[cfg(not(target_arch = "wasm32"))]
+mod commands;
+mod custom_alphabet;
+mod drill;
+mod exchange;
+mod files;
+mod fist;
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+mod gamepad;
+mod head_copy;
+mod i18n;
 mod inputs;
+mod keyer;
+mod koch;
+mod microphone;
+#[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+mod midi;
+#[cfg(feature = "network")]
+mod network;
+mod player;
+mod qso;
+mod reaction;
+mod records;
 mod screens;
+#[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+mod serial_key;
+mod session;
+mod settings;
+#[cfg(feature = "speech")]
+mod speech;
+mod speed;
 mod state;
-mod utils;
+mod stats;
+mod text_source;
+mod timeline;
+mod transcript;
+#[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+mod winkeyer;
 
 use audio::AudioManager;
+use i18n::tr;
+use settings::Settings;
 use state::AppState;
 
+/// Height of the lamp flashing with the tone
+static LAMP_HEIGHT: f32 = 160.0;
+
 /// Main application structure
 struct MorsetApp {
     state: AppState,
     audio: Option<AudioManager>,
+    /// Why the audio couldn't be opened, practice is silent until it's retried
+    audio_error: Option<String>,
+    settings: Settings,
     main_menu: screens::MainMenuScreen,
     writing_screen: Option<screens::WritingScreen>,
+    listening_screen: Option<screens::ListeningScreen>,
+    settings_screen: Option<screens::SettingsScreen>,
+    /// Stats of the practice screen that was just left
+    summary: Option<stats::SessionStats>,
+    /// The device was asked to vibrate with the tone
+    vibrating: bool,
     last_update: Instant,
 }
 
 impl MorsetApp {
-    fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut settings = Settings::load(cc.storage);
+        settings.theme.apply(&cc.egui_ctx);
+        settings.apply_scale(&cc.egui_ctx);
+        settings.apply_characters();
+        settings.apply_language();
+
         Self {
             state: AppState::MainMenu,
             audio: None,
+            audio_error: None,
+            settings,
             main_menu: screens::MainMenuScreen::new(),
             writing_screen: None,
+            listening_screen: None,
+            settings_screen: None,
+            summary: None,
+            vibrating: false,
             last_update: Instant::now(),
         }
     }
+
+    /// Create the audio manager with the configured tone and output device,
+    /// leaving the practice silent if no output can be opened.
+    fn open_audio(&mut self) {
+        if self.settings.silent {
+            self.audio = None;
+            self.audio_error = None;
+            return;
+        }
+        match self.try_open_audio() {
+            Ok(audio) => {
+                self.audio = Some(audio);
+                self.audio_error = None;
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+                self.audio = None;
+                self.audio_error = Some(err);
+            }
+        }
+    }
+
+    fn try_open_audio(&mut self) -> Result<AudioManager, String> {
+        let settings = &mut self.settings;
+        let frequency = settings.frequency as f32;
+        let volume = settings.gain();
+        let audio = match &settings.output_device {
+            Some(device) => AudioManager::with_device(device, frequency, volume).or_else(|err| {
+                tracing::warn!("{}, using the default device", err);
+                settings.output_device = None;
+                AudioManager::new(frequency, volume)
+            }),
+            None => AudioManager::new(frequency, volume),
+        };
+        let mut audio = audio?;
+        settings.apply_tone(&mut audio);
+        Ok(audio)
+    }
 }
 
 impl eframe::App for MorsetApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         let now = Instant::now();
         let delta = now.duration_since(self.last_update);
         self.last_update = now;
 
+        // The lamp is drawn once the screen has updated the tone, so it matches the audio.
+        let practicing = matches!(self.state, AppState::Writing | AppState::Listening);
+        let lamp = (practicing && self.settings.flash).then(|| {
+            egui::TopBottomPanel::bottom("lamp")
+                .exact_height(LAMP_HEIGHT)
+                .show(ctx, |ui| {
+                    let rect = ui.available_rect_before_wrap();
+                    ui.allocate_rect(rect, egui::Sense::hover());
+                    (ui.painter().clone(), rect)
+                })
+                .inner
+        });
+
+        if self.state != AppState::MainMenu
+            && let Some(err) = &self.audio_error
+        {
+            let mut retry = false;
+            egui::TopBottomPanel::top("audio_error").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        tr("Audio unavailable — practice will be silent"),
+                    )
+                    .on_hover_text(err);
+                    retry = ui.button(tr("Retry audio")).clicked();
+                });
+            });
+            if retry {
+                self.open_audio();
+            }
+        }
+
+        // Screens request repaints while something is going on,
+        // otherwise the app idles until the next input.
+        let previous_state = self.state;
         match self.state {
             AppState::MainMenu => {
-                if let Some(new_state) = self.main_menu.render(ctx) {
+                if let Some(stats) = &self.summary {
+                    let mut open = true;
+                    egui::Window::new(tr("Session summary"))
+                        .open(&mut open)
+                        .collapsible(false)
+                        .show(ctx, |ui| screens::render_stats(ui, stats));
+                    if !open {
+                        self.summary = None;
+                    }
+                }
+                if let Some(new_state) = self.main_menu.render(ctx, &self.settings) {
                     self.state = new_state;
+                    self.summary = None;
                     // We only create audio after user interaction.
                     // Otherwise, some browsers block audio playback.
-                    self.audio = Some(AudioManager::new(600.0, 0.2).unwrap());
+                    self.open_audio();
                     // Initialize writing screen when entering
                     if self.state == AppState::Writing {
-                        self.writing_screen = Some(screens::WritingScreen::new());
+                        self.writing_screen = Some(screens::WritingScreen::new(&self.settings));
+                    }
+                    if self.state == AppState::Listening {
+                        self.listening_screen = Some(screens::ListeningScreen::new(&self.settings));
+                    }
+                    if self.state == AppState::Settings {
+                        self.settings_screen = Some(screens::SettingsScreen::new());
                     }
                 }
             }
             AppState::Writing => {
                 if let Some(ref mut screen) = self.writing_screen
-                    && let Some(new_state) = screen.update(ctx, delta, &mut self.audio)
+                    && let Some(new_state) =
+                        screen.update(ctx, delta, &mut self.audio, &mut self.settings)
                 {
                     self.state = new_state;
-                    // Clean up when leaving
-                    if self.state != AppState::Writing {
+                    // The paused session is kept while the settings are changed.
+                    if self.state == AppState::Settings {
+                        self.settings_screen = Some(screens::SettingsScreen::new());
+                    } else if self.state != AppState::Writing {
+                        let stats = screen.stats();
+                        self.settings.practice.add(stats.day(), stats.active());
+                        self.summary = screen.take_stats();
                         self.writing_screen = None;
                     }
                 }
             }
             AppState::Listening => {
-                // TODO: Implement listening screen
-                egui::CentralPanel::default().show(ctx, |ui| {
-                    ui.label("Listening mode - Coming soon!");
-                    if ui.button("Back to Menu").clicked() {
-                        self.state = AppState::MainMenu;
+                if let Some(ref mut screen) = self.listening_screen
+                    && let Some(new_state) =
+                        screen.render(ctx, delta, &mut self.audio, &mut self.settings)
+                {
+                    self.state = new_state;
+                    // Clean up when leaving
+                    if self.state != AppState::Listening {
+                        let stats = screen.stats();
+                        self.settings.practice.add(stats.day(), stats.active());
+                        self.summary = screen.take_stats();
+                        self.listening_screen = None;
                     }
-                });
+                }
+            }
+            AppState::Settings => {
+                let new_state = self
+                    .settings_screen
+                    .as_mut()
+                    .and_then(|screen| screen.render(ctx, &mut self.settings));
+                // The defaults are kept even if the app doesn't close cleanly.
+                if self
+                    .settings_screen
+                    .as_mut()
+                    .is_some_and(screens::SettingsScreen::take_reset)
+                {
+                    if let Some(audio) = &mut self.audio {
+                        self.settings.apply_tone(audio);
+                    }
+                    if let Some(storage) = frame.storage_mut() {
+                        self.settings.save(storage);
+                        storage.flush();
+                    }
+                }
+                if let Some(new_state) = new_state {
+                    self.state = new_state;
+                    // Clean up when leaving
+                    if self.state != AppState::Settings {
+                        self.settings_screen = None;
+                    }
+                    // Go back to a paused session, reopening the audio with the new settings.
+                    if self.state == AppState::MainMenu
+                        && let Some(screen) = &mut self.writing_screen
+                    {
+                        self.state = AppState::Writing;
+                        screen.apply_settings(&mut self.settings);
+                        self.open_audio();
+                    }
+                }
             }
         }
 
-        // Request continuous repaint for smooth updates
-        ctx.request_repaint();
+        // Taken from the screens rather than the audio, which is missing in silent mode.
+        let tone_on = match self.state {
+            AppState::Writing => self
+                .writing_screen
+                .as_ref()
+                .is_some_and(screens::WritingScreen::is_tone_on),
+            AppState::Listening => self
+                .listening_screen
+                .as_ref()
+                .is_some_and(screens::ListeningScreen::is_tone_on),
+            AppState::MainMenu | AppState::Settings => false,
+        };
+        let vibrating = self.settings.vibration && tone_on;
+        if vibrating != self.vibrating {
+            self.vibrating = vibrating;
+            audio::vibrate(vibrating);
+        }
+        if let Some((painter, rect)) = lamp {
+            let [r, g, b] = if tone_on {
+                self.settings.flash_on_color
+            } else {
+                self.settings.flash_off_color
+            };
+            painter.rect_filled(rect, 8.0, egui::Color32::from_rgb(r, g, b));
+        }
+
+        // Draw the new screen right away.
+        if self.state != previous_state {
+            ctx.request_repaint();
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.settings.save(storage);
+    }
+}
+
+/// Release builds on Windows have no console of their own, so the output of the
+/// commands and of `--help` goes to the console of the terminal that started the program.
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Started from the desktop there's no console to attach to, and nothing is shown as before.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
+    use clap::Parser;
+
+    #[cfg(all(windows, not(debug_assertions)))]
+    attach_console();
+    let args = args::Args::parse();
+    if let Some(command) = args.command {
+        if let Err(err) = commands::run(command) {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1280.0, 720.0])
             .with_title("MORSET"),
+        // The size above is only used on the first launch. Afterwards the
+        // window is restored where it was left, clamped to the connected monitors.
+        persist_window: true,
         ..Default::default()
     };
     tracing_subscriber::fmt().init();
@@ -0,0 +1,90 @@
+use std::sync::mpsc::{self, Receiver};
+
+use rodio::cpal::{
+    self, SampleFormat, SizedSample,
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+};
+
+/// Captures audio from the default input device.
+///
+/// Capturing stops when the value is dropped.
+pub struct Microphone {
+    _stream: cpal::Stream,
+    receiver: Receiver<Vec<f32>>,
+    sample_rate: u32,
+}
+
+impl Microphone {
+    pub fn open() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("No audio input device available")?;
+        let config = device
+            .default_input_config()
+            .map_err(|e| format!("Failed to get input device config: {}", e))?;
+        let sample_rate = config.sample_rate().0;
+        let channels = usize::from(config.channels().max(1));
+        let (sender, receiver) = mpsc::channel();
+
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, channels, sender),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, channels, sender),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, channels, sender),
+            format => return Err(format!("Unsupported input sample format {}", format)),
+        }?;
+        stream
+            .play()
+            .map_err(|e| format!("Failed to start audio capture: {}", e))?;
+
+        Ok(Self {
+            _stream: stream,
+            receiver,
+            sample_rate,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Hand all mono samples captured since the last call to `f`.
+    pub fn drain(&self, mut f: impl FnMut(&[f32])) {
+        for samples in self.receiver.try_iter() {
+            f(&samples);
+        }
+    }
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+    channels: usize,
+    sender: mpsc::Sender<Vec<f32>>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    device
+        .build_input_stream(
+            &config.config(),
+            move |data: &[T], _| {
+                // Mix all channels down to mono.
+                let mono = data
+                    .chunks(channels)
+                    .map(|frame| {
+                        frame
+                            .iter()
+                            .map(|sample| cpal::Sample::to_sample::<f32>(*sample))
+                            .sum::<f32>()
+                            / frame.len() as f32
+                    })
+                    .collect();
+                // The receiver is gone once the screen is closed.
+                let _ = sender.send(mono);
+            },
+            |err| tracing::warn!("Audio capture error: {}", err),
+            None,
+        )
+        .map_err(|e| format!("Failed to open audio input: {}", e))
+}
@@ -0,0 +1,204 @@
+use std::sync::mpsc::{self, Receiver};
+
+use midir::{MidiInput, MidiInputConnection};
+use serde::{Deserialize, Serialize};
+
+use crate::inputs::{Action, KeyBindings, KeyEvents};
+
+/// Note or controller a MIDI device sends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MidiControl {
+    /// Note On while held, Note Off when released
+    Note(u8),
+    /// Control Change, held while the value is 64 or more like a sustain pedal
+    Controller(u8),
+}
+
+impl MidiControl {
+    /// Kinds offered in the settings, with the number to fill in
+    pub const KINDS: [MidiControl; 2] = [MidiControl::Note(0), MidiControl::Controller(0)];
+
+    pub fn number(self) -> u8 {
+        match self {
+            MidiControl::Note(number) | MidiControl::Controller(number) => number,
+        }
+    }
+
+    /// The same kind of control with another number.
+    pub fn with_number(self, number: u8) -> Self {
+        match self {
+            MidiControl::Note(_) => MidiControl::Note(number),
+            MidiControl::Controller(_) => MidiControl::Controller(number),
+        }
+    }
+
+    pub fn kind_name(self) -> &'static str {
+        match self {
+            MidiControl::Note(_) => "Note",
+            MidiControl::Controller(_) => "CC",
+        }
+    }
+}
+
+/// Controls of the MIDI device that key the straight key and the paddles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MidiMapping {
+    pub straight_key: MidiControl,
+    pub dot_paddle: MidiControl,
+    pub dash_paddle: MidiControl,
+}
+
+impl Default for MidiMapping {
+    fn default() -> Self {
+        Self {
+            // Footswitches usually send the sustain pedal controller.
+            straight_key: MidiControl::Controller(64),
+            dot_paddle: MidiControl::Note(60),
+            dash_paddle: MidiControl::Note(62),
+        }
+    }
+}
+
+impl MidiMapping {
+    fn actions(&self) -> [(Action, MidiControl); 3] {
+        [
+            (Action::StraightKey, self.straight_key),
+            (Action::DotPaddle, self.dot_paddle),
+            (Action::DashPaddle, self.dash_paddle),
+        ]
+    }
+}
+
+/// Read the control and whether it's held from a Note On, Note Off or Control Change message.
+fn parse_message(message: &[u8]) -> Option<(MidiControl, bool)> {
+    let [status, number, value] = *message else {
+        return None;
+    };
+    match status & 0xF0 {
+        0x80 => Some((MidiControl::Note(number), false)),
+        0x90 => Some((MidiControl::Note(number), value > 0)),
+        0xB0 => Some((MidiControl::Controller(number), value >= 64)),
+        _ => None,
+    }
+}
+
+/// Names of the MIDI input ports
+pub fn list_ports() -> Vec<String> {
+    let input = match MidiInput::new("morset") {
+        Ok(input) => input,
+        Err(e) => {
+            tracing::warn!("Failed to list MIDI devices: {}", e);
+            return Vec::new();
+        }
+    };
+    input
+        .ports()
+        .iter()
+        .filter_map(|port| input.port_name(port).ok())
+        .collect()
+}
+
+/// Keying from the MIDI device selected in the settings
+pub struct MidiKeying {
+    /// Device the connection was opened for
+    device: Option<String>,
+    _connection: Option<MidiInputConnection<()>>,
+    receiver: Option<Receiver<(MidiControl, bool)>>,
+    /// Whether the straight key and the paddles are held, so repeated values aren't keyed twice
+    held: [bool; 3],
+    error: Option<String>,
+}
+
+impl MidiKeying {
+    pub fn new() -> Self {
+        Self {
+            device: None,
+            _connection: None,
+            receiver: None,
+            held: [false; 3],
+            error: None,
+        }
+    }
+
+    /// Why the selected device couldn't be opened.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Follow the device selected in the settings, opening it when it changes.
+    pub fn select(&mut self, ctx: &egui::Context, device: Option<&str>) {
+        if self.device.as_deref() == device {
+            return;
+        }
+        self.device = device.map(str::to_string);
+        self._connection = None;
+        self.receiver = None;
+        self.held = [false; 3];
+        self.error = None;
+        if let Some(name) = device
+            && let Err(err) = self.open(ctx, name)
+        {
+            tracing::warn!("{}", err);
+            self.error = Some(err);
+        }
+    }
+
+    fn open(&mut self, ctx: &egui::Context, name: &str) -> Result<(), String> {
+        let input =
+            MidiInput::new("morset").map_err(|e| format!("Failed to open MIDI input: {}", e))?;
+        let port = input
+            .ports()
+            .into_iter()
+            .find(|port| {
+                input
+                    .port_name(port)
+                    .is_ok_and(|port_name| port_name == name)
+            })
+            .ok_or_else(|| format!("MIDI device \"{}\" is not available", name))?;
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        let connection = input
+            .connect(
+                &port,
+                "morset-key",
+                move |_, message, _| {
+                    if let Some(event) = parse_message(message) {
+                        let _ = sender.send(event);
+                        // The app idles between inputs, MIDI isn't one egui sees.
+                        ctx.request_repaint();
+                    }
+                },
+                (),
+            )
+            .map_err(|e| format!("Failed to connect to MIDI device \"{}\": {}", name, e))?;
+        self._connection = Some(connection);
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+
+    /// Add the MIDI events received since the last call as presses and releases
+    /// of the keys bound to the actions they're mapped to.
+    pub fn key_events(
+        &mut self,
+        mapping: &MidiMapping,
+        bindings: &KeyBindings,
+        events: &mut KeyEvents,
+    ) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        for (control, on) in receiver.try_iter() {
+            for (index, (action, mapped)) in mapping.actions().into_iter().enumerate() {
+                if mapped != control || self.held[index] == on {
+                    continue;
+                }
+                self.held[index] = on;
+                if on {
+                    events.pressed.push(bindings.key(action));
+                } else {
+                    events.released.push(bindings.key(action));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! Keying shared with a partner through a WebSocket relay.
+//!
+//! Every key down and up is sent as a JSON text message, stamped with the time
+//! since the link was opened. The relay passes messages on to the other clients.
+//! Received events are played on the other station's tone a jitter buffer
+//! after the time they were stamped with, so the spacing survives the network.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use morset::{
+    decoder::{Decoded, Decoder},
+    utils::CharacterTable,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioManager;
+
+/// How long received events wait before they're played, absorbing network jitter
+const JITTER_BUFFER: Duration = Duration::from_millis(150);
+
+/// Events due later than this are taken as a clock that jumped, the schedule starts over
+const MAX_SCHEDULE: Duration = Duration::from_millis(1000);
+
+/// The partner is heard this much higher than the local tone, in Hz
+const PARTNER_PITCH_OFFSET: usize = 150;
+
+/// Key down or up, stamped with milliseconds since the sender opened the link
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeyingEvent {
+    Down {
+        at_ms: u64,
+    },
+    /// Ends a dot (`.`) or a dash (`-`), sent with the sender's dit length to decode the gaps
+    Up {
+        at_ms: u64,
+        element: char,
+        dit_ms: u64,
+    },
+}
+
+impl KeyingEvent {
+    fn at_ms(&self) -> u64 {
+        match self {
+            KeyingEvent::Down { at_ms } | KeyingEvent::Up { at_ms, .. } => *at_ms,
+        }
+    }
+}
+
+/// What the connection reports
+enum Incoming {
+    Open,
+    Message(String),
+    /// The connection was closed, with the error that closed it
+    Closed(Option<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkState {
+    Disconnected,
+    Connecting,
+    Open,
+    /// The connection failed or was lost
+    Failed(String),
+}
+
+/// Keying sent to and received from a partner
+pub struct Link {
+    connection: Option<transport::Connection>,
+    state: LinkState,
+    /// Time since the link was opened, events are stamped with it
+    clock: Duration,
+    /// Local minus remote clock of the received events, in milliseconds
+    offset: Option<i64>,
+    /// Received events with the local time they're played at
+    pending: VecDeque<(Duration, KeyingEvent)>,
+    /// The partner's key is down
+    keyed: bool,
+    /// Silence since the partner's last element
+    silence: Duration,
+    /// Dit length of the partner
+    dit: Duration,
+    decoder: Decoder,
+    /// What the partner sent
+    text: String,
+}
+
+impl Link {
+    pub fn new(characters: CharacterTable) -> Self {
+        Self {
+            connection: None,
+            state: LinkState::Disconnected,
+            clock: Duration::ZERO,
+            offset: None,
+            pending: VecDeque::new(),
+            keyed: false,
+            silence: Duration::ZERO,
+            dit: Duration::from_millis(120),
+            decoder: Decoder::new(1, characters),
+            text: String::new(),
+        }
+    }
+
+    /// Decode what the partner sends with the characters of this table.
+    pub fn set_characters(&mut self, characters: CharacterTable) {
+        self.decoder.set_characters(characters);
+    }
+
+    pub fn state(&self) -> &LinkState {
+        &self.state
+    }
+
+    /// What the partner sent since the text was last cleared.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn clear_text(&mut self) {
+        self.text.clear();
+    }
+
+    /// Connect to the relay, dropping any previous connection.
+    pub fn connect(&mut self, ctx: &egui::Context, url: &str, audio: &mut Option<AudioManager>) {
+        self.disconnect(audio);
+        match transport::Connection::open(ctx, url) {
+            Ok(connection) => {
+                self.connection = Some(connection);
+                self.state = LinkState::Connecting;
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+                self.state = LinkState::Failed(err);
+            }
+        }
+    }
+
+    pub fn disconnect(&mut self, audio: &mut Option<AudioManager>) {
+        self.connection = None;
+        self.state = LinkState::Disconnected;
+        self.clock = Duration::ZERO;
+        self.decoder.clear();
+        self.silence(audio);
+    }
+
+    /// Drop what the partner is sending right now, until their next element.
+    pub fn silence(&mut self, audio: &mut Option<AudioManager>) {
+        self.keyed = false;
+        self.pending.clear();
+        self.offset = None;
+        if let Some(audio) = audio {
+            audio.key_station(false);
+        }
+    }
+
+    /// Tell the partner the key went down.
+    pub fn key_down(&mut self) {
+        self.send(KeyingEvent::Down {
+            at_ms: self.clock.as_millis() as u64,
+        });
+    }
+
+    /// Tell the partner the key went up, ending the element.
+    pub fn key_up(&mut self, element: char, dit: Duration) {
+        self.send(KeyingEvent::Up {
+            at_ms: self.clock.as_millis() as u64,
+            element,
+            dit_ms: dit.as_millis() as u64,
+        });
+    }
+
+    fn send(&mut self, event: KeyingEvent) {
+        if self.state != LinkState::Open {
+            return;
+        }
+        if let Some(connection) = &mut self.connection {
+            match serde_json::to_string(&event) {
+                Ok(message) => connection.send(message),
+                Err(e) => tracing::warn!("Failed to encode keying event: {}", e),
+            }
+        }
+    }
+
+    /// Receive the partner's events and play the ones that are due at the given pitch.
+    pub fn update(&mut self, delta: Duration, frequency: usize, audio: &mut Option<AudioManager>) {
+        let Some(connection) = &mut self.connection else {
+            return;
+        };
+        self.clock += delta;
+        let received = connection.receive();
+        for incoming in received {
+            match incoming {
+                Incoming::Open => self.state = LinkState::Open,
+                Incoming::Message(message) => match serde_json::from_str(&message) {
+                    Ok(event) => self.schedule(event),
+                    Err(e) => tracing::warn!("Ignoring message from the relay: {}", e),
+                },
+                Incoming::Closed(err) => {
+                    let err = err.unwrap_or_else(|| "The relay closed the connection".to_string());
+                    tracing::warn!("{}", err);
+                    self.disconnect(audio);
+                    self.state = LinkState::Failed(err);
+                    return;
+                }
+            }
+        }
+
+        if let Some(audio) = audio {
+            audio.set_station_frequency((frequency + PARTNER_PITCH_OFFSET) as f32);
+        }
+        if !self.keyed {
+            self.silence += delta;
+        }
+        while let Some((due, _)) = self.pending.front()
+            && *due <= self.clock
+        {
+            let Some((_, event)) = self.pending.pop_front() else {
+                break;
+            };
+            self.play(event, audio);
+        }
+        if !self.keyed {
+            self.decoder
+                .on_gap(self.silence.as_secs_f32() / self.dit.as_secs_f32());
+        }
+        while let Some(decoded) = self.decoder.take_decoded() {
+            match decoded {
+                Decoded::Char(ch) | Decoded::Guess(ch) => self.text.push(ch),
+                Decoded::ProSign(prosign) => self.text.push_str(prosign.tag()),
+                Decoded::Space => self.text.push(' '),
+                // No custom prosigns are given to the decoder here
+                Decoded::Custom(_) | Decoded::Unknown => {}
+            }
+        }
+    }
+
+    /// Queue a received event for the local time matching its stamp, plus the jitter buffer.
+    fn schedule(&mut self, event: KeyingEvent) {
+        let clock = self.clock.as_millis() as i64;
+        let at = event.at_ms() as i64;
+        let buffer = JITTER_BUFFER.as_millis() as i64;
+        let mut due = self.offset.map(|offset| at + offset + buffer);
+        // Late events and far off ones come from a partner who paused or reconnected.
+        if due.is_none_or(|due| due < clock || due > clock + MAX_SCHEDULE.as_millis() as i64) {
+            self.offset = Some(clock - at);
+            due = Some(clock + buffer);
+        }
+        let due = Duration::from_millis(due.unwrap_or(clock).max(0) as u64);
+        self.pending.push_back((due, event));
+    }
+
+    fn play(&mut self, event: KeyingEvent, audio: &mut Option<AudioManager>) {
+        match event {
+            KeyingEvent::Down { .. } => self.keyed = true,
+            KeyingEvent::Up {
+                element, dit_ms, ..
+            } => {
+                self.keyed = false;
+                self.silence = Duration::ZERO;
+                self.dit = Duration::from_millis(dit_ms.max(1));
+                self.decoder.on_element(element == '-');
+            }
+        }
+        if let Some(audio) = audio {
+            audio.key_station(self.keyed);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod transport {
+    use std::io::ErrorKind;
+    use std::net::TcpStream;
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    use tungstenite::stream::MaybeTlsStream;
+    use tungstenite::{Message, WebSocket};
+
+    use super::Incoming;
+
+    /// How long a read waits for a message before sending what's queued
+    const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+    /// WebSocket served on a background thread
+    pub struct Connection {
+        outgoing: Sender<String>,
+        incoming: Receiver<Incoming>,
+    }
+
+    impl Connection {
+        pub fn open(ctx: &egui::Context, url: &str) -> Result<Self, String> {
+            let (outgoing, to_send) = mpsc::channel();
+            let (sender, incoming) = mpsc::channel();
+            let ctx = ctx.clone();
+            let url = url.to_string();
+            thread::Builder::new()
+                .name("network".to_string())
+                .spawn(move || {
+                    let err = run(&url, &to_send, &sender, &ctx).err();
+                    let _ = sender.send(Incoming::Closed(err));
+                    ctx.request_repaint();
+                })
+                .map_err(|e| format!("Failed to start the network thread: {}", e))?;
+            Ok(Self { outgoing, incoming })
+        }
+
+        /// Send the message once the connection is open.
+        pub fn send(&mut self, message: String) {
+            // The thread reports why it stopped on its own.
+            let _ = self.outgoing.send(message);
+        }
+
+        /// What happened since the last call.
+        pub fn receive(&mut self) -> Vec<Incoming> {
+            self.incoming.try_iter().collect()
+        }
+    }
+
+    /// Serve the connection until it's closed or the `Connection` is dropped.
+    fn run(
+        url: &str,
+        to_send: &Receiver<String>,
+        sender: &Sender<Incoming>,
+        ctx: &egui::Context,
+    ) -> Result<(), String> {
+        let (mut socket, _) = tungstenite::connect(url)
+            .map_err(|e| format!("Failed to connect to \"{}\": {}", url, e))?;
+        // Reads time out so queued messages don't wait for the partner.
+        if let MaybeTlsStream::Plain(stream) = socket.get_mut() {
+            stream
+                .set_read_timeout(Some(POLL_INTERVAL))
+                .map_err(|e| format!("Failed to set up the connection: {}", e))?;
+        }
+        if sender.send(Incoming::Open).is_err() {
+            return Ok(());
+        }
+        ctx.request_repaint();
+        loop {
+            loop {
+                match to_send.try_recv() {
+                    Ok(message) => socket
+                        .send(Message::text(message))
+                        .map_err(|e| format!("Lost the connection: {}", e))?,
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => return close(&mut socket),
+                }
+            }
+            match socket.read() {
+                Ok(Message::Text(text)) => {
+                    if sender.send(Incoming::Message(text.to_string())).is_err() {
+                        return close(&mut socket);
+                    }
+                    // The app idles between inputs, the network isn't one egui sees.
+                    ctx.request_repaint();
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => return Err(format!("Lost the connection: {}", e)),
+            }
+        }
+    }
+
+    fn close(socket: &mut WebSocket<MaybeTlsStream<TcpStream>>) -> Result<(), String> {
+        // Leaving anyway, the relay notices a dropped connection too.
+        let _ = socket.close(None);
+        let _ = socket.flush();
+        Ok(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod transport {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use wasm_bindgen::JsCast as _;
+    use wasm_bindgen::closure::Closure;
+    use web_sys::{CloseEvent, Event, MessageEvent, WebSocket};
+
+    use super::Incoming;
+
+    /// Browser WebSocket, its callbacks queue what happens
+    pub struct Connection {
+        socket: WebSocket,
+        incoming: Rc<RefCell<Vec<Incoming>>>,
+        _on_open: Closure<dyn FnMut(Event)>,
+        _on_message: Closure<dyn FnMut(MessageEvent)>,
+        _on_close: Closure<dyn FnMut(CloseEvent)>,
+    }
+
+    impl Connection {
+        pub fn open(ctx: &egui::Context, url: &str) -> Result<Self, String> {
+            let socket = WebSocket::new(url)
+                .map_err(|e| format!("Failed to connect to \"{}\": {:?}", url, e))?;
+            let incoming = Rc::new(RefCell::new(Vec::new()));
+
+            let on_open = {
+                let incoming = incoming.clone();
+                let ctx = ctx.clone();
+                Closure::<dyn FnMut(Event)>::new(move |_| {
+                    incoming.borrow_mut().push(Incoming::Open);
+                    ctx.request_repaint();
+                })
+            };
+            let on_message = {
+                let incoming = incoming.clone();
+                let ctx = ctx.clone();
+                Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                    if let Some(text) = event.data().as_string() {
+                        incoming.borrow_mut().push(Incoming::Message(text));
+                        // The app idles between inputs, the network isn't one egui sees.
+                        ctx.request_repaint();
+                    }
+                })
+            };
+            let on_close = {
+                let incoming = incoming.clone();
+                let ctx = ctx.clone();
+                Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+                    let err = (!event.was_clean())
+                        .then(|| format!("Lost the connection (code {})", event.code()));
+                    incoming.borrow_mut().push(Incoming::Closed(err));
+                    ctx.request_repaint();
+                })
+            };
+            socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+            socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+            Ok(Self {
+                socket,
+                incoming,
+                _on_open: on_open,
+                _on_message: on_message,
+                _on_close: on_close,
+            })
+        }
+
+        /// Send the message, if the connection is open.
+        pub fn send(&mut self, message: String) {
+            if self.socket.ready_state() == WebSocket::OPEN
+                && let Err(e) = self.socket.send_with_str(&message)
+            {
+                tracing::warn!("Failed to send keying event: {:?}", e);
+            }
+        }
+
+        /// What happened since the last call.
+        pub fn receive(&mut self) -> Vec<Incoming> {
+            std::mem::take(&mut *self.incoming.borrow_mut())
+        }
+    }
+
+    impl Drop for Connection {
+        fn drop(&mut self) {
+            // The callbacks are freed with the connection.
+            self.socket.set_onopen(None);
+            self.socket.set_onmessage(None);
+            self.socket.set_onclose(None);
+            let _ = self.socket.close();
+        }
+    }
+}
@@ -0,0 +1,312 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+use morset::{
+    encoder::{self, Timing},
+    utils::CharacterTable,
+};
+
+use crate::audio::AudioManager;
+
+/// Sends text as morse code through the audio manager
+pub struct MorsePlayer {
+    /// Tone on or off with how long it lasts
+    signal: Vec<(bool, Duration)>,
+    /// Byte index of the character every timing of the signal sends, if it's text
+    characters: Vec<Option<usize>>,
+    /// Index of the next timing to send
+    next: usize,
+    /// Whether the tone of the current timing is on, `None` between timings
+    current: Option<bool>,
+    /// Time left of the current timing
+    remaining: Duration,
+    /// Length of the whole signal and of the part already sent
+    total: Duration,
+    sent: Duration,
+    paused: bool,
+    /// Random variation of every queued timing, as a fraction of its length
+    jitter: f32,
+    /// Unit of the gaps between characters and words, if they're stretched
+    spacing: Option<Duration>,
+}
+
+impl MorsePlayer {
+    pub fn new() -> Self {
+        Self {
+            signal: Vec::new(),
+            characters: Vec::new(),
+            next: 0,
+            current: None,
+            remaining: Duration::ZERO,
+            total: Duration::ZERO,
+            sent: Duration::ZERO,
+            paused: false,
+            jitter: 0.,
+            spacing: None,
+        }
+    }
+
+    /// Vary the timings of text sent from now on, so it sounds hand keyed.
+    pub fn set_jitter(&mut self, jitter: f32) {
+        self.jitter = jitter;
+    }
+
+    /// Stretch the gaps between characters and words of text sent from now on
+    /// to this unit, for Farnsworth timing, or send them at the dit length.
+    pub fn set_spacing(&mut self, spacing: Option<Duration>) {
+        self.spacing = spacing;
+    }
+
+    /// Start sending the text, replacing anything that was being sent.
+    ///
+    /// The text is preceded by a character gap so consecutive texts don't run together.
+    /// Characters without a morse mapping in the table are skipped.
+    pub fn play(
+        &mut self,
+        text: &str,
+        dit_duration: Duration,
+        weight: f32,
+        characters: &CharacterTable,
+    ) {
+        self.play_signal([]);
+        self.queue(text, 0, dit_duration, weight, characters);
+    }
+
+    /// Send the text after anything that's still being sent.
+    ///
+    /// `offset` is added to the character indices reported by [`Self::current_character`].
+    /// The text is preceded by a character gap, or a word gap if it starts with whitespace.
+    pub fn queue(
+        &mut self,
+        text: &str,
+        offset: usize,
+        dit_duration: Duration,
+        weight: f32,
+        characters: &CharacterTable,
+    ) {
+        let timings = encoder::character_timings(text, true, characters).unwrap_or_default();
+        let Some((first, _)) = timings.first() else {
+            return;
+        };
+        if !self.is_playing() {
+            self.play_signal([]);
+        }
+        let gap = Timing {
+            on: false,
+            units: if text.starts_with(char::is_whitespace) {
+                7
+            } else {
+                3
+            },
+        };
+        for (index, timing) in std::iter::once((*first, gap)).chain(timings) {
+            let variation = 1. + self.jitter * (fastrand::f32() * 2. - 1.);
+            let mut duration = timing.duration(dit_duration, weight);
+            if !timing.on
+                && timing.units >= 3
+                && let Some(spacing) = self.spacing
+            {
+                duration += spacing.saturating_sub(dit_duration) * timing.units;
+            }
+            let duration = duration.mul_f32(variation);
+            self.total += duration;
+            self.signal.push((timing.on, duration));
+            self.characters.push(Some(offset + index));
+        }
+    }
+
+    /// Start sending tone on and off durations, replacing anything that was being sent.
+    pub fn play_signal(&mut self, signal: impl IntoIterator<Item = (bool, Duration)>) {
+        self.signal.clear();
+        self.signal.extend(signal);
+        self.characters.clear();
+        self.characters.resize(self.signal.len(), None);
+        self.next = 0;
+        self.current = None;
+        self.remaining = Duration::ZERO;
+        self.total = self.signal.iter().map(|(_, duration)| *duration).sum();
+        self.sent = Duration::ZERO;
+        self.paused = false;
+    }
+
+    /// Stop sending immediately.
+    pub fn stop(&mut self, audio: &mut Option<AudioManager>) {
+        self.signal.clear();
+        self.characters.clear();
+        self.next = 0;
+        self.current = None;
+        self.total = Duration::ZERO;
+        self.sent = Duration::ZERO;
+        self.paused = false;
+        if let Some(audio) = audio {
+            audio.pause();
+        }
+    }
+
+    /// Hold sending where it is until resumed.
+    pub fn pause(&mut self, audio: &mut Option<AudioManager>) {
+        self.paused = true;
+        if self.current == Some(true)
+            && let Some(audio) = audio
+        {
+            audio.pause();
+        }
+    }
+
+    pub fn resume(&mut self, audio: &mut Option<AudioManager>) {
+        self.paused = false;
+        if self.current == Some(true)
+            && let Some(audio) = audio
+        {
+            audio.play();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Whether the tone is on right now, as opposed to a gap or a pause.
+    pub fn is_tone_on(&self) -> bool {
+        self.current == Some(true) && !self.paused
+    }
+
+    /// Go back to the start of what's being sent.
+    pub fn reset(&mut self, audio: &mut Option<AudioManager>) {
+        self.next = 0;
+        self.current = None;
+        self.remaining = Duration::ZERO;
+        self.sent = Duration::ZERO;
+        if let Some(audio) = audio {
+            audio.pause();
+        }
+    }
+
+    /// Whether there's anything left to send, paused or not.
+    pub fn is_playing(&self) -> bool {
+        self.current.is_some() || self.next < self.signal.len()
+    }
+
+    /// Part of what's being sent that has been sent, `None` when nothing is being sent.
+    pub fn progress(&self) -> Option<f32> {
+        (self.is_playing() && !self.total.is_zero())
+            .then(|| (self.sent.as_secs_f32() / self.total.as_secs_f32()).min(1.))
+    }
+
+    /// Byte index of the text character being sent, if text is being sent.
+    pub fn current_character(&self) -> Option<usize> {
+        self.current?;
+        self.characters
+            .get(self.next.checked_sub(1)?)
+            .copied()
+            .flatten()
+    }
+
+    /// Time left until the tone is turned on or off, `None` when nothing is being sent.
+    pub fn next_change(&self) -> Option<Duration> {
+        if self.paused {
+            None
+        } else if self.current.is_some() {
+            Some(self.remaining)
+        } else if self.next < self.signal.len() {
+            Some(Duration::ZERO)
+        } else {
+            None
+        }
+    }
+
+    /// Progress sending by the time passed since the last update.
+    /// Returns whether everything has just been sent.
+    pub fn update(&mut self, delta: Duration, audio: &mut Option<AudioManager>) -> bool {
+        self.update_with(delta, |on| {
+            if let Some(audio) = audio {
+                if on {
+                    audio.play();
+                } else {
+                    audio.pause();
+                }
+            }
+        })
+    }
+
+    /// Progress sending, turning the tone on and off with `key`.
+    /// Returns whether everything has just been sent.
+    pub fn update_with(&mut self, delta: Duration, mut key: impl FnMut(bool)) -> bool {
+        if self.paused {
+            return false;
+        }
+        let mut delta = delta;
+        loop {
+            if self.current.is_none() {
+                let Some(&(on, duration)) = self.signal.get(self.next) else {
+                    return false;
+                };
+                self.next += 1;
+                self.current = Some(on);
+                self.remaining = duration;
+                key(on);
+            }
+            if delta < self.remaining {
+                self.remaining -= delta;
+                self.sent += delta;
+                return false;
+            }
+            delta -= self.remaining;
+            self.sent += self.remaining;
+            self.current = None;
+            if self.next >= self.signal.len() {
+                key(false);
+                return true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use morset::utils::wpm_to_dit_duration;
+
+    /// Times at which the tone is turned on, sending everything in 1 ms steps.
+    fn key_downs(player: &mut MorsePlayer) -> Vec<Duration> {
+        let step = Duration::from_millis(1);
+        let mut elapsed = Duration::ZERO;
+        let mut downs = Vec::new();
+        while player.is_playing() {
+            player.update_with(step, |on| {
+                if on {
+                    downs.push(elapsed + step);
+                }
+            });
+            elapsed += step;
+        }
+        downs
+    }
+
+    #[test]
+    fn paris_lasts_fifty_dits() {
+        for wpm in [5, 13, 20, 30] {
+            let dit = wpm_to_dit_duration(wpm);
+            for weight in [0.3, encoder::STANDARD_WEIGHT, 0.7] {
+                let characters = CharacterTable::default();
+                let mut player = MorsePlayer::new();
+                player.play("PARIS", dit, weight, &characters);
+                player.queue(" PARIS", 5, dit, weight, &characters);
+                let downs = key_downs(&mut player);
+                // Every PARIS has 14 elements
+                assert_eq!(downs.len(), 28);
+                let word = downs[14] - downs[0];
+                assert!(
+                    word.abs_diff(dit * 50) <= Duration::from_millis(2),
+                    "PARIS lasts {:?} instead of {:?} at {} WPM and weight {}",
+                    word,
+                    dit * 50,
+                    wpm,
+                    weight
+                );
+            }
+        }
+    }
+}
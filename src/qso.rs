@@ -0,0 +1,111 @@
+use morset::consts::{ABC, NUMBERS};
+
+use crate::{
+    callsign::{Region, generate_callsign},
+    drill::Grade,
+};
+
+/// Names operators give during a contact
+static NAMES: [&str; 12] = [
+    "JOHN", "BOB", "ANNA", "MIKE", "TOM", "PAUL", "OLGA", "HANS", "LUIS", "KEN", "SAM", "EVA",
+];
+
+/// Locations operators give during a contact
+static QTHS: [&str; 12] = [
+    "BERLIN", "TOKYO", "DENVER", "LYON", "OSLO", "PERTH", "RIGA", "QUITO", "MADRID", "OTTAWA",
+    "KYIV", "ROME",
+];
+
+/// Signal reports exchanged during a contact
+static REPORTS: [&str; 6] = ["599", "589", "579", "569", "559", "449"];
+
+/// What the user's station sends and what the other station replies
+pub struct Over {
+    pub sent: String,
+    pub reply: String,
+}
+
+/// Scripted contact with another station whose replies the user copies
+pub struct Qso {
+    overs: Vec<Over>,
+    /// Index of the over being copied
+    current: usize,
+    /// Grade of the copy of every reply so far
+    grades: Vec<Grade>,
+}
+
+fn pick<'a>(items: &[&'a str]) -> &'a str {
+    items[fastrand::usize(..items.len())]
+}
+
+/// Random callsign from one of the regions.
+fn callsign(regions: &[Region]) -> String {
+    let pool = ABC
+        .iter()
+        .chain(NUMBERS.iter())
+        .map(|(ch, _)| *ch)
+        .collect::<Vec<_>>();
+    generate_callsign(regions, &pool).unwrap_or_else(|| "DL1ABC".to_string())
+}
+
+impl Qso {
+    /// Script a contact with random callsigns, reports, names and locations.
+    pub fn new(regions: &[Region]) -> Self {
+        let me = callsign(regions);
+        let dx = callsign(regions);
+        let (my_name, my_qth) = (pick(&NAMES), pick(&QTHS));
+        let (dx_name, dx_qth) = (pick(&NAMES), pick(&QTHS));
+        let overs = vec![
+            Over {
+                sent: format!("CQ CQ DE {me} {me} K"),
+                reply: format!("{me} DE {dx} {dx} K"),
+            },
+            Over {
+                sent: format!(
+                    "{dx} DE {me} GM UR RST {} NAME {my_name} QTH {my_qth} HW? {dx} DE {me} K",
+                    pick(&REPORTS)
+                ),
+                reply: format!(
+                    "{me} DE {dx} R TNX UR RST {} NAME {dx_name} QTH {dx_qth} {me} DE {dx} K",
+                    pick(&REPORTS)
+                ),
+            },
+            Over {
+                sent: format!("{dx} DE {me} TNX FER QSO 73 {dx} DE {me} SK"),
+                reply: format!("{me} DE {dx} 73 TU {dx_name} SK"),
+            },
+        ];
+        Self {
+            overs,
+            current: 0,
+            grades: Vec::new(),
+        }
+    }
+
+    /// Over whose reply is being copied, `None` once the contact is over.
+    pub fn current(&self) -> Option<&Over> {
+        self.overs.get(self.current)
+    }
+
+    /// Grade the copy of the current reply and move on to the next over.
+    pub fn submit(&mut self, copy: &str) -> Option<&Grade> {
+        let over = self.overs.get(self.current)?;
+        let grade = Grade::new(&over.reply, &copy.trim().to_uppercase(), None);
+        self.current += 1;
+        self.grades.push(grade);
+        self.grades.last()
+    }
+
+    /// Overs already copied along with the grade of their reply.
+    pub fn history(&self) -> impl Iterator<Item = (&Over, &Grade)> {
+        self.overs.iter().zip(&self.grades)
+    }
+
+    /// Part of the other station's characters copied correctly so far.
+    pub fn accuracy(&self) -> Option<f32> {
+        if self.grades.is_empty() {
+            return None;
+        }
+        Some(self.grades.iter().map(|grade| grade.accuracy).sum::<f32>() / self.grades.len() as f32)
+    }
+}
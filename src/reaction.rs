@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+/// Characters played in a run
+pub static REACTION_RUN: usize = 10;
+
+/// Width of the steps of the distribution
+pub static REACTION_STEP: Duration = Duration::from_millis(100);
+
+/// Steps of the distribution, the last one taking every slower reaction
+pub static REACTION_STEPS: usize = 10;
+
+/// Plays single characters and times how fast the right key is typed after each one ends
+pub struct ReactionTest {
+    /// Characters the run picks from
+    pool: Vec<char>,
+    /// Character being played, with the time waited for its key once it ended
+    current: Option<(char, Option<Duration>)>,
+    /// Characters left to play after the current one
+    remaining: usize,
+    /// Reaction times of the right keys of the run
+    times: Vec<Duration>,
+    misses: usize,
+}
+
+impl ReactionTest {
+    pub fn new() -> Self {
+        Self {
+            pool: Vec::new(),
+            current: None,
+            remaining: 0,
+            times: Vec::new(),
+            misses: 0,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Start a run of characters from the pool, returning the first one to play.
+    pub fn start(&mut self, pool: Vec<char>) -> Option<char> {
+        self.pool = pool;
+        self.remaining = REACTION_RUN;
+        self.times.clear();
+        self.misses = 0;
+        self.next()
+    }
+
+    pub fn stop(&mut self) {
+        self.current = None;
+    }
+
+    fn next(&mut self) -> Option<char> {
+        if self.remaining == 0 || self.pool.is_empty() {
+            self.current = None;
+            return None;
+        }
+        self.remaining -= 1;
+        let ch = self.pool[fastrand::usize(..self.pool.len())];
+        self.current = Some((ch, None));
+        Some(ch)
+    }
+
+    /// The character finished playing, the clock starts.
+    pub fn tone_ended(&mut self) {
+        if let Some((_, waited @ None)) = &mut self.current {
+            *waited = Some(Duration::ZERO);
+        }
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        if let Some((_, Some(waited))) = &mut self.current {
+            *waited += delta;
+        }
+    }
+
+    /// Time the key typed after the character ended. Returns the character with
+    /// whether the key was right, and the next character to play if the run goes on.
+    /// Keys typed while the character is still playing are ignored.
+    pub fn answer(&mut self, given: char) -> Option<(char, bool, Option<char>)> {
+        let (expected, waited) = self.current?;
+        let waited = waited?;
+        let correct = expected.eq_ignore_ascii_case(&given);
+        if correct {
+            self.times.push(waited);
+        } else {
+            self.misses += 1;
+        }
+        Some((expected, correct, self.next()))
+    }
+
+    pub fn times(&self) -> &[Duration] {
+        &self.times
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        (!self.times.is_empty())
+            .then(|| self.times.iter().sum::<Duration>() / self.times.len() as u32)
+    }
+
+    pub fn best(&self) -> Option<Duration> {
+        self.times.iter().min().copied()
+    }
+
+    /// Number of reactions in every step of the distribution, the fastest first.
+    pub fn distribution(&self) -> Vec<usize> {
+        let mut steps = vec![0; REACTION_STEPS];
+        for time in &self.times {
+            let step = (time.as_millis() / REACTION_STEP.as_millis()) as usize;
+            steps[step.min(REACTION_STEPS - 1)] += 1;
+        }
+        steps
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Best result reached in a drill
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub accuracy: f32,
+    /// Highest speed copied with enough accuracy, if any
+    pub wpm: Option<u8>,
+}
+
+/// Fastest reactions of the reaction test, in milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReactionRecord {
+    /// Mean of a run without a wrong key, if there was one
+    pub mean_ms: Option<u32>,
+    pub best_ms: Option<u32>,
+}
+
+/// Best results of every drill, each difficulty kept apart
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Records {
+    drills: BTreeMap<String, Record>,
+    reaction: ReactionRecord,
+}
+
+impl Records {
+    pub fn get(&self, drill: &str) -> Option<Record> {
+        self.drills.get(drill).copied()
+    }
+
+    /// Keep the result if it beats the record of the drill, returning whether it did.
+    ///
+    /// `wpm` is only given when the accuracy was high enough for the speed to count.
+    pub fn submit(&mut self, drill: &str, accuracy: f32, wpm: Option<u8>) -> bool {
+        let record = self.drills.entry(drill.to_string()).or_default();
+        let mut beaten = false;
+        if accuracy > record.accuracy {
+            record.accuracy = accuracy;
+            beaten = true;
+        }
+        if wpm > record.wpm {
+            record.wpm = wpm;
+            beaten = true;
+        }
+        beaten
+    }
+
+    pub fn reaction(&self) -> ReactionRecord {
+        self.reaction
+    }
+
+    /// Keep the reaction times that beat the record, returning whether any did.
+    ///
+    /// `mean` is only given for runs without a wrong key.
+    pub fn submit_reaction(&mut self, mean: Option<Duration>, best: Option<Duration>) -> bool {
+        let faster = |time: Option<Duration>, record: &mut Option<u32>| match time {
+            Some(time) if record.is_none_or(|ms| (time.as_millis() as u32) < ms) => {
+                *record = Some(time.as_millis() as u32);
+                true
+            }
+            _ => false,
+        };
+        let mean = faster(mean, &mut self.reaction.mean_ms);
+        let best = faster(best, &mut self.reaction.best_ms);
+        mean || best
+    }
+}
+
+/// Active practice time of every day, for the streak on the main menu
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PracticeLog {
+    /// Seconds practiced on each day, in days since the Unix epoch in UTC
+    days: BTreeMap<u64, u64>,
+}
+
+impl PracticeLog {
+    pub fn add(&mut self, day: u64, time: Duration) {
+        if !time.is_zero() {
+            *self.days.entry(day).or_default() += time.as_secs();
+        }
+    }
+
+    /// Time practiced on the day.
+    pub fn day(&self, day: u64) -> Duration {
+        Duration::from_secs(self.days.get(&day).copied().unwrap_or_default())
+    }
+
+    /// Time practiced over the seven days ending with the day.
+    pub fn week(&self, day: u64) -> Duration {
+        Duration::from_secs(
+            self.days
+                .range(day.saturating_sub(6)..=day)
+                .map(|(_, secs)| secs)
+                .sum(),
+        )
+    }
+
+    /// Days practiced in a row up to the day,
+    /// or up to the day before while nothing was practiced on it yet.
+    pub fn streak(&self, day: u64) -> usize {
+        let last = if self.days.contains_key(&day) {
+            day
+        } else {
+            day.saturating_sub(1)
+        };
+        (0..=last)
+            .rev()
+            .take_while(|day| self.days.contains_key(day))
+            .count()
+    }
+}
+
+/// Characters answered fewer times than this aren't ranked among the weakest
+pub static MIN_RANKED_ANSWERS: u32 = 3;
+
+/// Answers given for a character and how many of them were right
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CharacterScore {
+    pub correct: u32,
+    pub total: u32,
+}
+
+impl CharacterScore {
+    pub fn accuracy(&self) -> f32 {
+        if self.total == 0 {
+            0.
+        } else {
+            self.correct as f32 / self.total as f32
+        }
+    }
+}
+
+/// How well every character was copied or sent over all sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterAccuracy {
+    characters: BTreeMap<char, CharacterScore>,
+}
+
+impl CharacterAccuracy {
+    pub fn record(&mut self, ch: char, correct: bool) {
+        let score = self.characters.entry(ch).or_default();
+        score.total += 1;
+        if correct {
+            score.correct += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.characters.clear();
+    }
+
+    /// Every answered character with its score, in character order.
+    pub fn scores(&self) -> impl Iterator<Item = (char, CharacterScore)> + '_ {
+        self.characters.iter().map(|(ch, score)| (*ch, *score))
+    }
+
+    /// Up to `count` characters with the lowest accuracy, the weakest first.
+    pub fn weakest(&self, count: usize) -> Vec<(char, CharacterScore)> {
+        let mut ranked = self
+            .scores()
+            .filter(|(_, score)| score.total >= MIN_RANKED_ANSWERS && score.correct < score.total)
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| {
+            a.1.accuracy()
+                .total_cmp(&b.1.accuracy())
+                .then(b.1.total.cmp(&a.1.total))
+        });
+        ranked.truncate(count);
+        ranked
+    }
+}
@@ -1,30 +1,1799 @@
-use crate::state::AppState;
+use egui::RichText;
+use std::time::Duration;
 
-#[allow(dead_code)]
-pub struct ListeningScreen;
+use morset::{
+    consts::WORD_LIST,
+    encoder::STANDARD_WEIGHT,
+    signal::{self, LiveDecoder, SPECTRUM_LEN, Transcription},
+    utils::{CharacterTable, wpm_to_dit_duration},
+};
+
+use super::settings_screen::text_source_settings;
+use super::{
+    SCOPE_WIDTH, WEAKEST_COUNT, render_accuracy, render_spectrum, render_stats, render_timer,
+};
+use crate::{
+    adaptive::AdaptiveSpeed,
+    audio::{AudioManager, SPECTRUM_BIN},
+    code_groups::{CodeGroupDrill, GroupCharacters, MAX_CODE_GROUPS, MIN_CODE_GROUPS},
+    exchange::{ExchangeDrill, ExchangeFormat},
+    files::{self, PendingFile},
+    head_copy::{HeadCopy, MAX_HEAD_COPY_WORDS},
+    i18n::tr,
+    koch::{self, ADVANCE_ACCURACY, KochTrainer, MAX_LESSON},
+    microphone::Microphone,
+    player::MorsePlayer,
+    qso::Qso,
+    reaction::{REACTION_RUN, REACTION_STEP, ReactionTest},
+    session::Session,
+    settings::{
+        MAX_ADAPTIVE_STEP, MAX_ADAPTIVE_STREAK, MAX_FADING_DEPTH, MAX_FREQUENCY, MAX_PITCH_SPREAD,
+        MAX_SNR, MAX_WPM, MIN_ADAPTIVE_STEP, MIN_ADAPTIVE_STREAK, MIN_FADING_DEPTH, MIN_FREQUENCY,
+        MIN_PITCH_SPREAD, MIN_SNR, MIN_WPM, Settings,
+    },
+    state::AppState,
+    stats::SessionStats,
+};
+
+pub static MAX_NOISE_GATE: f32 = 0.5;
+pub static MIN_NOISE_GATE: f32 = 0.001;
+
+/// Speeds the interfering station sends at
+static INTERFERENCE_WPM: std::ops::RangeInclusive<u8> = 12..=25;
+
+/// How unevenly the other station of a QSO keys, as a fraction of every timing
+static STATION_JITTER: f32 = 0.15;
+
+/// Most WPM the other station of a QSO sends faster or slower than the user
+static STATION_WPM_SPREAD: u8 = 2;
+
+/// Wait after a Koch character before saying its name, answering first skips it
+#[cfg(feature = "speech")]
+static ANNOUNCE_DELAY: Duration = Duration::from_millis(1000);
+
+/// Drill item that was sent, kept to send it again
+struct SentItem {
+    text: String,
+    dit_duration: Duration,
+    /// Unit of the stretched gaps, for Farnsworth timing
+    spacing: Option<Duration>,
+    weight: f32,
+    pitch: f32,
+}
+
+pub struct ListeningScreen {
+    /// Characters sent and decoded, from the settings the screen was opened with
+    characters: CharacterTable,
+    /// WAV file dialog that hasn't been closed yet
+    pending_wav: Option<PendingFile>,
+    /// Name of the loaded file with its transcription
+    transcription: Option<(String, Transcription)>,
+    error: Option<String>,
+
+    /// Session file dialog that hasn't been closed yet
+    pending_session: Option<PendingFile>,
+    /// Name of the loaded session with its decoded text
+    session: Option<(String, Session, String)>,
+    session_error: Option<String>,
+    /// Position of the session replay, `None` when not replaying
+    replay_position: Option<Duration>,
+    /// The replay uses perfect timing, so the position doesn't match the recorded one
+    replay_perfect: bool,
+
+    /// Text file dialog that hasn't been closed yet
+    pending_text: Option<PendingFile>,
+    /// Name of the loaded text file with its contents
+    text_file: Option<(String, String)>,
+    /// Characters of the text file that have no morse mapping and are skipped
+    text_skipped: String,
+    text_error: Option<String>,
+    /// The player is sending the text file
+    text_playing: bool,
+    /// Speed the text file is sent at
+    text_wpm: u8,
+
+    /// Live capture, decoded as it arrives
+    microphone: Option<(Microphone, LiveDecoder)>,
+    microphone_text: String,
+    microphone_error: Option<String>,
+    /// Latest captured samples, kept for the spectrum while it's shown
+    microphone_samples: Vec<f32>,
+    /// Signal level below which the input is treated as silence
+    noise_gate: f32,
+    /// Speed the sender is expected to use
+    expected_wpm: u8,
+
+    /// Character recognition drill
+    koch: KochTrainer,
+    player: MorsePlayer,
+    /// Character, words, group or exchange the player sent last
+    last_item: Option<SentItem>,
+    /// Character that was sent and is waiting to be named
+    koch_current: Option<char>,
+    /// Pitch the waiting character is sent at, kept for repeating it
+    koch_pitch: f32,
+    /// The last answer with the character that was sent
+    koch_feedback: Option<(bool, char)>,
+    /// The last answer beat the record of the lesson
+    new_record: bool,
+    /// Says the names of Koch characters, opened the first time it's needed
+    #[cfg(feature = "speech")]
+    speaker: Option<crate::speech::Speaker>,
+    /// Time left until the name of the waiting character is said
+    #[cfg(feature = "speech")]
+    announce_in: Option<Duration>,
+
+    /// Typing single characters as fast as possible
+    reaction: ReactionTest,
+    /// The last character of the reaction test with the reaction time, if the key was right
+    reaction_feedback: Option<(char, Option<Duration>)>,
+    /// The last run beat the reaction record
+    new_reaction_record: bool,
+
+    /// Copying words without seeing them
+    head_copy: HeadCopy,
+    head_copy_attempt: String,
+    /// Number of words sent at once
+    head_copy_words: usize,
+
+    /// Runs of five character groups
+    code_groups: CodeGroupDrill,
+    code_group_copy: String,
+
+    exchange: ExchangeDrill,
+    exchange_copy: String,
+
+    /// Listening speed following the answers, when adaptive speed is on
+    adaptive: AdaptiveSpeed,
+
+    /// What was copied since the screen was opened, summed up when leaving
+    stats: SessionStats,
+    stats_open: bool,
+    /// Window with the long-term accuracy of every character
+    accuracy_open: bool,
+    /// Side panel with the spectrum of the output, or of the microphone while it listens
+    spectrum_open: bool,
+
+    /// Keys the interfering station
+    interference: MorsePlayer,
+    /// Pitch of the interfering station relative to the tone, in Hz
+    interference_offset: f32,
+
+    qso: Option<Qso>,
+    qso_copy: String,
+    /// Keys the other station of the QSO
+    station: MorsePlayer,
+    station_pitch: f32,
+    station_wpm: u8,
+    /// The other station replies once the user's side of the over has been sent
+    reply_pending: bool,
+}
 
-#[allow(dead_code)]
 impl ListeningScreen {
-    pub fn new() -> Self {
-        Self
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            characters: settings.characters.clone(),
+            pending_wav: None,
+            transcription: None,
+            error: None,
+            pending_session: None,
+            session: None,
+            session_error: None,
+            replay_position: None,
+            replay_perfect: false,
+            pending_text: None,
+            text_file: None,
+            text_skipped: String::new(),
+            text_error: None,
+            text_playing: false,
+            text_wpm: settings.listening_wpm,
+            microphone: None,
+            microphone_text: String::new(),
+            microphone_error: None,
+            microphone_samples: Vec::new(),
+            noise_gate: 0.05,
+            expected_wpm: 15,
+            koch: KochTrainer::new(settings.koch_lesson),
+            player: MorsePlayer::new(),
+            last_item: None,
+            koch_current: None,
+            koch_pitch: settings.frequency as f32,
+            koch_feedback: None,
+            new_record: false,
+            reaction: ReactionTest::new(),
+            reaction_feedback: None,
+            new_reaction_record: false,
+            #[cfg(feature = "speech")]
+            speaker: None,
+            #[cfg(feature = "speech")]
+            announce_in: None,
+            head_copy: HeadCopy::new(),
+            head_copy_attempt: String::new(),
+            head_copy_words: 1,
+            code_groups: CodeGroupDrill::new(),
+            code_group_copy: String::new(),
+            exchange: ExchangeDrill::new(),
+            exchange_copy: String::new(),
+            adaptive: AdaptiveSpeed::new(),
+            stats: SessionStats::new(),
+            stats_open: false,
+            accuracy_open: false,
+            spectrum_open: false,
+            interference: MorsePlayer::new(),
+            interference_offset: fastrand::f32() * 200. + 80.,
+            qso: None,
+            qso_copy: String::new(),
+            station: {
+                let mut station = MorsePlayer::new();
+                station.set_jitter(STATION_JITTER);
+                station
+            },
+            station_pitch: settings.frequency as f32,
+            station_wpm: settings.listening_wpm,
+            reply_pending: false,
+        }
+    }
+
+    /// Whether the player has the tone on, with or without audio.
+    pub fn is_tone_on(&self) -> bool {
+        self.player.is_tone_on()
+    }
+
+    pub fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+
+    /// Stats of the session, if anything was copied.
+    pub fn take_stats(&mut self) -> Option<SessionStats> {
+        let stats = std::mem::replace(&mut self.stats, SessionStats::new());
+        (!stats.is_empty()).then_some(stats)
+    }
+
+    /// Keep the interfering station sending random words while it's enabled.
+    fn update_interference(
+        &mut self,
+        delta: Duration,
+        audio: &mut Option<AudioManager>,
+        settings: &Settings,
+    ) {
+        if !settings.interference {
+            if self.interference.is_playing() {
+                self.interference = MorsePlayer::new();
+                if let Some(audio) = audio {
+                    audio.key_interference(false);
+                }
+            }
+            return;
+        }
+        if !self.interference.is_playing() {
+            let word = WORD_LIST[fastrand::usize(..WORD_LIST.len())];
+            let wpm = fastrand::u8(INTERFERENCE_WPM.clone());
+            self.interference.play(
+                word,
+                wpm_to_dit_duration(wpm),
+                STANDARD_WEIGHT,
+                &self.characters,
+            );
+        }
+        if let Some(audio) = audio {
+            let frequency = settings.frequency as f32;
+            let mut pitch = frequency + self.interference_offset;
+            if pitch > MAX_FREQUENCY as f32 {
+                pitch = (frequency - self.interference_offset).max(MIN_FREQUENCY as f32);
+            }
+            audio.set_interference_frequency(pitch);
+        }
+        self.interference.update_with(delta, |on| {
+            if let Some(audio) = audio {
+                audio.key_interference(on);
+            }
+        });
+    }
+
+    /// Send the other station's reply once the user's side of the over has been sent.
+    fn update_station(&mut self, delta: Duration, audio: &mut Option<AudioManager>) {
+        if self.reply_pending && !self.player.is_playing() {
+            self.reply_pending = false;
+            if let Some(over) = self.qso.as_ref().and_then(Qso::current) {
+                if let Some(audio) = audio {
+                    audio.set_station_frequency(self.station_pitch);
+                }
+                self.station.play(
+                    &over.reply,
+                    wpm_to_dit_duration(self.station_wpm),
+                    STANDARD_WEIGHT,
+                    &self.characters,
+                );
+            }
+        }
+        self.station.update_with(delta, |on| {
+            if let Some(audio) = audio {
+                audio.key_station(on);
+            }
+        });
+    }
+
+    /// Send the user's side of the current over, the other station replying after it.
+    fn send_over(&mut self, audio: &mut Option<AudioManager>, settings: &Settings) {
+        let Some(sent) = self
+            .qso
+            .as_ref()
+            .and_then(Qso::current)
+            .map(|over| over.sent.clone())
+        else {
+            return;
+        };
+        self.stop_drill(audio, settings);
+        self.replay_position = None;
+        self.player.set_spacing(settings.listening_spacing());
+        self.player.play(
+            &sent,
+            settings.listening_dit_duration(),
+            settings.weight_ratio(),
+            &self.characters,
+        );
+        self.reply_pending = true;
+    }
+
+    /// Start a new QSO with a station at another pitch and a slightly different speed.
+    fn start_qso(&mut self, audio: &mut Option<AudioManager>, settings: &Settings) {
+        self.qso = Some(Qso::new(&settings.callsign_regions));
+        self.qso_copy.clear();
+        let frequency = settings.frequency as f32;
+        let offset = fastrand::f32() * 150. + 100.;
+        self.station_pitch = if frequency + offset <= MAX_FREQUENCY as f32 {
+            frequency + offset
+        } else {
+            (frequency - offset).max(MIN_FREQUENCY as f32)
+        };
+        self.station_wpm = (settings.listening_wpm + fastrand::u8(..=STATION_WPM_SPREAD * 2))
+            .saturating_sub(STATION_WPM_SPREAD)
+            .clamp(MIN_WPM, MAX_WPM);
+        self.send_over(audio, settings);
+    }
+
+    /// Send a random unlocked character for the user to name.
+    fn next_character(&mut self, audio: &mut Option<AudioManager>, settings: &Settings) {
+        self.koch_current = Some(self.koch.pick());
+        self.koch_pitch = settings.pick_pitch();
+        self.send_character(audio, settings);
+    }
+
+    /// Send the character waiting to be named at its pitch.
+    fn send_character(&mut self, audio: &mut Option<AudioManager>, settings: &Settings) {
+        let Some(ch) = self.koch_current else {
+            return;
+        };
+        self.send_item(
+            SentItem {
+                text: ch.to_string(),
+                dit_duration: settings.listening_dit_duration(),
+                spacing: settings.listening_spacing(),
+                weight: settings.weight_ratio(),
+                pitch: self.koch_pitch,
+            },
+            audio,
+        );
+    }
+
+    /// Send a drill item at its pitch, keeping it to be replayed.
+    fn send_item(&mut self, item: SentItem, audio: &mut Option<AudioManager>) {
+        if let Some(audio) = audio {
+            audio.set_frequency(item.pitch);
+        }
+        self.player.set_spacing(item.spacing);
+        self.player
+            .play(&item.text, item.dit_duration, item.weight, &self.characters);
+        self.last_item = Some(item);
+    }
+
+    /// Send the last drill item again the way it was sent, cutting off anything being sent.
+    fn replay_last(&mut self, audio: &mut Option<AudioManager>) {
+        let Some(item) = self.last_item.take() else {
+            return;
+        };
+        self.replay_position = None;
+        self.text_playing = false;
+        self.player.stop(audio);
+        self.send_item(item, audio);
+    }
+
+    /// Stop the drill, going back to the configured pitch.
+    fn stop_drill(&mut self, audio: &mut Option<AudioManager>, settings: &Settings) {
+        self.koch_current = None;
+        self.reaction.stop();
+        #[cfg(feature = "speech")]
+        self.skip_announcement();
+        self.text_playing = false;
+        self.player.stop(audio);
+        self.station.play_signal([]);
+        self.reply_pending = false;
+        if let Some(audio) = audio {
+            audio.set_frequency(settings.frequency as f32);
+            audio.key_station(false);
+        }
     }
 
-    /// Render the listening screen (placeholder for now)
-    pub fn render(&mut self, ctx: &egui::Context) -> Option<AppState> {
+    /// Send the words waiting to be copied, stopping anything else.
+    fn send_head_copy(&mut self, audio: &mut Option<AudioManager>, settings: &Settings) {
+        let Some(answer) = self.head_copy.answer().map(str::to_string) else {
+            return;
+        };
+        self.stop_drill(audio, settings);
+        self.replay_position = None;
+        self.send_item(
+            SentItem {
+                text: answer,
+                dit_duration: settings.listening_dit_duration(),
+                spacing: settings.listening_spacing(),
+                weight: settings.weight_ratio(),
+                pitch: settings.frequency as f32,
+            },
+            audio,
+        );
+    }
+
+    /// Send the code groups waiting to be copied at their speed, stopping anything else.
+    fn send_code_groups(&mut self, audio: &mut Option<AudioManager>, settings: &Settings) {
+        self.stop_drill(audio, settings);
+        self.replay_position = None;
+        self.send_item(
+            SentItem {
+                text: self.code_groups.text(),
+                dit_duration: wpm_to_dit_duration(settings.code_group_wpm),
+                spacing: None,
+                weight: settings.weight_ratio(),
+                pitch: settings.frequency as f32,
+            },
+            audio,
+        );
+    }
+
+    /// Send the exchange waiting to be copied, stopping anything else.
+    fn send_exchange(&mut self, audio: &mut Option<AudioManager>, settings: &Settings) {
+        let Some(sent) = self.exchange.sent().map(str::to_string) else {
+            return;
+        };
+        self.stop_drill(audio, settings);
+        self.replay_position = None;
+        self.send_item(
+            SentItem {
+                text: sent,
+                dit_duration: settings.listening_dit_duration(),
+                spacing: settings.listening_spacing(),
+                weight: settings.weight_ratio(),
+                pitch: settings.frequency as f32,
+            },
+            audio,
+        );
+    }
+
+    /// Grade the character typed by the user and send the next one.
+    fn answer_character(
+        &mut self,
+        given: char,
+        audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
+    ) {
+        let Some(expected) = self.koch_current.take() else {
+            return;
+        };
+        #[cfg(feature = "speech")]
+        self.skip_announcement();
+        let lesson = self.koch.lesson();
+        let correct = self.koch.answer(expected, given);
+        // Speeds only count once copied well enough to unlock characters.
+        self.new_record = self.koch.window_accuracy().is_some_and(|accuracy| {
+            let wpm = (accuracy >= ADVANCE_ACCURACY).then_some(settings.listening_wpm);
+            settings
+                .records
+                .submit(&koch::record_name(lesson), accuracy, wpm)
+        });
+        self.stats.record_received(1);
+        self.stats.record_answer(expected, correct);
+        self.adapt_speed(correct, settings);
+        settings.koch_lesson = self.koch.lesson();
+        self.koch_feedback = Some((correct, expected));
+        self.next_character(audio, settings);
+    }
+
+    /// Send a character of the reaction test.
+    fn send_reaction(&mut self, ch: char, audio: &mut Option<AudioManager>, settings: &Settings) {
+        self.send_item(
+            SentItem {
+                text: ch.to_string(),
+                dit_duration: settings.listening_dit_duration(),
+                spacing: settings.listening_spacing(),
+                weight: settings.weight_ratio(),
+                pitch: settings.frequency as f32,
+            },
+            audio,
+        );
+    }
+
+    /// Time the key typed for the reaction test and send the next character,
+    /// keeping the reaction times that beat the records once the run is over.
+    fn answer_reaction(
+        &mut self,
+        given: char,
+        audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
+    ) {
+        let Some((expected, correct, next)) = self.reaction.answer(given) else {
+            return;
+        };
+        self.stats.record_received(1);
+        self.stats.record_answer(expected, correct);
+        let time = correct
+            .then(|| self.reaction.times().last().copied())
+            .flatten();
+        self.reaction_feedback = Some((expected, time));
+        match next {
+            Some(ch) => self.send_reaction(ch, audio, settings),
+            None => {
+                let mean = (self.reaction.misses() == 0)
+                    .then(|| self.reaction.mean())
+                    .flatten();
+                self.new_reaction_record =
+                    settings.records.submit_reaction(mean, self.reaction.best());
+            }
+        }
+    }
+
+    /// Say the name of the waiting Koch character a moment after its morse, unless answered first.
+    #[cfg(feature = "speech")]
+    fn update_announcement(&mut self, delta: Duration, finished: bool, settings: &Settings) {
+        if !settings.announce_characters || self.koch_current.is_none() {
+            self.announce_in = None;
+            return;
+        }
+        if finished {
+            self.announce_in = Some(ANNOUNCE_DELAY);
+        } else if let Some(left) = &mut self.announce_in {
+            *left = left.saturating_sub(delta);
+            if left.is_zero() {
+                self.announce_in = None;
+                if let Some(ch) = self.koch_current {
+                    self.speaker
+                        .get_or_insert_with(crate::speech::Speaker::new)
+                        .say(&crate::speech::character_name(ch));
+                }
+            }
+        }
+    }
+
+    /// Drop the announcement of the current character, cutting it off if it's being said.
+    #[cfg(feature = "speech")]
+    fn skip_announcement(&mut self) {
+        self.announce_in = None;
+        if let Some(speaker) = &mut self.speaker {
+            speaker.stop();
+        }
+    }
+
+    /// Follow the answer with the listening speed, when adaptive speed is on.
+    fn adapt_speed(&mut self, correct: bool, settings: &mut Settings) {
+        if !settings.adaptive_speed {
+            return;
+        }
+        self.stats.record_speed(settings.listening_wpm);
+        if self.adaptive.answer(correct, settings) {
+            self.stats.record_speed(settings.listening_wpm);
+        }
+    }
+
+    fn toggle_microphone(&mut self) {
+        self.microphone_samples.clear();
+        if self.microphone.take().is_some() {
+            return;
+        }
+        match Microphone::open() {
+            Ok(microphone) => {
+                let decoder = LiveDecoder::new(
+                    microphone.sample_rate(),
+                    self.expected_wpm,
+                    self.noise_gate,
+                    self.characters.clone(),
+                );
+                self.microphone = Some((microphone, decoder));
+                self.microphone_error = None;
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+                self.microphone_error = Some(err);
+            }
+        }
+    }
+
+    /// Feed captured audio to the decoder.
+    fn poll_microphone(&mut self) {
+        if let Some((microphone, decoder)) = &mut self.microphone {
+            microphone.drain(|samples| {
+                decoder.push_samples(samples);
+                if self.spectrum_open {
+                    self.microphone_samples.extend_from_slice(samples);
+                }
+            });
+            self.microphone_text.push_str(&decoder.take_text());
+            let excess = self.microphone_samples.len().saturating_sub(SPECTRUM_LEN);
+            self.microphone_samples.drain(..excess);
+        }
+    }
+
+    /// Decode the WAV file once the user has picked it.
+    fn poll_wav(&mut self) {
+        let Some(result) = self.pending_wav.as_ref().and_then(PendingFile::poll) else {
+            return;
+        };
+        self.pending_wav = None;
+        let Some(file) = result else {
+            return;
+        };
+        match signal::decode_wav(std::io::Cursor::new(file.bytes), &self.characters) {
+            Ok(transcription) => {
+                self.error = None;
+                self.transcription = Some((file.name, transcription));
+            }
+            Err(err) => {
+                tracing::warn!("Failed to decode {}: {}", file.name, err);
+                self.error = Some(err);
+            }
+        }
+    }
+
+    /// Read the session file once the user has picked it.
+    fn poll_session(&mut self) {
+        let Some(result) = self.pending_session.as_ref().and_then(PendingFile::poll) else {
+            return;
+        };
+        self.pending_session = None;
+        let Some(file) = result else {
+            return;
+        };
+        match Session::from_json(&file.bytes) {
+            Ok(session) => {
+                self.session_error = None;
+                let text = session.text(&self.characters);
+                self.session = Some((file.name, session, text));
+            }
+            Err(err) => {
+                tracing::warn!("Failed to load {}: {}", file.name, err);
+                self.session_error = Some(err);
+            }
+        }
+    }
+
+    /// Read the text file once the user has picked it.
+    fn poll_text(&mut self) {
+        let Some(result) = self.pending_text.as_ref().and_then(PendingFile::poll) else {
+            return;
+        };
+        self.pending_text = None;
+        let Some(file) = result else {
+            return;
+        };
+        let text = String::from_utf8_lossy(&file.bytes).into_owned();
+        if text.trim().is_empty() {
+            self.text_error = Some(format!("{} has no text", file.name));
+            return;
+        }
+        self.text_error = None;
+        self.text_skipped.clear();
+        for ch in text.chars() {
+            if !ch.is_whitespace()
+                && self.characters.char_to_morse(ch).is_none()
+                && !self.text_skipped.contains(ch)
+            {
+                self.text_skipped.push(ch);
+            }
+        }
+        self.text_playing = false;
+        self.text_file = Some((file.name, text));
+    }
+
+    /// Send the text file from the byte index at its speed, stopping anything else.
+    fn send_text_file(
+        &mut self,
+        start: usize,
+        audio: &mut Option<AudioManager>,
+        settings: &Settings,
+    ) {
+        let Some((_, text)) = &self.text_file else {
+            return;
+        };
+        let text = text[start..].to_string();
+        self.stop_drill(audio, settings);
+        self.replay_position = None;
+        self.player.play_signal([]);
+        self.player.set_spacing(None);
+        self.player.queue(
+            &text,
+            start,
+            wpm_to_dit_duration(self.text_wpm),
+            settings.weight_ratio(),
+            &self.characters,
+        );
+        self.text_playing = true;
+    }
+
+    /// Render the listening screen
+    pub fn render(
+        &mut self,
+        ctx: &egui::Context,
+        delta: Duration,
+        audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
+    ) -> Option<AppState> {
         let mut new_state = None;
+        self.poll_wav();
+        self.poll_session();
+        self.poll_text();
+        self.poll_microphone();
+        if let Some(audio) = audio {
+            audio.set_fading(settings.fading_ratio());
+            audio.set_noise(settings.noise_level());
+            audio.set_scope(self.spectrum_open);
+        }
+        self.update_interference(delta, audio, settings);
+        let finished = self.player.update(delta, audio);
+        if finished {
+            self.reaction.tone_ended();
+        } else {
+            self.reaction.update(delta);
+        }
+        if self.player.is_playing() && !self.player.is_paused() {
+            self.stats.record_activity();
+        }
+        if self.stats.reach_goal(settings.session_goal()) {
+            self.stats_open = true;
+        }
+        #[cfg(feature = "speech")]
+        self.update_announcement(delta, finished, settings);
+        self.text_playing &= !finished;
+        self.update_station(delta, audio);
+        if let Some(position) = &mut self.replay_position {
+            if finished {
+                self.replay_position = None;
+            } else if !self.player.is_paused() {
+                *position += delta;
+            }
+        }
+
+        // The first character typed while the drill or the reaction test waits
+        // is the answer, unless it goes into a text field.
+        let waiting = self.koch_current.is_some() || self.reaction.is_running();
+        if waiting && !ctx.wants_keyboard_input() {
+            let typed = ctx.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Text(text) => text.chars().find(|ch| !ch.is_whitespace()),
+                    _ => None,
+                })
+            });
+            if let Some(ch) = typed {
+                if self.koch_current.is_some() {
+                    self.answer_character(ch, audio, settings);
+                } else {
+                    self.answer_reaction(ch, audio, settings);
+                }
+            }
+        }
+
+        if self.spectrum_open {
+            egui::SidePanel::right("spectrum")
+                .resizable(false)
+                .exact_width(SCOPE_WIDTH)
+                .show(ctx, |ui| self.render_spectrum(ui, audio, settings));
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical_centered(|ui| {
-                ui.heading("Listening Mode");
-                ui.label("Coming soon!");
-                ui.add_space(20.0);
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.heading(tr("Listening Mode"));
+                    render_timer(ui, &self.stats, settings.session_goal());
+                    ui.add_space(20.0);
 
-                if ui.button("Back to Menu").clicked() {
-                    new_state = Some(AppState::MainMenu);
-                }
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut settings.fading, tr("Fading (QSB)"));
+                        ui.add_enabled_ui(settings.fading, |ui| {
+                            ui.label(tr("Fading depth:"));
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut settings.fading_depth,
+                                    MIN_FADING_DEPTH..=MAX_FADING_DEPTH,
+                                )
+                                .suffix("%"),
+                            );
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut settings.noise, tr("Noise (QRN)"));
+                        ui.add_enabled_ui(settings.noise, |ui| {
+                            ui.label(tr("Signal to noise:"));
+                            ui.add(
+                                egui::Slider::new(&mut settings.snr, MIN_SNR..=MAX_SNR)
+                                    .suffix(" dB"),
+                            );
+                        });
+                        ui.checkbox(&mut settings.interference, tr("Interference (QRM)"));
+                    });
+                    ui.horizontal(|ui| {
+                        // Items already being sent keep their speed, the next one uses the new one.
+                        ui.label(tr("Speed:"));
+                        ui.add(
+                            egui::Slider::new(&mut settings.listening_wpm, MIN_WPM..=MAX_WPM)
+                                .suffix(" WPM"),
+                        );
+                        ui.checkbox(&mut settings.farnsworth, tr("Farnsworth"));
+                        ui.add_enabled_ui(settings.farnsworth, |ui| {
+                            ui.label(tr("Overall:"));
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut settings.farnsworth_wpm,
+                                    MIN_WPM..=settings.listening_wpm,
+                                )
+                                .suffix(" WPM"),
+                            );
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut settings.adaptive_speed, tr("Adaptive speed"))
+                            .on_hover_text(
+                                tr("Slow down after a wrong answer, speed up after a streak of right ones"),
+                            );
+                        ui.add_enabled_ui(settings.adaptive_speed, |ui| {
+                            ui.label(tr("Step:"));
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut settings.adaptive_step,
+                                    MIN_ADAPTIVE_STEP..=MAX_ADAPTIVE_STEP,
+                                )
+                                .suffix(" WPM"),
+                            );
+                            ui.label(tr("Streak:"));
+                            ui.add(egui::Slider::new(
+                                &mut settings.adaptive_streak,
+                                MIN_ADAPTIVE_STREAK..=MAX_ADAPTIVE_STREAK,
+                            ));
+                            ui.label(tr("Range:"));
+                            ui.add(
+                                egui::DragValue::new(&mut settings.adaptive_min_wpm)
+                                    .range(MIN_WPM..=settings.adaptive_max_wpm),
+                            );
+                            ui.label(tr("to"));
+                            ui.add(
+                                egui::DragValue::new(&mut settings.adaptive_max_wpm)
+                                    .range(settings.adaptive_min_wpm..=MAX_WPM)
+                                    .suffix(" WPM"),
+                            );
+                        });
+                    });
+                    self.render_transport(ui, audio, settings);
+                    ui.add_space(20.0);
+
+                    ui.add_enabled_ui(self.pending_wav.is_none(), |ui| {
+                        if ui.button(tr("Load WAV")).clicked() {
+                            self.pending_wav = Some(files::open_file(ctx, "WAV audio", &["wav"]));
+                        }
+                    });
+
+                    if let Some(error) = &self.error {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+
+                    if let Some((name, transcription)) = &self.transcription {
+                        ui.add_space(20.0);
+                        ui.label(RichText::new(name).strong());
+                        match transcription.wpm {
+                            Some(wpm) => ui.label(format!("Estimated speed: {:.0} WPM", wpm)),
+                            None => ui.label(tr("No signal found")),
+                        };
+                        ui.label(RichText::new(&transcription.text).size(32.));
+                    }
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_session(ctx, ui, audio, settings);
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_text_file(ctx, ui, audio, settings);
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_koch(ui, audio, settings);
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_reaction(ui, audio, settings);
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_head_copy(ui, audio, settings);
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_code_groups(ui, audio, settings);
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_exchange(ui, audio, settings);
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_qso(ui, audio, settings);
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    self.render_microphone(ui);
+
+                    ui.add_space(20.0);
+                    if ui.button(tr("Stats")).clicked() {
+                        self.stats_open = !self.stats_open;
+                    }
+                    if ui.button(tr("Character accuracy")).clicked() {
+                        self.accuracy_open = !self.accuracy_open;
+                    }
+                    if ui.button(tr("Spectrum")).clicked() {
+                        self.spectrum_open = !self.spectrum_open;
+                    }
+                    if ui.button(tr("Back to Menu")).clicked()
+                        || ui.input(|i| i.key_pressed(egui::Key::Escape))
+                    {
+                        new_state = Some(AppState::MainMenu);
+                    }
+                });
             });
         });
 
+        egui::Window::new(tr("Session stats"))
+            .open(&mut self.stats_open)
+            .collapsible(false)
+            .show(ctx, |ui| render_stats(ui, &self.stats));
+
+        for (ch, correct) in self.stats.take_answers() {
+            settings.accuracy.record(ch, correct);
+        }
+        let drill_weakest = egui::Window::new(tr("Character accuracy"))
+            .open(&mut self.accuracy_open)
+            .collapsible(false)
+            .show(ctx, |ui| render_accuracy(ui, &mut settings.accuracy))
+            .and_then(|response| response.inner)
+            .unwrap_or_default();
+        if drill_weakest {
+            let weakest = settings.accuracy.weakest(WEAKEST_COUNT);
+            let pool = weakest.iter().map(|(ch, _)| *ch).collect::<Vec<_>>();
+            self.code_groups.start(settings.code_group_count, &pool);
+            self.code_group_copy.clear();
+            self.send_code_groups(audio, settings);
+        }
+
+        if self.microphone.is_some() {
+            // The level and the decoded text change with every captured buffer.
+            ctx.request_repaint();
+        } else if let Some(after) = [
+            self.player.next_change(),
+            #[cfg(feature = "speech")]
+            self.announce_in,
+            self.interference.next_change(),
+            self.station.next_change(),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        {
+            ctx.request_repaint_after(after);
+        }
+
+        if new_state.is_some() {
+            self.stop_drill(audio, settings);
+            self.replay_position = None;
+            // Other screens play a clean tone.
+            self.interference = MorsePlayer::new();
+            if let Some(audio) = audio {
+                audio.set_fading(0.);
+                audio.set_noise(0.);
+                audio.key_interference(false);
+                audio.set_scope(false);
+            }
+        }
         new_state
     }
+
+    /// Controls of whatever the player is sending, with its progress.
+    fn render_transport(
+        &mut self,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &Settings,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Playback:"));
+            let playing = self.player.is_playing();
+            if self.player.is_paused() {
+                if ui.button(tr("Play")).clicked() {
+                    self.player.resume(audio);
+                }
+            } else if ui
+                .add_enabled(playing, egui::Button::new(tr("Pause")))
+                .clicked()
+            {
+                self.player.pause(audio);
+            }
+            if ui
+                .add_enabled(playing, egui::Button::new(tr("Stop")))
+                .clicked()
+            {
+                self.stop_drill(audio, settings);
+                self.replay_position = None;
+            }
+            if ui
+                .add_enabled(playing, egui::Button::new(tr("Reset")))
+                .clicked()
+            {
+                self.player.reset(audio);
+                if self.replay_position.is_some() {
+                    self.replay_position = Some(Duration::ZERO);
+                }
+            }
+            let replay = egui::Button::new(tr("Replay last"));
+            if ui.add_enabled(self.last_item.is_some(), replay).clicked() {
+                self.replay_last(audio);
+            }
+            let progress = self.player.progress().unwrap_or(0.);
+            ui.add(
+                egui::ProgressBar::new(progress)
+                    .desired_width(150.)
+                    .show_percentage(),
+            );
+        });
+    }
+
+    fn render_session(
+        &mut self,
+        ctx: &egui::Context,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &Settings,
+    ) {
+        ui.heading(tr("Session replay"));
+        ui.add_enabled_ui(self.pending_session.is_none(), |ui| {
+            if ui.button(tr("Load session")).clicked() {
+                self.pending_session = Some(files::open_file(ctx, "Session", &["json"]));
+            }
+        });
+        if let Some(error) = &self.session_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        let Some((name, session, text)) = &self.session else {
+            return;
+        };
+        ui.label(RichText::new(name).strong());
+        ui.label(format!(
+            "{} WPM, {} keyer, {} Hz",
+            session.wpm, session.keyer_mode, session.frequency
+        ));
+        ui.label(RichText::new(text).size(32.));
+
+        // Elements already replayed are highlighted.
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.;
+            for element in &session.elements {
+                let mut symbol = RichText::new(element.kind.symbol()).monospace().size(20.);
+                if !self.replay_perfect
+                    && self
+                        .replay_position
+                        .is_some_and(|position| element.start() <= position)
+                {
+                    symbol = symbol.strong().color(ui.visuals().strong_text_color());
+                } else {
+                    symbol = symbol.weak();
+                }
+                ui.label(symbol);
+            }
+        });
+
+        if self.replay_position.is_some() {
+            ui.horizontal(|ui| {
+                if self.player.is_paused() {
+                    if ui.button(tr("Resume")).clicked() {
+                        self.player.resume(audio);
+                    }
+                } else if ui.button(tr("Pause")).clicked() {
+                    self.player.pause(audio);
+                }
+                if ui.button(tr("Restart")).clicked() {
+                    self.player.reset(audio);
+                    self.replay_position = Some(Duration::ZERO);
+                }
+                if ui.button(tr("Stop replay")).clicked() {
+                    self.replay_position = None;
+                    self.player.stop(audio);
+                }
+            });
+        } else {
+            let replay = ui
+                .horizontal(|ui| {
+                    if ui.button(tr("Replay as keyed")).clicked() {
+                        Some((session.signal(), false))
+                    } else if ui.button(tr("Replay with perfect timing")).clicked() {
+                        Some((session.perfect_signal(settings.weight_ratio()), true))
+                    } else {
+                        None
+                    }
+                })
+                .inner;
+            if let Some((signal, perfect)) = replay {
+                self.stop_drill(audio, settings);
+                self.replay_perfect = perfect;
+                self.replay_position = Some(Duration::ZERO);
+                self.player.play_signal(signal);
+            }
+        }
+    }
+
+    fn render_text_file(
+        &mut self,
+        ctx: &egui::Context,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &Settings,
+    ) {
+        ui.heading(tr("Text reading"));
+        ui.add_enabled_ui(self.pending_text.is_none(), |ui| {
+            if ui.button(tr("Load text")).clicked() {
+                self.pending_text = Some(files::open_file(ctx, "Text", &["txt"]));
+            }
+        });
+        if let Some(error) = &self.text_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        let Some((name, text)) = &self.text_file else {
+            return;
+        };
+        ui.label(RichText::new(name).strong());
+        if !self.text_skipped.is_empty() {
+            ui.label(RichText::new(format!("Skipped, no morse for: {}", self.text_skipped)).weak());
+        }
+
+        // The word being sent is highlighted.
+        let current = self
+            .player
+            .current_character()
+            .filter(|_| self.text_playing);
+        egui::ScrollArea::vertical()
+            .id_salt("text_file")
+            .max_height(200.)
+            .show(ui, |ui| {
+                let font = egui::FontId::proportional(20.);
+                let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
+                let mut job = egui::text::LayoutJob::default();
+                match current {
+                    Some(index) => {
+                        let word = word_at(text, index);
+                        job.append(&text[..word.start], 0., normal.clone());
+                        job.append(
+                            &text[word.clone()],
+                            0.,
+                            egui::TextFormat {
+                                background: ui.visuals().selection.bg_fill,
+                                ..normal.clone()
+                            },
+                        );
+                        job.append(&text[word.end..], 0., normal);
+                    }
+                    None => job.append(text, 0., normal),
+                }
+                ui.label(job);
+            });
+
+        let mut restart = None;
+        ui.horizontal(|ui| {
+            ui.label(tr("Speed:"));
+            let speed =
+                ui.add(egui::Slider::new(&mut self.text_wpm, MIN_WPM..=MAX_WPM).suffix(" WPM"));
+            // Timings are fixed once queued, so the rest is queued again from the current word.
+            if speed.changed()
+                && self.text_playing
+                && let Some(index) = self.player.current_character()
+            {
+                restart = Some(word_at(text, index).start);
+            }
+        });
+        ui.horizontal(|ui| {
+            if self.text_playing {
+                if self.player.is_paused() {
+                    if ui.button(tr("Resume")).clicked() {
+                        self.player.resume(audio);
+                    }
+                } else if ui.button(tr("Pause")).clicked() {
+                    self.player.pause(audio);
+                }
+                if ui.button(tr("Stop")).clicked() {
+                    self.text_playing = false;
+                    self.player.stop(audio);
+                }
+            } else if ui.button(tr("Play")).clicked() {
+                restart = Some(0);
+            }
+        });
+        if let Some(start) = restart {
+            let paused = self.text_playing && self.player.is_paused();
+            self.send_text_file(start, audio, settings);
+            if paused {
+                self.player.pause(audio);
+            }
+        }
+    }
+
+    /// Single characters to type as fast as possible, with the reaction times of the last run.
+    fn render_reaction(
+        &mut self,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &Settings,
+    ) {
+        ui.heading(tr("Reaction test"));
+        ui.label(format!(
+            "Type each of {} characters as fast as you can once it ends.",
+            REACTION_RUN
+        ));
+        let record = settings.records.reaction();
+        if record.mean_ms.is_some() || record.best_ms.is_some() {
+            let ms = |ms: Option<u32>| ms.map_or("--".to_string(), |ms| format!("{} ms", ms));
+            ui.label(format!(
+                "Best: {} on average, {} fastest",
+                ms(record.mean_ms),
+                ms(record.best_ms)
+            ));
+        }
+        if self.new_reaction_record {
+            ui.label(RichText::new(tr("🏆 New record!")).strong().size(20.));
+        }
+
+        if self.reaction.is_running() {
+            if ui.button(tr("Stop")).clicked() {
+                self.stop_drill(audio, settings);
+            }
+        } else if ui.button(tr("Start run")).clicked() {
+            self.stop_drill(audio, settings);
+            self.replay_position = None;
+            self.reaction_feedback = None;
+            self.new_reaction_record = false;
+            if let Some(ch) = self
+                .reaction
+                .start(GroupCharacters::Mixed.pool(self.characters.alphabet()))
+            {
+                self.send_reaction(ch, audio, settings);
+            }
+        }
+
+        match self.reaction_feedback {
+            Some((ch, Some(time))) => {
+                ui.label(RichText::new(format!("{} in {} ms", ch, time.as_millis())).strong());
+            }
+            Some((ch, None)) => {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!(
+                        "It was {} ({})",
+                        ch,
+                        self.characters.char_to_morse(ch).unwrap_or_default()
+                    ),
+                );
+            }
+            None => {}
+        }
+
+        if self.reaction.is_running() {
+            return;
+        }
+        let (Some(mean), Some(best)) = (self.reaction.mean(), self.reaction.best()) else {
+            return;
+        };
+        ui.label(format!(
+            "Mean {} ms, fastest {} ms, {} wrong",
+            mean.as_millis(),
+            best.as_millis(),
+            self.reaction.misses()
+        ));
+        let distribution = self.reaction.distribution();
+        let last = distribution.len() - 1;
+        let step = REACTION_STEP.as_millis() as usize;
+        egui::Grid::new("reaction_distribution").show(ui, |ui| {
+            for (i, count) in distribution.iter().enumerate() {
+                if i == last {
+                    ui.monospace(format!("{:>4}+ ms", i * step));
+                } else {
+                    ui.monospace(format!("{:>4}-{} ms", i * step, (i + 1) * step - 1));
+                }
+                ui.monospace("█".repeat(*count));
+                ui.end_row();
+            }
+        });
+    }
+
+    fn render_koch(
+        &mut self,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
+    ) {
+        ui.heading(tr("Koch trainer"));
+        #[cfg(feature = "speech")]
+        {
+            ui.checkbox(&mut settings.announce_characters, tr("Say the character"))
+                .on_hover_text(tr("Said a moment after its morse, answer first to skip it"));
+            if let Some(error) = self.speaker.as_ref().and_then(|speaker| speaker.error()) {
+                ui.colored_label(ui.visuals().warn_fg_color, error);
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.random_pitch, tr("Random pitch"));
+            ui.add_enabled_ui(settings.random_pitch, |ui| {
+                ui.label(tr("Range:"));
+                ui.add(
+                    egui::Slider::new(
+                        &mut settings.pitch_spread,
+                        MIN_PITCH_SPREAD..=MAX_PITCH_SPREAD,
+                    )
+                    .suffix(" Hz"),
+                );
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Lesson:"));
+            let mut lesson = self.koch.lesson();
+            if ui
+                .add(egui::Slider::new(&mut lesson, 1..=MAX_LESSON))
+                .changed()
+            {
+                self.koch.set_lesson(lesson);
+                settings.koch_lesson = lesson;
+            }
+        });
+        ui.label(
+            RichText::new(self.koch.characters().iter().collect::<String>())
+                .monospace()
+                .size(20.),
+        );
+        match self.koch.accuracy() {
+            Some(accuracy) => ui.label(format!(
+                "Accuracy: {:.0}% ({:.0}% unlocks the next character)",
+                accuracy * 100.,
+                ADVANCE_ACCURACY * 100.
+            )),
+            None => ui.label(tr("Name the characters you hear by typing them.")),
+        };
+        if let Some(record) = settings.records.get(&koch::record_name(self.koch.lesson())) {
+            let speed = record
+                .wpm
+                .map(|wpm| format!(" at up to {} WPM", wpm))
+                .unwrap_or_default();
+            ui.label(format!(
+                "Best: {:.0}%{} (now {} WPM)",
+                record.accuracy * 100.,
+                speed,
+                settings.listening_wpm
+            ));
+        }
+        if self.new_record {
+            ui.label(RichText::new(tr("🏆 New record!")).strong().size(20.));
+        }
+
+        if self.koch_current.is_some() {
+            ui.horizontal(|ui| {
+                let repeat = egui::Button::new(tr("Repeat"));
+                if ui.add_enabled(!self.player.is_playing(), repeat).clicked() {
+                    self.send_character(audio, settings);
+                }
+                if ui.button(tr("Stop")).clicked() {
+                    self.stop_drill(audio, settings);
+                }
+            });
+        } else if ui.button(tr("Start drill")).clicked() {
+            self.replay_position = None;
+            self.koch_feedback = None;
+            self.next_character(audio, settings);
+        }
+
+        if let Some((correct, ch)) = self.koch_feedback {
+            if correct {
+                ui.label(RichText::new(format!("Correct: {}", ch)).strong());
+            } else {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!(
+                        "It was {} ({})",
+                        ch,
+                        self.characters.char_to_morse(ch).unwrap_or_default()
+                    ),
+                );
+            }
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            for ch in self.koch.characters() {
+                if let Some(accuracy) = self.koch.character_accuracy(*ch) {
+                    ui.label(RichText::new(format!("{} {:.0}%", ch, accuracy * 100.)).monospace());
+                }
+            }
+        });
+    }
+
+    fn render_head_copy(
+        &mut self,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
+    ) {
+        ui.heading(tr("Head copy"));
+        text_source_settings(ui, settings);
+        ui.horizontal(|ui| {
+            ui.label(tr("Words:"));
+            ui.add(egui::Slider::new(
+                &mut self.head_copy_words,
+                1..=MAX_HEAD_COPY_WORDS,
+            ));
+        });
+        match self.head_copy.accuracy() {
+            Some(accuracy) => ui.label(format!("Accuracy: {:.0}%", accuracy * 100.)),
+            None => ui.label(tr("Copy the words you hear in your head, then type them.")),
+        };
+
+        if self.head_copy.answer().is_some() {
+            ui.horizontal(|ui| {
+                let attempt = ui.add(
+                    egui::TextEdit::singleline(&mut self.head_copy_attempt)
+                        .hint_text(tr("What did you hear?")),
+                );
+                let entered = attempt.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let repeat = egui::Button::new(tr("Repeat"));
+                if ui.add_enabled(!self.player.is_playing(), repeat).clicked() {
+                    self.send_head_copy(audio, settings);
+                }
+                if entered || ui.button(tr("Reveal")).clicked() {
+                    self.player.stop(audio);
+                    if let Some((answer, grade)) = self.head_copy.submit(&self.head_copy_attempt) {
+                        self.stats.record_received(answer.chars().count());
+                        self.stats.record_grade(grade);
+                        let correct = grade.accuracy >= 1.;
+                        self.adapt_speed(correct, settings);
+                    }
+                    self.head_copy_attempt.clear();
+                }
+            });
+        } else if ui.button(tr("Play words")).clicked() {
+            self.head_copy
+                .next(self.head_copy_words, &settings.text_generator());
+            self.send_head_copy(audio, settings);
+        }
+
+        if let Some((answer, grade)) = self.head_copy.last() {
+            ui.label(RichText::new(answer).size(32.));
+            if grade.passed() {
+                ui.label(RichText::new(tr("Copied correctly")).strong());
+            } else {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("Copied {:.0}% correctly", grade.accuracy * 100.),
+                );
+            }
+        }
+    }
+
+    fn render_code_groups(
+        &mut self,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
+    ) {
+        ui.heading(tr("Code groups"));
+        ui.add_enabled_ui(!self.code_groups.is_running(), |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("code_group_characters")
+                    .selected_text(settings.code_group_characters.to_string())
+                    .show_ui(ui, |ui| {
+                        for option in GroupCharacters::ALL {
+                            ui.selectable_value(
+                                &mut settings.code_group_characters,
+                                option,
+                                option.to_string(),
+                            );
+                        }
+                    });
+                ui.label(tr("Groups:"));
+                ui.add(egui::Slider::new(
+                    &mut settings.code_group_count,
+                    MIN_CODE_GROUPS..=MAX_CODE_GROUPS,
+                ));
+                ui.label(tr("WPM:"));
+                ui.add(egui::Slider::new(
+                    &mut settings.code_group_wpm,
+                    MIN_WPM..=MAX_WPM,
+                ));
+            });
+        });
+
+        if self.code_groups.is_running() {
+            ui.add(
+                egui::TextEdit::multiline(&mut self.code_group_copy)
+                    .hint_text(tr("Type the groups you copy, separated by spaces"))
+                    .font(egui::TextStyle::Monospace),
+            );
+            ui.horizontal(|ui| {
+                let repeat = egui::Button::new(tr("Repeat"));
+                if ui.add_enabled(!self.player.is_playing(), repeat).clicked() {
+                    self.send_code_groups(audio, settings);
+                }
+                if ui.button(tr("Check")).clicked() {
+                    self.player.stop(audio);
+                    for result in self.code_groups.submit(&self.code_group_copy) {
+                        self.stats.record_received(result.group.chars().count());
+                        self.stats.record_grade(&result.grade);
+                    }
+                    self.code_group_copy.clear();
+                }
+            });
+        } else if ui.button(tr("Start run")).clicked() {
+            self.code_groups.start(
+                settings.code_group_count,
+                &settings
+                    .code_group_characters
+                    .pool(self.characters.alphabet()),
+            );
+            self.send_code_groups(audio, settings);
+        }
+
+        let results = self.code_groups.results();
+        if !results.is_empty() {
+            let correct = results
+                .iter()
+                .filter(|result| result.grade.passed())
+                .count();
+            ui.label(format!("{} of {} groups correct", correct, results.len()));
+            egui::Grid::new("code_group_results")
+                .striped(true)
+                .show(ui, |ui| {
+                    for result in results.iter().filter(|result| !result.grade.passed()) {
+                        ui.label(RichText::new(&result.group).monospace().strong());
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            RichText::new(&result.copy).monospace(),
+                        );
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+
+    fn render_exchange(
+        &mut self,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
+    ) {
+        ui.heading(tr("Contest exchanges"));
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("exchange_format")
+                .selected_text(settings.exchange_format.to_string())
+                .show_ui(ui, |ui| {
+                    for option in ExchangeFormat::ALL {
+                        ui.selectable_value(
+                            &mut settings.exchange_format,
+                            option,
+                            option.to_string(),
+                        );
+                    }
+                });
+            ui.checkbox(&mut settings.cut_numbers, tr("Cut numbers"))
+                .on_hover_text(tr("Send T for 0, N for 9 and A for 1"));
+        });
+        match self.exchange.accuracy() {
+            Some(accuracy) => ui.label(format!("Accuracy: {:.0}%", accuracy * 100.)),
+            None => ui.label(tr(
+                "Copy signal reports and exchanges, in digits or cut numbers.",
+            )),
+        };
+
+        if self.exchange.sent().is_some() {
+            ui.horizontal(|ui| {
+                let copy = ui.add(
+                    egui::TextEdit::singleline(&mut self.exchange_copy)
+                        .hint_text(tr("What did you hear?")),
+                );
+                let entered = copy.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let repeat = egui::Button::new(tr("Repeat"));
+                if ui.add_enabled(!self.player.is_playing(), repeat).clicked() {
+                    self.send_exchange(audio, settings);
+                }
+                if entered || ui.button(tr("Reveal")).clicked() {
+                    self.player.stop(audio);
+                    if let Some((sent, grade)) = self.exchange.submit(&self.exchange_copy) {
+                        self.stats.record_received(sent.chars().count());
+                        self.stats.record_grade(grade);
+                        let correct = grade.accuracy >= 1.;
+                        self.adapt_speed(correct, settings);
+                    }
+                    self.exchange_copy.clear();
+                }
+            });
+        } else if ui.button(tr("Play exchange")).clicked() {
+            self.exchange
+                .next(settings.exchange_format, settings.cut_numbers);
+            self.send_exchange(audio, settings);
+        }
+
+        if let Some((sent, grade)) = self.exchange.last() {
+            ui.label(RichText::new(sent).size(32.));
+            if grade.passed() {
+                ui.label(RichText::new(tr("Copied correctly")).strong());
+            } else {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("Copied {:.0}% correctly", grade.accuracy * 100.),
+                );
+            }
+        }
+    }
+
+    fn render_qso(
+        &mut self,
+        ui: &mut egui::Ui,
+        audio: &mut Option<AudioManager>,
+        settings: &Settings,
+    ) {
+        ui.heading(tr("QSO"));
+        match self.qso.as_ref().and_then(Qso::accuracy) {
+            Some(accuracy) => ui.label(format!(
+                "Copied {:.0}% of the other station",
+                accuracy * 100.
+            )),
+            None => ui.label(tr("Call CQ and copy the station that answers.")),
+        };
+
+        if let Some(qso) = &self.qso {
+            for (over, grade) in qso.history() {
+                ui.label(RichText::new(&over.sent).monospace());
+                ui.label(RichText::new(&over.reply).monospace().strong());
+                if !grade.passed() {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("Copied {:.0}% correctly", grade.accuracy * 100.),
+                    );
+                }
+            }
+        }
+
+        if let Some(over) = self.qso.as_ref().and_then(Qso::current) {
+            ui.label(RichText::new(&over.sent).monospace());
+            ui.horizontal(|ui| {
+                let copy = ui.add(
+                    egui::TextEdit::singleline(&mut self.qso_copy).hint_text(tr("Copy the reply")),
+                );
+                let entered = copy.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let busy = self.player.is_playing() || self.station.is_playing();
+                if ui
+                    .add_enabled(!busy, egui::Button::new(tr("Repeat")))
+                    .clicked()
+                {
+                    self.reply_pending = true;
+                }
+                if entered || ui.button(tr("Reply")).clicked() {
+                    if let Some(qso) = &mut self.qso {
+                        let count = qso.current().map_or(0, |over| over.reply.chars().count());
+                        if let Some(grade) = qso.submit(&self.qso_copy) {
+                            self.stats.record_received(count);
+                            self.stats.record_grade(grade);
+                        }
+                    }
+                    self.qso_copy.clear();
+                    self.send_over(audio, settings);
+                }
+            });
+        } else {
+            let label = if self.qso.is_some() {
+                "New QSO"
+            } else {
+                "Call CQ"
+            };
+            if ui.button(label).clicked() {
+                self.start_qso(audio, settings);
+            }
+        }
+    }
+
+    /// Spectrum of the microphone while it listens, of the output otherwise.
+    fn render_spectrum(
+        &self,
+        ui: &mut egui::Ui,
+        audio: &Option<AudioManager>,
+        settings: &Settings,
+    ) {
+        let sidetone = settings.frequency as f32;
+        if let Some((microphone, _)) = &self.microphone {
+            if self.microphone_samples.len() == SPECTRUM_LEN {
+                let bin_width = microphone.sample_rate() as f32 / SPECTRUM_LEN as f32;
+                let power = signal::power_spectrum(&self.microphone_samples);
+                render_spectrum(ui, &power, bin_width, sidetone);
+            } else {
+                ui.heading(tr("Spectrum"));
+            }
+            ui.label(tr("From the microphone"));
+        } else if let Some(audio) = audio {
+            render_spectrum(ui, &audio.spectrum(), SPECTRUM_BIN, sidetone);
+        } else {
+            ui.heading(tr("Spectrum"));
+            ui.label(tr("No audio output"));
+        }
+    }
+
+    fn render_microphone(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("Microphone"));
+        let label = if self.microphone.is_some() {
+            "Stop listening"
+        } else {
+            "Start listening"
+        };
+        if ui.button(label).clicked() {
+            self.toggle_microphone();
+        }
+        if let Some(error) = &self.microphone_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr("Noise gate:"));
+            let gate = ui.add(
+                egui::Slider::new(&mut self.noise_gate, MIN_NOISE_GATE..=MAX_NOISE_GATE)
+                    .logarithmic(true),
+            );
+            if gate.changed()
+                && let Some((_, decoder)) = &mut self.microphone
+            {
+                decoder.set_threshold(self.noise_gate);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Expected WPM:"));
+            let wpm = ui.add(egui::Slider::new(&mut self.expected_wpm, MIN_WPM..=MAX_WPM));
+            if wpm.changed()
+                && let Some((_, decoder)) = &mut self.microphone
+            {
+                decoder.set_wpm(self.expected_wpm);
+            }
+        });
+
+        if let Some((_, decoder)) = &self.microphone {
+            let level = decoder.level();
+            let bar = egui::ProgressBar::new((level / MAX_NOISE_GATE).min(1.))
+                .desired_width(200.)
+                .text(if level >= self.noise_gate {
+                    "Tone"
+                } else {
+                    "Silence"
+                });
+            ui.add(bar);
+            ui.label(format!("Estimated speed: {:.0} WPM", decoder.wpm()));
+        }
+
+        if !self.microphone_text.is_empty() {
+            ui.label(RichText::new(&self.microphone_text).size(32.));
+            if ui.button(tr("Clear")).clicked() {
+                self.microphone_text.clear();
+            }
+        }
+    }
+}
+
+/// Byte range of the word around the byte index.
+fn word_at(text: &str, index: usize) -> std::ops::Range<usize> {
+    let start = text[..index].rfind(char::is_whitespace).map_or(0, |space| {
+        space + text[space..].chars().next().map_or(1, char::len_utf8)
+    });
+    let end = text[index..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |space| index + space);
+    start..end
 }
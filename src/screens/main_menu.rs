@@ -1,44 +1,135 @@
 use egui::{self, RichText};
 
-use crate::state::AppState;
+use crate::{i18n::tr, settings::Settings, state::AppState, stats};
 
-pub struct MainMenuScreen;
+/// Keys opening the entries of the menu, in order
+static SHORTCUTS: [egui::Key; 4] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+];
+
+pub struct MainMenuScreen {
+    about_open: bool,
+    /// Pass the menu was last shown in, to tell when it's entered again
+    last_pass: Option<u64>,
+}
 
 impl MainMenuScreen {
     pub fn new() -> Self {
-        Self
+        Self {
+            about_open: false,
+            last_pass: None,
+        }
     }
 
     /// Render the main menu and return the new state if changed
-    pub fn render(&mut self, ctx: &egui::Context) -> Option<AppState> {
+    pub fn render(&mut self, ctx: &egui::Context, settings: &Settings) -> Option<AppState> {
         let mut new_state = None;
+        // Focus starts on the first button whenever the menu is entered.
+        let pass = ctx.cumulative_pass_nr();
+        let entered = self.last_pass.is_none_or(|last| last + 1 < pass);
+        self.last_pass = Some(pass);
+        let (shortcut, navigating) = ctx.input(|i| {
+            (
+                SHORTCUTS.iter().position(|key| i.key_pressed(*key)),
+                i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::ArrowDown),
+            )
+        });
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical_centered_justified(|ui| {
-                ui.add_space(200.0);
-
-                ui.heading(RichText::new("MORSET").size(48.0));
-                ui.add_space(20.0);
-                ui.label("Morse Code Practice");
-                ui.add_space(40.0);
-
-                ui.group(|ui| {
-                    if ui.button(RichText::new("Writing").size(24.0)).clicked() {
-                        new_state = Some(AppState::Writing);
-                    }
-
-                    if ui.button(RichText::new("Listening").size(24.0)).clicked() {
-                        new_state = Some(AppState::Listening);
-                    }
-
-                    #[cfg(not(target_arch = "wasm32"))]
-                    if ui.button(RichText::new("Exit").size(24.0)).clicked() {
-                        std::process::exit(0);
-                    }
+            // Less room above the title on short screens, and scrolling when it still doesn't fit.
+            let top = (ui.available_height() * 0.2).min(200.0);
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    ui.vertical_centered_justified(|ui| {
+                        ui.add_space(top);
+
+                        ui.heading(RichText::new("MORSET").size(48.0));
+                        ui.add_space(20.0);
+                        ui.label(tr("Morse Code Practice"));
+                        let today = stats::today();
+                        let streak = settings.practice.streak(today);
+                        if streak > 0 {
+                            ui.add_space(10.0);
+                            ui.label(format!(
+                                "🔥 {} day streak · {} min today · {} min this week",
+                                streak,
+                                settings.practice.day(today).as_secs() / 60,
+                                settings.practice.week(today).as_secs() / 60
+                            ));
+                        }
+                        ui.add_space(40.0);
+
+                        ui.group(|ui| {
+                            // Up and Down move the focus between the buttons and Enter presses
+                            // the focused one, as egui does for any focused widget.
+                            let writing = ui.button(RichText::new(tr("Writing")).size(24.0));
+                            if entered
+                                || navigating && ui.memory(|memory| memory.focused().is_none())
+                            {
+                                writing.request_focus();
+                            }
+                            if writing.clicked() || shortcut == Some(0) {
+                                new_state = Some(AppState::Writing);
+                            }
+
+                            if ui
+                                .button(RichText::new(tr("Listening")).size(24.0))
+                                .clicked()
+                                || shortcut == Some(1)
+                            {
+                                new_state = Some(AppState::Listening);
+                            }
+
+                            if ui
+                                .button(RichText::new(tr("Settings")).size(24.0))
+                                .clicked()
+                                || shortcut == Some(2)
+                            {
+                                new_state = Some(AppState::Settings);
+                            }
+
+                            if ui.button(RichText::new(tr("About")).size(24.0)).clicked()
+                                || shortcut == Some(3)
+                            {
+                                self.about_open = true;
+                            }
+
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if ui.button(RichText::new(tr("Exit")).size(24.0)).clicked() {
+                                std::process::exit(0);
+                            }
+                        });
+                        ui.add_space(10.0);
+                        ui.weak(tr("↑↓ and Enter, or 1-4"));
+                    });
                 });
-            });
         });
 
+        egui::Window::new(tr("About"))
+            .open(&mut self.about_open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.heading(format!("Morset {}", env!("CARGO_PKG_VERSION")));
+                ui.label(env!("CARGO_PKG_DESCRIPTION"));
+                ui.add_space(8.0);
+                egui::Grid::new("about").num_columns(2).show(ui, |ui| {
+                    ui.label(tr("License:"));
+                    ui.label(env!("CARGO_PKG_LICENSE"));
+                    ui.end_row();
+                    ui.label(tr("Target:"));
+                    ui.label(env!("MORSET_TARGET"));
+                    ui.end_row();
+                    ui.label(tr("Source:"));
+                    ui.hyperlink(env!("CARGO_PKG_REPOSITORY"));
+                    ui.end_row();
+                });
+            });
+
         new_state
     }
 }
@@ -1,6 +1,14 @@
 mod listening;
 mod main_menu;
+mod scope;
+mod settings_screen;
+mod stats;
 mod writing_screen;
 
+pub use listening::ListeningScreen;
 pub use main_menu::MainMenuScreen;
+use scope::{SCOPE_WIDTH, render_scope, render_spectrum};
+pub use settings_screen::SettingsScreen;
+use stats::{WEAKEST_COUNT, render_accuracy};
+pub use stats::{render_stats, render_timer};
 pub use writing_screen::WritingScreen;
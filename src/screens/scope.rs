@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use morset::signal::spectrum_peak;
+
+use crate::{audio::AudioManager, i18n::tr};
+
+/// Width of the side panel with the oscilloscope and the spectrum
+pub static SCOPE_WIDTH: f32 = 240.;
+
+/// Time between frames while a signal is shown, about 30 per second
+static SCOPE_FRAME: Duration = Duration::from_millis(33);
+
+/// Highest frequency shown by the spectrum, in Hz
+static SPECTRUM_MAX: f32 = 2000.;
+
+/// Level at the bottom of the spectrum, in dB below a full scale tone
+static SPECTRUM_FLOOR: f32 = -80.;
+
+/// Oscilloscope of the output tone: its envelope scrolling by, and the shape
+/// of its last few cycles. Both are flat lines while the key is up.
+pub fn render_scope(ui: &mut egui::Ui, audio: Option<&AudioManager>) {
+    ui.heading(tr("Scope"));
+    let Some(audio) = audio else {
+        ui.label(tr("No audio output"));
+        return;
+    };
+    let peaks = audio.scope_peaks();
+    let stroke = egui::Stroke::new(1.5, ui.visuals().strong_text_color());
+    let trace = |ui: &mut egui::Ui, values: &[f32], mirrored: bool| {
+        let (rect, _) =
+            ui.allocate_exact_size(egui::vec2(ui.available_width(), 100.), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2., ui.visuals().extreme_bg_color);
+        let half = rect.height() * 0.45;
+        let step = rect.width() / (values.len() - 1) as f32;
+        let points = |sign: f32| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    egui::pos2(
+                        rect.left() + i as f32 * step,
+                        rect.center().y - sign * value * half,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        painter.line(points(1.), stroke);
+        if mirrored {
+            painter.line(points(-1.), stroke);
+        }
+    };
+    ui.label(tr("Envelope"));
+    trace(ui, &peaks, true);
+    ui.label(tr("Waveform"));
+    trace(ui, &audio.scope_samples(), false);
+    // Keep scrolling until the last tone has gone by.
+    if peaks.iter().any(|&peak| peak > 0.) {
+        ui.ctx().request_repaint_after(SCOPE_FRAME);
+    }
+}
+
+/// Spectrum on a decibel scale with the sidetone frequency marked,
+/// reading out the pitch of the strongest signal.
+pub fn render_spectrum(ui: &mut egui::Ui, power: &[f32], bin_width: f32, sidetone: f32) {
+    ui.heading(tr("Spectrum"));
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), 120.), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2., ui.visuals().extreme_bg_color);
+    let x = |frequency: f32| rect.left() + frequency / SPECTRUM_MAX * rect.width();
+    let level = |power: f32| {
+        let db = 10. * power.max(f32::MIN_POSITIVE).log10();
+        ((db - SPECTRUM_FLOOR) / -SPECTRUM_FLOOR).clamp(0., 1.)
+    };
+    painter.vline(
+        x(sidetone),
+        rect.y_range(),
+        egui::Stroke::new(1., ui.visuals().selection.bg_fill),
+    );
+    let bins = ((SPECTRUM_MAX / bin_width) as usize + 1).min(power.len());
+    let points = power[..bins]
+        .iter()
+        .enumerate()
+        .map(|(i, &power)| {
+            egui::pos2(
+                x(i as f32 * bin_width),
+                rect.bottom() - level(power) * rect.height(),
+            )
+        })
+        .collect();
+    painter.line(
+        points,
+        egui::Stroke::new(1.5, ui.visuals().strong_text_color()),
+    );
+
+    let peak = spectrum_peak(&power[..bins], bin_width).filter(|&(_, power)| level(power) > 0.);
+    match peak {
+        Some((frequency, _)) => {
+            ui.label(format!(
+                "Peak {:.0} Hz, sidetone {:.0} Hz",
+                frequency, sidetone
+            ));
+            ui.ctx().request_repaint_after(SCOPE_FRAME);
+        }
+        None => {
+            ui.label(format!("Sidetone {:.0} Hz", sidetone));
+        }
+    }
+}
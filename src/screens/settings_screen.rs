@@ -0,0 +1,887 @@
+use egui::{self, RichText};
+use morset::consts::Alphabet;
+
+use crate::{
+    audio::Waveform,
+    callsign::Region,
+    custom_alphabet::CustomAlphabet,
+    files::{self, PendingFile},
+    i18n::{Language, tr},
+    inputs::KeyBindings,
+    keyer::KeyerMode,
+    settings::{
+        FREQUENCY_PRESETS, MAX_CUSTOM_FREQUENCIES, MAX_FREQUENCY, MAX_PAN, MAX_RAMP_TIME,
+        MAX_SESSION_GOAL, MAX_TRANSCRIPT_SIZE, MAX_UI_SCALE, MAX_VOLUME, MAX_WEIGHT, MAX_WPM,
+        MIN_FREQUENCY, MIN_PAN, MIN_RAMP_TIME, MIN_TRANSCRIPT_SIZE, MIN_UI_SCALE, MIN_VOLUME,
+        MIN_WEIGHT, MIN_WPM, Settings, Theme,
+    },
+    state::AppState,
+    text_source::TextSource,
+};
+
+pub struct SettingsScreen {
+    /// Alphabet file dialog that hasn't been closed yet
+    pending_alphabet: Option<PendingFile>,
+    alphabet_error: Option<String>,
+    /// Cached names of the MIDI input devices
+    #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+    midi_ports: Option<Vec<String>>,
+    /// Cached names of the serial ports
+    #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+    serial_ports: Option<Vec<String>>,
+    /// Asking whether the custom key bindings should be reset too
+    confirm_reset: bool,
+    /// The settings were reset since this was last asked
+    reset: bool,
+}
+
+impl SettingsScreen {
+    pub fn new() -> Self {
+        Self {
+            pending_alphabet: None,
+            alphabet_error: None,
+            #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+            midi_ports: None,
+            #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+            serial_ports: None,
+            confirm_reset: false,
+            reset: false,
+        }
+    }
+
+    /// Whether the settings were reset since the last call, so they can be saved right away.
+    pub fn take_reset(&mut self) -> bool {
+        std::mem::take(&mut self.reset)
+    }
+
+    /// Restore the defaults and apply the ones the screen shows right away.
+    fn reset_settings(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &mut Settings,
+        keep_bindings: bool,
+    ) {
+        settings.reset(keep_bindings);
+        settings.theme.apply(ctx);
+        settings.apply_scale(ctx);
+        settings.apply_characters();
+        self.alphabet_error = None;
+        self.confirm_reset = false;
+        self.reset = true;
+    }
+
+    /// Pick the serial port of the WinKeyer that sends what's keyed and typed.
+    #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+    fn winkeyer_settings(&mut self, ui: &mut egui::Ui, settings: &mut Settings) {
+        use crate::serial_key;
+
+        let ports = self.serial_ports.get_or_insert_with(serial_key::list_ports);
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("winkeyer_port")
+                .selected_text(settings.winkeyer_port.as_deref().unwrap_or("None"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.winkeyer_port, None, "None");
+                    for port in ports.iter() {
+                        ui.selectable_value(&mut settings.winkeyer_port, Some(port.clone()), port);
+                    }
+                });
+            if ui.button(tr("Refresh")).clicked() {
+                *ports = serial_key::list_ports();
+            }
+        })
+        .response
+        .on_hover_text(tr(
+            "Keys a transmitter with what's keyed and typed on the Writing screen",
+        ));
+    }
+
+    /// Pick the serial port and the lines that key the straight key and the paddles.
+    #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+    fn serial_settings(&mut self, ui: &mut egui::Ui, settings: &mut Settings) {
+        use crate::serial_key::{self, SerialLine};
+
+        let ports = self.serial_ports.get_or_insert_with(serial_key::list_ports);
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("serial_port")
+                    .selected_text(settings.serial_port.as_deref().unwrap_or("None"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut settings.serial_port, None, "None");
+                        for port in ports.iter() {
+                            ui.selectable_value(
+                                &mut settings.serial_port,
+                                Some(port.clone()),
+                                port,
+                            );
+                        }
+                    });
+                if ui.button(tr("Refresh")).clicked() {
+                    *ports = serial_key::list_ports();
+                }
+            })
+            .response
+            .on_hover_text(tr("DTR and RTS are held high to supply the key"));
+            let mapping = &mut settings.serial_mapping;
+            for (label, line) in [
+                ("Straight key:", &mut mapping.straight_key),
+                ("Dot paddle:", &mut mapping.dot_paddle),
+                ("Dash paddle:", &mut mapping.dash_paddle),
+            ] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    egui::ComboBox::from_id_salt(("serial_line", label))
+                        .selected_text(line.to_string())
+                        .show_ui(ui, |ui| {
+                            for option in SerialLine::ALL {
+                                ui.selectable_value(line, option, option.to_string());
+                            }
+                        });
+                });
+            }
+        });
+    }
+
+    /// Pick the MIDI device and the controls that key the straight key and the paddles.
+    #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+    fn midi_settings(&mut self, ui: &mut egui::Ui, settings: &mut Settings) {
+        use crate::midi::{self, MidiControl};
+
+        let ports = self.midi_ports.get_or_insert_with(midi::list_ports);
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("midi_device")
+                    .selected_text(settings.midi_device.as_deref().unwrap_or("None"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut settings.midi_device, None, "None");
+                        for port in ports.iter() {
+                            ui.selectable_value(
+                                &mut settings.midi_device,
+                                Some(port.clone()),
+                                port,
+                            );
+                        }
+                    });
+                if ui.button(tr("Refresh")).clicked() {
+                    *ports = midi::list_ports();
+                }
+            });
+            let mapping = &mut settings.midi_mapping;
+            for (label, control) in [
+                ("Straight key:", &mut mapping.straight_key),
+                ("Dot paddle:", &mut mapping.dot_paddle),
+                ("Dash paddle:", &mut mapping.dash_paddle),
+            ] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    egui::ComboBox::from_id_salt(label)
+                        .selected_text(control.kind_name())
+                        .show_ui(ui, |ui| {
+                            for kind in MidiControl::KINDS {
+                                let kind = kind.with_number(control.number());
+                                ui.selectable_value(control, kind, kind.kind_name());
+                            }
+                        });
+                    let mut number = control.number();
+                    if ui
+                        .add(egui::DragValue::new(&mut number).range(0..=127))
+                        .changed()
+                    {
+                        *control = control.with_number(number);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Read the alphabet file once the user has picked it,
+    /// falling back to the built-in characters if it can't be used.
+    fn poll_alphabet(&mut self, settings: &mut Settings) {
+        let Some(result) = self.pending_alphabet.as_ref().and_then(PendingFile::poll) else {
+            return;
+        };
+        self.pending_alphabet = None;
+        let Some(file) = result else {
+            return;
+        };
+        match CustomAlphabet::from_json(&file.name, &file.bytes) {
+            Ok(alphabet) => {
+                self.alphabet_error = None;
+                settings.custom_alphabet = Some(alphabet);
+            }
+            Err(err) => {
+                tracing::warn!("Failed to load {}: {}", file.name, err);
+                self.alphabet_error = Some(err);
+                settings.custom_alphabet = None;
+            }
+        }
+        settings.apply_characters();
+    }
+
+    /// Load, toggle and remove the custom alphabet.
+    fn custom_alphabet_settings(&mut self, ui: &mut egui::Ui, settings: &mut Settings) {
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        self.pending_alphabet.is_none(),
+                        egui::Button::new(tr("Load alphabet")),
+                    )
+                    .on_hover_text(tr(
+                        "JSON object mapping characters to sequences, like {\"A\": \".-\"}",
+                    ))
+                    .clicked()
+                {
+                    self.pending_alphabet = Some(files::open_file(ui.ctx(), "Alphabet", &["json"]));
+                }
+                let mut removed = false;
+                if let Some(custom) = &mut settings.custom_alphabet {
+                    ui.label(format!(
+                        "{} ({} characters)",
+                        custom.name,
+                        custom.characters.len()
+                    ));
+                    if ui
+                        .checkbox(&mut custom.replace, tr("Replace built-in"))
+                        .on_hover_text(tr("Use only these characters instead of adding them"))
+                        .changed()
+                    {
+                        settings.apply_characters();
+                    }
+                    removed = ui.button(tr("Remove")).clicked();
+                }
+                if removed {
+                    settings.custom_alphabet = None;
+                    settings.apply_characters();
+                }
+            });
+            if let Some(error) = &self.alphabet_error {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("{}, using the built-in characters", error),
+                );
+            }
+            if let Some(custom) = &settings.custom_alphabet {
+                for duplicate in custom.duplicates() {
+                    ui.colored_label(ui.visuals().warn_fg_color, duplicate);
+                }
+            }
+        });
+    }
+
+    /// Render the settings and return the new state if changed
+    pub fn render(&mut self, ctx: &egui::Context, settings: &mut Settings) -> Option<AppState> {
+        let mut new_state = None;
+        self.poll_alphabet(settings);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.add_space(100.0);
+                        ui.heading(RichText::new(tr("Settings")).size(36.0));
+                        ui.add_space(20.0);
+
+                        egui::Grid::new("settings")
+                            .num_columns(2)
+                            .spacing([20.0, 10.0])
+                            .show(ui, |ui| {
+                                ui.label(tr("Language:"));
+                                language_combo(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("WPM:")).on_hover_text(WPM_HINT);
+                                wpm_slider(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Quick speeds:"))
+                                    .on_hover_text(QUICK_SPEED_HINT);
+                                quick_speed_sliders(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Weight:")).on_hover_text(WEIGHT_HINT);
+                                weight_slider(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Frequency:")).on_hover_text(FREQUENCY_HINT);
+                                frequency_slider(ui, settings);
+                                ui.end_row();
+
+                                ui.label("");
+                                frequency_presets(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Volume:")).on_hover_text(VOLUME_HINT);
+                                volume_slider(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Waveform:")).on_hover_text(WAVEFORM_HINT);
+                                waveform_combo(ui, &mut settings.waveform);
+                                ui.end_row();
+
+                                ui.label(tr("Pan:"));
+                                pan_slider(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Rise time:")).on_hover_text(RAMP_HINT);
+                                slider_with_value(
+                                    ui,
+                                    &mut settings.rise_time,
+                                    MIN_RAMP_TIME..=MAX_RAMP_TIME,
+                                    " ms",
+                                    RAMP_HINT,
+                                );
+                                ui.end_row();
+
+                                ui.label(tr("Fall time:")).on_hover_text(RAMP_HINT);
+                                slider_with_value(
+                                    ui,
+                                    &mut settings.fall_time,
+                                    MIN_RAMP_TIME..=MAX_RAMP_TIME,
+                                    " ms",
+                                    RAMP_HINT,
+                                );
+                                ui.end_row();
+
+                                ui.label(tr("Sound:"));
+                                ui.checkbox(&mut settings.silent, tr("Silent mode"))
+                                    .on_hover_text(tr(
+                                        "Practice keying and decoding without any sound",
+                                    ));
+                                ui.end_row();
+
+                                ui.label(tr("Lamp:"));
+                                flash_settings(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Session goal:"));
+                                ui.add(
+                                    egui::Slider::new(
+                                        &mut settings.session_goal,
+                                        0..=MAX_SESSION_GOAL,
+                                    )
+                                    .custom_formatter(
+                                        |minutes, _| match minutes as u8 {
+                                            0 => "Off".to_string(),
+                                            minutes => format!("{} min", minutes),
+                                        },
+                                    ),
+                                )
+                                .on_hover_text(tr(
+                                    "Show the session summary after practicing this long",
+                                ));
+                                ui.end_row();
+
+                                #[cfg(target_arch = "wasm32")]
+                                {
+                                    ui.label(tr("Vibration:"));
+                                    ui.checkbox(
+                                        &mut settings.vibration,
+                                        tr("Vibrate with the tone"),
+                                    )
+                                    .on_hover_text(tr(
+                                        "Only works in mobile browsers that support it",
+                                    ));
+                                    ui.end_row();
+                                }
+
+                                ui.label(tr("Drill text:"));
+                                text_source_settings(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Callsigns from:"));
+                                region_checkboxes(ui, &mut settings.callsign_regions);
+                                ui.end_row();
+
+                                ui.label(tr("Keyer:")).on_hover_text(KEYER_HINT);
+                                keyer_combo(ui, &mut settings.keyer_mode);
+                                ui.end_row();
+
+                                #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+                                {
+                                    ui.label(tr("MIDI:"));
+                                    self.midi_settings(ui, settings);
+                                    ui.end_row();
+                                }
+
+                                #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+                                {
+                                    ui.label(tr("Serial key:"));
+                                    self.serial_settings(ui, settings);
+                                    ui.end_row();
+                                }
+
+                                #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+                                {
+                                    ui.label(tr("WinKeyer:"));
+                                    self.winkeyer_settings(ui, settings);
+                                    ui.end_row();
+                                }
+
+                                #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+                                {
+                                    ui.label(tr("Gamepad:"));
+                                    gamepad_settings(ui, settings);
+                                    ui.end_row();
+                                }
+
+                                ui.label(tr("Theme:"));
+                                theme_combo(ui, &mut settings.theme);
+                                ui.end_row();
+
+                                ui.label(tr("Interface scale:"));
+                                scale_slider(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Transcript size:"));
+                                slider_with_value(
+                                    ui,
+                                    &mut settings.transcript_size,
+                                    MIN_TRANSCRIPT_SIZE..=MAX_TRANSCRIPT_SIZE,
+                                    " pt",
+                                    "Font size of the text keyed on the Writing screen.",
+                                );
+                                ui.end_row();
+
+                                ui.label(tr("Tick indicator:"));
+                                ui.checkbox(&mut settings.high_contrast, tr("High contrast"));
+                                ui.end_row();
+
+                                ui.label(tr("Alphabet:"));
+                                alphabet_combo(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Characters:"));
+                                extended_checkbox(ui, settings);
+                                ui.end_row();
+
+                                ui.label(tr("Custom alphabet:"));
+                                self.custom_alphabet_settings(ui, settings);
+                                ui.end_row();
+                            });
+
+                        ui.add_space(20.0);
+                        if ui.button(tr("Reset to defaults")).clicked() {
+                            if settings.bindings == KeyBindings::default() {
+                                self.reset_settings(ctx, settings, false);
+                            } else {
+                                self.confirm_reset = true;
+                            }
+                        }
+                        ui.add_space(20.0);
+                        if ui.button(RichText::new(tr("Back")).size(24.0)).clicked()
+                            || ui.input(|i| i.key_pressed(egui::Key::Escape))
+                        {
+                            new_state = Some(AppState::MainMenu);
+                        }
+                    });
+                });
+        });
+
+        if self.confirm_reset {
+            egui::Window::new(tr("Reset to defaults"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+                .show(ctx, |ui| {
+                    ui.label(tr("Your key bindings were changed. Reset them too?"));
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Reset everything")).clicked() {
+                            self.reset_settings(ctx, settings, false);
+                        }
+                        if ui.button(tr("Keep key bindings")).clicked() {
+                            self.reset_settings(ctx, settings, true);
+                        }
+                        if ui.button(tr("Cancel")).clicked() {
+                            self.confirm_reset = false;
+                        }
+                    });
+                });
+        }
+
+        new_state
+    }
+}
+
+/// Pick the gamepad buttons that key the straight key and the paddles.
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+fn gamepad_settings(ui: &mut egui::Ui, settings: &mut Settings) {
+    use crate::gamepad::GamepadButton;
+
+    let mapping = &mut settings.gamepad_mapping;
+    ui.vertical(|ui| {
+        for (label, button) in [
+            ("Straight key:", &mut mapping.straight_key),
+            ("Dot paddle:", &mut mapping.dot_paddle),
+            ("Dash paddle:", &mut mapping.dash_paddle),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                egui::ComboBox::from_id_salt(("gamepad_button", label))
+                    .selected_text(button.to_string())
+                    .show_ui(ui, |ui| {
+                        for option in GamepadButton::ALL {
+                            ui.selectable_value(button, option, option.to_string());
+                        }
+                    });
+            });
+        }
+    });
+}
+
+/// Shown over the speed sliders
+const WPM_HINT: &str = "Words per minute by the PARIS standard: a word is as long as PARIS \
+    with the gap after it, 50 dits, so a dit lasts 1200 ms divided by the speed.";
+
+/// Shown over the quick speed sliders
+const QUICK_SPEED_HINT: &str =
+    "Speeds the QRS and QRQ keys switch to, to slow down or speed up in a hurry.";
+
+/// Shown over the weight slider
+const WEIGHT_HINT: &str = "Length of the dits and dahs against the gaps between them. \
+    50% is the standard, heavier sounds fuller and lighter crisper.";
+
+/// Shown over the frequency slider
+const FREQUENCY_HINT: &str = "Pitch of the tone. Most operators listen between 500 and 800 Hz.";
+
+/// Shown over the volume slider
+const VOLUME_HINT: &str = "Loudness of the tone, 0 is silent.";
+
+/// Shown over the waveform selector
+const WAVEFORM_HINT: &str = "Shape of the tone. Sine is the softest, \
+    the others are buzzier like the sidetone of some rigs.";
+
+/// Shown over the keyer selector
+const KEYER_HINT: &str = "How presses of the keys are turned into dits and dahs.";
+
+/// Shown over the ramp time sliders
+const RAMP_HINT: &str = "How softly the tone starts and stops. \
+    Very short ramps make the keying click, very long ones blur fast characters.";
+
+/// Slider with a box next to it for typing an exact value, both clamped to the range
+/// and explained by the hint, followed by the range.
+fn slider_with_value<T: egui::emath::Numeric + std::fmt::Display>(
+    ui: &mut egui::Ui,
+    value: &mut T,
+    range: std::ops::RangeInclusive<T>,
+    suffix: &str,
+    hint: &str,
+) -> bool {
+    let hint = format!(
+        "{}\n\nFrom {}{} to {}{}.",
+        hint,
+        range.start(),
+        suffix,
+        range.end(),
+        suffix
+    );
+    ui.horizontal(|ui| {
+        let slider = ui
+            .add(
+                egui::Slider::new(value, range.clone())
+                    .show_value(false)
+                    .suffix(suffix),
+            )
+            .on_hover_text(&hint);
+        let typed = ui
+            .add(egui::DragValue::new(value).range(range).suffix(suffix))
+            .on_hover_text(&hint);
+        slider.changed() || typed.changed()
+    })
+    .inner
+}
+
+pub fn wpm_slider(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    slider_with_value(ui, &mut settings.wpm, MIN_WPM..=MAX_WPM, " WPM", WPM_HINT)
+}
+
+/// Speeds the QRS and QRQ keys switch to.
+pub fn quick_speed_sliders(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    ui.vertical(|ui| {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(tr("QRS:"));
+            changed |= slider_with_value(
+                ui,
+                &mut settings.qrs_wpm,
+                MIN_WPM..=MAX_WPM,
+                " WPM",
+                QUICK_SPEED_HINT,
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("QRQ:"));
+            changed |= slider_with_value(
+                ui,
+                &mut settings.qrq_wpm,
+                MIN_WPM..=MAX_WPM,
+                " WPM",
+                QUICK_SPEED_HINT,
+            );
+        });
+        changed
+    })
+    .inner
+}
+
+/// Position of the tone between the left and right speaker, double click centers it.
+pub fn pan_slider(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    let slider = ui
+        .add(
+            egui::Slider::new(&mut settings.pan, MIN_PAN..=MAX_PAN).custom_formatter(|pan, _| {
+                match pan {
+                    pan if pan < -0.005 => format!("{:.0}% L", -pan * 100.),
+                    pan if pan > 0.005 => format!("{:.0}% R", pan * 100.),
+                    _ => "Center".to_string(),
+                }
+            }),
+        )
+        .on_hover_text(tr("Double click to center"));
+    if slider.double_clicked() {
+        settings.pan = 0.;
+        return true;
+    }
+    slider.changed()
+}
+
+pub fn weight_slider(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    ui.add(egui::Slider::new(&mut settings.weight, MIN_WEIGHT..=MAX_WEIGHT).suffix("%"))
+        .on_hover_text(WEIGHT_HINT)
+        .changed()
+}
+
+pub fn frequency_slider(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    slider_with_value(
+        ui,
+        &mut settings.frequency,
+        MIN_FREQUENCY..=MAX_FREQUENCY,
+        " Hz",
+        FREQUENCY_HINT,
+    )
+}
+
+/// Buttons for the preset and saved frequencies, right click removes a saved one.
+pub fn frequency_presets(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    let before = settings.frequency;
+    ui.horizontal(|ui| {
+        for preset in FREQUENCY_PRESETS {
+            if ui.small_button(format!("{} Hz", preset)).clicked() {
+                settings.frequency = preset;
+            }
+        }
+        let mut removed = None;
+        for (index, preset) in settings.custom_frequencies.iter().enumerate() {
+            let button = ui
+                .small_button(RichText::new(format!("{} Hz", preset)).strong())
+                .on_hover_text(tr("Right click to remove"));
+            if button.clicked() {
+                settings.frequency = *preset;
+            } else if button.secondary_clicked() {
+                removed = Some(index);
+            }
+        }
+        if let Some(index) = removed {
+            settings.custom_frequencies.remove(index);
+        }
+        let saved = FREQUENCY_PRESETS.contains(&settings.frequency)
+            || settings.custom_frequencies.contains(&settings.frequency);
+        let save = egui::Button::new(tr("Save")).small();
+        if ui
+            .add_enabled(
+                !saved && settings.custom_frequencies.len() < MAX_CUSTOM_FREQUENCIES,
+                save,
+            )
+            .on_hover_text(tr("Save the current frequency as a preset"))
+            .clicked()
+        {
+            settings.custom_frequencies.push(settings.frequency);
+        }
+    });
+    settings.frequency != before
+}
+
+pub fn volume_slider(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    slider_with_value(
+        ui,
+        &mut settings.volume,
+        MIN_VOLUME..=MAX_VOLUME,
+        "",
+        VOLUME_HINT,
+    )
+}
+
+/// Toggle the lamp and pick its colors while lit and dark.
+pub fn flash_settings(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    ui.horizontal(|ui| {
+        let mut changed = ui
+            .checkbox(&mut settings.flash, tr("Flash with the tone"))
+            .on_hover_text(tr("Mute the sound to practice with the lamp alone"))
+            .changed();
+        ui.add_enabled_ui(settings.flash, |ui| {
+            ui.label(tr("Lit:"));
+            changed |= egui::color_picker::color_edit_button_srgb(ui, &mut settings.flash_on_color)
+                .changed();
+            ui.label(tr("Dark:"));
+            changed |=
+                egui::color_picker::color_edit_button_srgb(ui, &mut settings.flash_off_color)
+                    .changed();
+        });
+        changed
+    })
+    .inner
+}
+
+/// Select where the drills take their text from.
+pub fn text_source_settings(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    let before = (settings.text_source, settings.unlocked_only);
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("text_source")
+            .selected_text(settings.text_source.to_string())
+            .show_ui(ui, |ui| {
+                for option in TextSource::ALL {
+                    ui.selectable_value(&mut settings.text_source, option, option.to_string());
+                }
+            });
+        ui.checkbox(&mut settings.unlocked_only, tr("Koch characters only"))
+            .on_hover_text(tr("Only use the characters unlocked in the Koch trainer"));
+    });
+    (settings.text_source, settings.unlocked_only) != before
+}
+
+/// Pick the regions drill callsigns come from, at least one staying selected.
+pub fn region_checkboxes(ui: &mut egui::Ui, regions: &mut Vec<Region>) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        for region in Region::ALL {
+            let mut selected = regions.contains(&region);
+            let only = selected && regions.len() == 1;
+            if ui
+                .add_enabled(
+                    !only,
+                    egui::Checkbox::new(&mut selected, region.to_string()),
+                )
+                .changed()
+            {
+                if selected {
+                    regions.push(region);
+                } else {
+                    regions.retain(|other| *other != region);
+                }
+                changed = true;
+            }
+        }
+    });
+    changed
+}
+
+pub fn waveform_combo(ui: &mut egui::Ui, waveform: &mut Waveform) -> bool {
+    let before = *waveform;
+    egui::ComboBox::from_id_salt("waveform")
+        .selected_text(waveform.to_string())
+        .show_ui(ui, |ui| {
+            for option in Waveform::ALL {
+                ui.selectable_value(waveform, option, option.to_string());
+            }
+        })
+        .response
+        .on_hover_text(WAVEFORM_HINT);
+    *waveform != before
+}
+
+pub fn keyer_combo(ui: &mut egui::Ui, mode: &mut KeyerMode) -> bool {
+    let before = *mode;
+    egui::ComboBox::from_id_salt("keyer_mode")
+        .selected_text(mode.to_string())
+        .show_ui(ui, |ui| {
+            for option in KeyerMode::ALL {
+                ui.selectable_value(mode, option, option.to_string())
+                    .on_hover_text(option.description());
+            }
+        })
+        .response
+        .on_hover_text(format!(
+            "{}\n\n{}: {}.",
+            KEYER_HINT,
+            mode,
+            mode.description()
+        ));
+    *mode != before
+}
+
+/// Select the alphabet, applying it right away.
+pub fn alphabet_combo(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    let before = settings.alphabet;
+    egui::ComboBox::from_id_salt("alphabet")
+        .selected_text(settings.alphabet.to_string())
+        .show_ui(ui, |ui| {
+            for option in Alphabet::ALL {
+                ui.selectable_value(&mut settings.alphabet, option, option.to_string());
+            }
+        });
+    if settings.alphabet != before {
+        settings.apply_characters();
+        return true;
+    }
+    false
+}
+
+/// Select the language of the interface, switching to its alphabet too.
+pub fn language_combo(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    let before = settings.language;
+    egui::ComboBox::from_id_salt("language")
+        .selected_text(settings.language.to_string())
+        .show_ui(ui, |ui| {
+            for option in Language::ALL {
+                ui.selectable_value(&mut settings.language, option, option.to_string());
+            }
+        });
+    if settings.language != before {
+        settings.alphabet = settings.language.alphabet();
+        settings.apply_language();
+        settings.apply_characters();
+        return true;
+    }
+    false
+}
+
+/// Toggle the accented letters, applying it right away.
+pub fn extended_checkbox(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    let changed = ui
+        .checkbox(&mut settings.extended_characters, tr("Accented letters"))
+        .changed();
+    if changed {
+        settings.apply_characters();
+    }
+    changed
+}
+
+/// Zoom of the interface, applied once the slider is let go so it doesn't move under the pointer.
+pub fn scale_slider(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    let slider = ui.add(
+        egui::Slider::new(&mut settings.ui_scale, MIN_UI_SCALE..=MAX_UI_SCALE)
+            .step_by(0.05)
+            .custom_formatter(|scale, _| format!("{:.0}%", scale * 100.)),
+    );
+    if slider.drag_stopped() || slider.changed() && !slider.dragged() {
+        settings.apply_scale(ui.ctx());
+        return true;
+    }
+    false
+}
+
+/// Select the theme, applying it right away.
+pub fn theme_combo(ui: &mut egui::Ui, theme: &mut Theme) -> bool {
+    let before = *theme;
+    egui::ComboBox::from_id_salt("theme")
+        .selected_text(theme.to_string())
+        .show_ui(ui, |ui| {
+            for option in Theme::ALL {
+                ui.selectable_value(theme, option, option.to_string());
+            }
+        });
+    if *theme != before {
+        theme.apply(ui.ctx());
+        return true;
+    }
+    false
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use egui::{self, RichText};
+
+use crate::{files, i18n::tr, records::CharacterAccuracy, stats::SessionStats};
+
+/// Number of weakest characters listed and drilled
+pub static WEAKEST_COUNT: usize = 6;
+
+/// Time spent on the practice screen, with the goal if there's one.
+pub fn render_timer(ui: &mut egui::Ui, stats: &SessionStats, goal: Option<Duration>) {
+    let text = match goal {
+        Some(goal) => format!("⏱ {} / {}", clock(stats.duration()), clock(goal)),
+        None => format!("⏱ {}", clock(stats.duration())),
+    };
+    let text = RichText::new(text).monospace();
+    let label = if stats.goal_reached() {
+        ui.label(text.strong())
+    } else {
+        ui.label(text)
+    };
+    label.on_hover_text(format!(
+        "Active {}, idle {}",
+        clock(stats.active()),
+        clock(stats.idle())
+    ));
+    // The clock ticks even while nothing else changes.
+    ui.ctx().request_repaint_after(Duration::from_secs(1));
+}
+
+/// Summary of the practice session with a button to export it.
+pub fn render_stats(ui: &mut egui::Ui, stats: &SessionStats) {
+    if stats.goal_reached() {
+        ui.label(RichText::new(tr("Session goal reached, nice work!")).strong());
+        ui.separator();
+    }
+    if stats.is_empty() {
+        ui.label(tr("Send or copy some characters to see how you did."));
+        return;
+    }
+    egui::Grid::new("session_stats")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label(tr("Duration"));
+            ui.label(clock(stats.duration()));
+            ui.end_row();
+            ui.label(tr("Active"));
+            ui.label(clock(stats.active()));
+            ui.end_row();
+            ui.label(tr("Idle"));
+            ui.label(clock(stats.idle()));
+            ui.end_row();
+            ui.label(tr("Characters sent"));
+            ui.label(stats.sent().to_string());
+            ui.end_row();
+            ui.label(tr("Characters received"));
+            ui.label(stats.received().to_string());
+            ui.end_row();
+            if let Some(wpm) = stats.effective_wpm() {
+                ui.label(tr("Effective speed"));
+                ui.label(format!("{:.1} WPM", wpm));
+                ui.end_row();
+            }
+            if let Some(accuracy) = stats.accuracy() {
+                ui.label(tr("Accuracy"));
+                ui.label(format!("{:.0}%", accuracy * 100.));
+                ui.end_row();
+            }
+            if let [(_, first), .., (_, last)] = stats.speeds() {
+                let speeds = stats.speeds().iter().map(|(_, wpm)| *wpm);
+                let lowest = speeds.clone().min().unwrap_or(*first);
+                let highest = speeds.max().unwrap_or(*first);
+                ui.label(tr("Speed"));
+                ui.label(format!(
+                    "{} → {} WPM (lowest {}, highest {})",
+                    first, last, lowest, highest
+                ));
+                ui.end_row();
+            }
+        });
+
+    let missed = stats.most_missed();
+    if !missed.is_empty() {
+        ui.separator();
+        ui.label(tr("Most missed characters:"));
+        for (ch, count) in missed {
+            ui.label(RichText::new(format!("{}: {}", ch, count)).monospace());
+        }
+    }
+    if ui.button(tr("Export CSV")).clicked() {
+        files::save_file(
+            ui.ctx(),
+            "morset-stats.csv",
+            "CSV",
+            &["csv"],
+            stats.to_csv().into_bytes(),
+        );
+    }
+}
+
+/// Long-term accuracy of every character, the more often it's missed the redder it is.
+/// Returns true when a drill of the weakest characters was asked for.
+pub fn render_accuracy(ui: &mut egui::Ui, accuracy: &mut CharacterAccuracy) -> bool {
+    if accuracy.is_empty() {
+        ui.label(tr(
+            "Copy or send some graded characters to see which ones need work.",
+        ));
+        return false;
+    }
+    ui.horizontal_wrapped(|ui| {
+        for (ch, score) in accuracy.scores() {
+            let miss = 1. - score.accuracy();
+            let text = RichText::new(format!(" {} ", ch))
+                .monospace()
+                .size(20.)
+                .background_color(ui.visuals().error_fg_color.gamma_multiply(miss));
+            ui.label(text).on_hover_text(format!(
+                "{} of {} right ({:.0}%)",
+                score.correct,
+                score.total,
+                score.accuracy() * 100.
+            ));
+        }
+    });
+
+    let weakest = accuracy.weakest(WEAKEST_COUNT);
+    let mut drill = false;
+    ui.separator();
+    if weakest.is_empty() {
+        ui.label(tr("No character stands out as weak yet."));
+    } else {
+        ui.label(tr("Weakest characters:"));
+        for (ch, score) in &weakest {
+            ui.label(RichText::new(format!("{}: {:.0}%", ch, score.accuracy() * 100.)).monospace());
+        }
+        drill = ui
+            .button(tr("Drill them"))
+            .on_hover_text(tr("Send code groups made of these characters"))
+            .clicked();
+    }
+    if ui.button(tr("Reset stats")).clicked() {
+        accuracy.clear();
+    }
+    drill
+}
+
+/// Minutes and seconds of the duration.
+fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -1,92 +1,294 @@
-use egui::{self, Key, RichText};
+use egui::{self, RichText};
 use std::time::Duration;
 
+use morset::{
+    consts::{self, Alphabet, ProSign},
+    decoder::{CustomProSign, Decoded, Decoder},
+    encoder::Timing,
+    utils::{CharacterTable, classify_press},
+};
+
+use super::settings_screen::{
+    alphabet_combo, extended_checkbox, frequency_presets, frequency_slider, keyer_combo,
+    text_source_settings, theme_combo, volume_slider, waveform_combo, weight_slider, wpm_slider,
+};
+use super::{SCOPE_WIDTH, render_scope, render_spectrum, render_stats, render_timer};
 use crate::{
-    audio::AudioManager,
-    inputs::InputStateExt,
+    audio::{AudioManager, SPECTRUM_BIN},
+    drill::{Mark, SendingDrill},
+    files::{self, PendingSave},
+    fist::{FistAnalysis, MAX_CHARACTER_GAP, RATIO_TOLERANCE, Stats},
+    i18n::tr,
+    inputs::{Action, ActionCategory, FIXED_SHORTCUTS, InputStateExt, KeyBindings, KeyEvents},
+    keyer::{IambicKey, IambicScheduler, KeyerEvent, KeyerMode},
+    player::MorsePlayer,
+    session::{ElementKind, SessionRecorder},
+    settings::{
+        MAX_CUSTOM_PROSIGNS, MAX_DASH_THRESHOLD, MAX_DECODE_TOLERANCE, MAX_HISTORY_LIMIT,
+        MAX_METRONOME_VOLUME, MIN_DASH_THRESHOLD, MIN_HISTORY_LIMIT, MIN_METRONOME_VOLUME,
+        MetronomeMode, Settings,
+    },
+    speed::SpeedMeter,
     state::AppState,
-    utils::{morse_to_char, wpm_to_dit_duration},
+    stats::SessionStats,
+    timeline::{TIMELINE_SPAN, Timeline},
+    transcript::{Transcript, TranscriptEntry},
 };
 
-pub static MAX_WPM: u8 = 40;
-pub static MIN_WPM: u8 = 1;
-
-pub static MAX_FREQUENCY: usize = 1200;
-pub static MIN_FREQUENCY: usize = 300;
-
-pub static MAX_VOLUME: usize = 100;
-pub static MIN_VOLUME: usize = 0;
+/// Text size of the speed next to the tick indicator, the blocks are sized after it
+const TICKS_TEXT_SIZE: f32 = 25.;
 
 pub struct WritingScreen {
     // Display state
-    text: String,
-    buffer: Vec<char>,
+    transcript: Transcript,
+    /// Asking whether to clear all text
+    confirm_clear: bool,
+    decoder: Decoder,
 
     // Private state
     elapsed: Duration,
     ticks: usize,
     pressed: bool,
+    /// How long the straight key has been held
+    press_duration: Duration,
+    /// How long the key has been up since the last element
+    silence: Duration,
+    /// Speed the user is actually keying at
+    speed: SpeedMeter,
+    /// Timing of every element and gap the user keys
+    fist: FistAnalysis,
+    /// Everything keyed since the screen was opened or cleared, for saving
+    recorder: SessionRecorder,
+    /// Start the saved transcript with the date, speed and keyer mode
+    transcript_header: bool,
+    /// Session or transcript being saved
+    pending_save: Option<PendingSave>,
+    /// How the last save went
+    save_message: Option<String>,
+    analysis_open: bool,
+    /// Recent tones, drawn when the timeline is shown
+    timeline: Timeline,
+    timeline_open: bool,
+    /// What was sent since the screen was opened, summed up when leaving
+    stats: SessionStats,
+    stats_open: bool,
+    /// Overlay listing every keyboard shortcut
+    help_open: bool,
+    /// Side panel drawing the output tone
+    scope_open: bool,
+    /// Side panel with the spectrum of the output
+    spectrum_open: bool,
+    /// Word the user is asked to send, when drilling
+    drill: Option<SendingDrill>,
+    /// Onscreen keys currently held: the straight key, the dot and the dash paddle
+    touch_held: [bool; 3],
+    keyer: IambicScheduler,
+    /// Plays abbreviations picked in the cheat sheet and replays the session
+    player: MorsePlayer,
+    /// The session is being replayed, keying is ignored until it's done
+    replaying: bool,
+    /// Line typed to be heard as morse, when that mode is on
+    hear_text: Option<String>,
+    /// Send every character as it's typed instead of the whole line on Enter
+    hear_live: bool,
+    /// Length of the typed line already sent in live mode
+    hear_queued: usize,
+    /// The typed line is being sent
+    hearing: bool,
+    /// A steady tone is held for tuning, keying is ignored until it's turned off
+    tuning: bool,
     cheat_sheet_open: bool,
+    /// Entries of the cheat sheet with the character table they were built for
+    cheat_sheet: (CharacterTable, Vec<CheatSheetEntry>),
+    /// Character or pattern the cheat sheet is filtered by
+    cheat_sheet_search: String,
+    /// Last character or prosign decoded, highlighted in the cheat sheet
+    last_decoded: Option<Decoded>,
+    /// Tag and sequence of the custom prosign being added
+    new_prosign: (String, String),
+    /// Cached names of the available output devices
+    output_devices: Option<Vec<String>>,
+    /// Warning shown when the selected output device could not be used
+    device_warning: Option<String>,
+    /// Keying from the MIDI device selected in the settings
+    #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+    midi: crate::midi::MidiKeying,
+    /// Keying from the serial port selected in the settings
+    #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+    serial: crate::serial_key::SerialKeying,
+    /// Keying from the buttons of a gamepad
+    #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+    gamepad: crate::gamepad::GamepadKeying,
+    /// WinKeyer sending the keying and typed text to a transmitter
+    #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+    winkeyer: crate::winkeyer::WinKeyer,
+    /// Keying sent to and heard from a partner through a relay
+    #[cfg(feature = "network")]
+    link: crate::network::Link,
+    #[cfg(feature = "network")]
+    link_open: bool,
+    /// Practice is held behind the pause menu, nothing is keyed and time stands still
+    paused: bool,
+    /// Action waiting for a key to be bound to it
+    rebinding: Option<Action>,
+    /// Warning shown when a new binding conflicts with an existing one
+    binding_conflict: Option<String>,
 
-    /// User settings
-    frequency: usize,
-    volume: usize,
-    wpm: u8,
+    /// Dit length at the configured speed
     dit_duration: Duration,
 }
 
 impl WritingScreen {
-    pub fn new() -> Self {
-        let wpm = 10;
-        let dit_duration = wpm_to_dit_duration(wpm);
-
+    pub fn new(settings: &Settings) -> Self {
+        let mut decoder = Decoder::new(settings.decode_tolerance, settings.characters.clone());
+        decoder.set_custom_prosigns(settings.custom_prosigns.clone());
         Self {
-            text: String::new(),
-            buffer: Vec::new(),
+            transcript: Transcript::new(settings.history_limit),
+            confirm_clear: false,
+            decoder,
             elapsed: Duration::ZERO,
             ticks: 0,
-            wpm,
-            frequency: 550,
             pressed: false,
+            press_duration: Duration::ZERO,
+            silence: Duration::ZERO,
+            speed: SpeedMeter::new(),
+            fist: FistAnalysis::new(),
+            recorder: SessionRecorder::new(),
+            transcript_header: true,
+            pending_save: None,
+            save_message: None,
+            analysis_open: false,
+            timeline: Timeline::new(),
+            timeline_open: false,
+            stats: SessionStats::new(),
+            stats_open: false,
+            help_open: false,
+            scope_open: false,
+            spectrum_open: false,
+            drill: None,
+            touch_held: [false; 3],
+            keyer: IambicScheduler::new(settings.keyer_mode),
+            player: MorsePlayer::new(),
+            replaying: false,
+            tuning: false,
+            hear_text: None,
+            hear_live: false,
+            hear_queued: 0,
+            hearing: false,
             cheat_sheet_open: true,
-            volume: 20,
-            dit_duration,
+            cheat_sheet: (
+                settings.characters.clone(),
+                cheat_sheet_entries(&settings.characters, &settings.custom_prosigns),
+            ),
+            cheat_sheet_search: String::new(),
+            last_decoded: None,
+            new_prosign: (String::new(), String::new()),
+            output_devices: None,
+            device_warning: None,
+            #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+            midi: crate::midi::MidiKeying::new(),
+            #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+            serial: crate::serial_key::SerialKeying::new(),
+            #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+            gamepad: crate::gamepad::GamepadKeying::new(),
+            #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+            winkeyer: crate::winkeyer::WinKeyer::new(),
+            #[cfg(feature = "network")]
+            link: crate::network::Link::new(settings.characters.clone()),
+            #[cfg(feature = "network")]
+            link_open: false,
+            paused: false,
+            rebinding: None,
+            binding_conflict: None,
+            dit_duration: settings.dit_duration(),
         }
     }
 
+    pub fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+
+    /// Stats of the session, if anything was sent.
+    pub fn take_stats(&mut self) -> Option<SessionStats> {
+        let stats = std::mem::replace(&mut self.stats, SessionStats::new());
+        (!stats.is_empty()).then_some(stats)
+    }
+
     /// Reset the timer and tick count.
     fn reset_timer(&mut self) {
         self.elapsed = Duration::ZERO;
         self.ticks = 0;
+        self.silence = Duration::ZERO;
     }
 
-    /// Progress the timer and see if value has been updated.
-    fn tick(&mut self, delta: Duration) -> Option<usize> {
+    /// Dits counted by the tick indicator after the key is pressed or released,
+    /// up to the silence that completes a word.
+    fn max_ticks(&self) -> usize {
+        self.decoder.word_gap().ceil() as usize
+    }
+
+    /// Progress the timer, counting the dits passed for the tick indicator.
+    fn tick(&mut self, delta: Duration) {
         self.elapsed += delta;
 
         while self.elapsed >= self.dit_duration {
             self.elapsed -= self.dit_duration;
-            if self.ticks < 7 {
+            if self.ticks < self.max_ticks() {
                 self.ticks += 1;
             }
         }
-
-        if self.ticks > 0 {
-            Some(self.ticks)
-        } else {
-            None
-        }
     }
 
-    /// This function just verifies that all values are within bounds.
-    fn normalize_values(&mut self) {
-        self.wpm = self.wpm.clamp(MIN_WPM, MAX_WPM);
-        self.frequency = self.frequency.clamp(MIN_FREQUENCY, MAX_FREQUENCY);
-        self.volume = self.volume.clamp(MIN_VOLUME, MAX_VOLUME);
-        let dit_duration = wpm_to_dit_duration(self.wpm);
+    /// Keep the settings within bounds and follow a change of speed.
+    ///
+    /// The time into the current dit, the press being keyed and the silence since
+    /// the last element are scaled to the new speed, so an element, character or gap
+    /// being keyed keeps its progress in dits instead of starting over.
+    fn normalize_values(&mut self, settings: &mut Settings) {
+        settings.normalize();
+        let dit_duration = settings.dit_duration();
         if self.dit_duration != dit_duration {
+            let scale = dit_duration.as_secs_f32() / self.dit_duration.as_secs_f32();
+            self.elapsed = self.elapsed.mul_f32(scale).min(dit_duration);
+            self.silence = self.silence.mul_f32(scale);
+            self.press_duration = self.press_duration.mul_f32(scale);
             self.dit_duration = dit_duration;
-            self.reset_timer();
+        }
+    }
+
+    /// Re-read the list of output devices and fall back to the
+    /// default one if the selected device has disappeared.
+    fn refresh_output_devices(
+        &mut self,
+        settings: &mut Settings,
+        audio: &mut Option<AudioManager>,
+    ) {
+        let devices = AudioManager::list_output_devices();
+        if let Some(selected) = &settings.output_device
+            && !devices.contains(selected)
+        {
+            self.device_warning = Some(format!(
+                "Output device \"{}\" is no longer available, using the default device",
+                selected
+            ));
+            settings.output_device = None;
+            self.apply_output_device(settings, audio);
+        }
+        self.output_devices = Some(devices);
+    }
+
+    /// Make the audio manager use the selected output device.
+    fn apply_output_device(&mut self, settings: &mut Settings, audio: &mut Option<AudioManager>) {
+        let Some(audio) = audio else {
+            return;
+        };
+        if let Err(err) = audio.set_device(settings.output_device.as_deref()) {
+            tracing::warn!("{}", err);
+            self.device_warning = Some(format!("{}, using the default device", err));
+            settings.output_device = None;
+            if let Err(err) = audio.set_device(None) {
+                tracing::error!("{}", err);
+            }
         }
     }
 
@@ -96,222 +298,2234 @@ impl WritingScreen {
         ctx: &egui::Context,
         delta: Duration,
         audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
     ) -> Option<AppState> {
         let mut new_state = None;
 
-        // Handle timing
-        self.handle_timers(delta);
+        // Handle input, only keys meant for keying are passed on.
+        // The input is locked while it's read, so the focus is looked up before.
+        let typing = ctx.wants_keyboard_input();
+        let keys = ctx.input(|i| {
+            // While waiting for a new binding, the next pressed key
+            // is captured instead of triggering any action.
+            if let Some(action) = self.rebinding {
+                if let Some(key) = i.any_key_just_pressed() {
+                    self.rebinding = None;
+                    self.binding_conflict =
+                        settings.bindings.bind(action, key).err().map(|other| {
+                            format!(
+                                "{} is already bound to \"{}\"",
+                                key.name(),
+                                other.description()
+                            )
+                        });
+                }
+                return KeyEvents::default();
+            }
 
-        // Handle input
-        ctx.input(|i| {
-            if i.key_pressed(Key::Escape) {
-                new_state = Some(AppState::MainMenu);
-            } else if i.key_pressed(Key::Backspace) {
-                self.text.clear();
-                self.buffer.clear();
-            } else if i.key_pressed(Key::F1) {
-                self.wpm = self.wpm.saturating_sub(1);
-                self.normalize_values();
-            } else if i.key_pressed(Key::F2) {
-                self.wpm = self.wpm.saturating_add(1);
-                self.normalize_values();
-            } else if i.key_pressed(Key::F3) {
-                self.frequency = self.frequency.saturating_sub(50);
-                if let Some(audio) = audio {
-                    audio.set_frequency(self.frequency as f32);
+            // Typing into a text field doesn't trigger any action.
+            if typing {
+                return KeyEvents::default();
+            }
+
+            // Undo and redo of the text, the keys don't key anything.
+            if i.modifiers.command && !self.paused {
+                if i.key_pressed(egui::Key::Y) || i.modifiers.shift && i.key_pressed(egui::Key::Z) {
+                    self.transcript.redo();
+                    return KeyEvents::default();
+                } else if i.key_pressed(egui::Key::Z) {
+                    self.transcript.undo();
+                    return KeyEvents::default();
                 }
-            } else if i.key_pressed(Key::F4) {
-                self.frequency = self.frequency.saturating_add(50);
-                if let Some(audio) = audio {
-                    audio.set_frequency(self.frequency as f32);
+            }
+
+            let bindings = settings.bindings.clone();
+            // Escape dismisses the shortcuts overlay before it pauses.
+            if self.help_open && i.key_pressed(egui::Key::Escape) {
+                self.help_open = false;
+                return KeyEvents::default();
+            }
+            if i.key_pressed(bindings.key(Action::ToggleHelp)) {
+                self.help_open = !self.help_open;
+            }
+            if i.key_pressed(bindings.key(Action::ReturnToMenu)) {
+                self.set_paused(!self.paused, audio);
+            }
+            if self.paused {
+                return KeyEvents::default();
+            }
+            if i.key_pressed(bindings.key(Action::Backspace)) {
+                // Drop the character being keyed first, then decoded ones.
+                if self.decoder.is_empty() {
+                    self.transcript.pop();
+                    if let Some(drill) = &mut self.drill {
+                        drill.pop();
+                    }
+                } else {
+                    self.decoder.clear();
+                    self.fist.discard_character();
                 }
-            } else if i.key_pressed(Key::F5) {
-                self.volume = self.volume.saturating_sub(5);
+            } else if i.key_pressed(bindings.key(Action::ClearAll)) {
+                self.request_clear();
+            } else if i.key_pressed(bindings.key(Action::DecreaseWpm)) {
+                settings.wpm = settings.wpm.saturating_sub(1);
+                self.normalize_values(settings);
+            } else if i.key_pressed(bindings.key(Action::IncreaseWpm)) {
+                settings.wpm = settings.wpm.saturating_add(1);
+                self.normalize_values(settings);
+            } else if i.key_pressed(bindings.key(Action::SlowSpeed)) {
+                settings.wpm = settings.qrs_wpm;
+                self.normalize_values(settings);
+            } else if i.key_pressed(bindings.key(Action::FastSpeed)) {
+                settings.wpm = settings.qrq_wpm;
+                self.normalize_values(settings);
+            } else if i.key_pressed(bindings.key(Action::DecreaseFrequency)) {
+                settings.frequency = settings.frequency.saturating_sub(50);
                 if let Some(audio) = audio {
-                    audio.set_volume(self.volume as f32 * 0.01);
+                    audio.set_frequency(settings.frequency as f32);
                 }
-            } else if i.key_pressed(Key::F6) {
-                self.volume = self.volume.saturating_add(5);
+            } else if i.key_pressed(bindings.key(Action::IncreaseFrequency)) {
+                settings.frequency = settings.frequency.saturating_add(50);
                 if let Some(audio) = audio {
-                    audio.set_volume(self.volume as f32 * 0.01);
+                    audio.set_frequency(settings.frequency as f32);
                 }
-            } else if i.key_pressed(Key::C) {
-                self.cheat_sheet_open = !self.cheat_sheet_open;
-            }
-
-            // Handle space key for morse code
-            if i.key_just_pressed(Key::Space) {
-                tracing::debug!("Start emitting wave");
-                self.pressed = true;
-                self.reset_timer();
+            } else if i.key_pressed(bindings.key(Action::DecreaseVolume)) {
+                settings.volume = settings.volume.saturating_sub(5);
                 if let Some(audio) = audio {
-                    audio.play();
+                    audio.set_volume(settings.gain());
                 }
-            } else if i.key_released(Key::Space) {
-                tracing::debug!("Stop emitting wave");
-                self.pressed = false;
+            } else if i.key_pressed(bindings.key(Action::IncreaseVolume)) {
+                settings.volume = settings.volume.saturating_add(5);
                 if let Some(audio) = audio {
-                    audio.pause();
-                }
-                // Add dot or dash based on how long it was pressed
-                if self.ticks <= 1 {
-                    self.buffer.push('.');
-                } else {
-                    self.buffer.push('-');
+                    audio.set_volume(settings.gain());
                 }
-                self.reset_timer();
+            } else if i.key_pressed(bindings.key(Action::ToggleCheatSheet)) {
+                self.cheat_sheet_open = !self.cheat_sheet_open;
+            } else if i.key_pressed(bindings.key(Action::ToggleMute))
+                && let Some(audio) = audio
+            {
+                audio.set_muted(!audio.is_muted());
             }
+            KeyEvents::from_input(i)
         });
+        // MIDI, serial and gamepad input key through the keys bound to the actions it's mapped to.
+        #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+        let keys = {
+            let mut keys = keys;
+            self.midi.select(ctx, settings.midi_device.as_deref());
+            self.midi
+                .key_events(&settings.midi_mapping, &settings.bindings, &mut keys);
+            keys
+        };
+        #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+        let keys = {
+            let mut keys = keys;
+            self.serial.select(ctx, settings.serial_port.as_deref());
+            self.serial
+                .key_events(&settings.serial_mapping, &settings.bindings, &mut keys);
+            keys
+        };
+        #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+        let keys = {
+            let mut keys = keys;
+            self.gamepad.key_events(
+                ctx,
+                &settings.gamepad_mapping,
+                &settings.bindings,
+                &mut keys,
+            );
+            keys
+        };
+        #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+        {
+            self.winkeyer.select(settings.winkeyer_port.as_deref());
+            self.winkeyer.set_speed(settings.wpm, settings.weight);
+        }
+        if !self.paused {
+            if !keys.pressed.is_empty() {
+                self.stats.record_activity();
+            }
+            self.step(delta, &keys, settings, audio);
+        }
+        if self.stats.reach_goal(settings.session_goal()) {
+            self.stats_open = true;
+        }
 
         // Render UI
-        self.render_ui(ctx, audio);
+        self.render_ui(ctx, audio, settings);
+        if self.paused {
+            if let Some(state) = self.render_pause_menu(ctx, audio) {
+                new_state = Some(state);
+            }
+        } else {
+            self.request_repaint(ctx, settings);
+        }
+        for (ch, correct) in self.stats.take_answers() {
+            settings.accuracy.record(ch, correct);
+        }
+        if let Some(audio) = audio {
+            let interval = (!self.paused)
+                .then(|| settings.metronome_interval())
+                .flatten();
+            audio.set_metronome(interval, settings.metronome_level());
+            audio.set_scope(self.scope_open || self.spectrum_open);
+        }
 
+        if new_state.is_some() {
+            self.player.stop(audio);
+            self.tuning = false;
+            #[cfg(feature = "network")]
+            self.link.silence(audio);
+            // Other screens don't keep time or draw the tone.
+            if let Some(audio) = audio {
+                audio.set_metronome(None, 0.);
+                audio.set_scope(false);
+            }
+        }
         new_state
     }
 
-    fn handle_timers(&mut self, delta: Duration) {
-        let Some(tick) = self.tick(delta) else {
+    /// Hold practice behind the pause menu, silencing everything, or continue it.
+    fn set_paused(&mut self, paused: bool, audio: &mut Option<AudioManager>) {
+        self.paused = paused;
+        if paused {
+            self.player.pause(audio);
+            self.reset_keying(audio);
+            #[cfg(feature = "network")]
+            self.link.silence(audio);
+        } else {
+            self.player.resume(audio);
+        }
+    }
+
+    /// Window offering to resume, change the settings or leave.
+    fn render_pause_menu(
+        &mut self,
+        ctx: &egui::Context,
+        audio: &mut Option<AudioManager>,
+    ) -> Option<AppState> {
+        let mut new_state = None;
+        egui::Window::new(tr("Paused"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+            .show(ctx, |ui| {
+                ui.vertical_centered_justified(|ui| {
+                    if ui.button(RichText::new(tr("Resume")).size(20.)).clicked() {
+                        self.set_paused(false, audio);
+                    }
+                    if ui.button(RichText::new(tr("Settings")).size(20.)).clicked() {
+                        new_state = Some(AppState::Settings);
+                    }
+                    if ui
+                        .button(RichText::new(tr("Main Menu")).size(20.))
+                        .clicked()
+                    {
+                        new_state = Some(AppState::MainMenu);
+                    }
+                });
+            });
+        new_state
+    }
+
+    /// Pick up the settings changed on the Settings screen while paused.
+    pub fn apply_settings(&mut self, settings: &mut Settings) {
+        self.normalize_values(settings);
+        self.keyer.set_mode(settings.keyer_mode);
+        self.decoder.set_tolerance(settings.decode_tolerance);
+        self.decoder.set_characters(settings.characters.clone());
+        self.decoder
+            .set_custom_prosigns(settings.custom_prosigns.clone());
+        #[cfg(feature = "network")]
+        self.link.set_characters(settings.characters.clone());
+        self.transcript.set_limit(settings.history_limit);
+    }
+
+    /// Advance the timing by `delta` and key with the given input.
+    ///
+    /// Nothing here depends on egui or a clock, so a scripted sequence of
+    /// deltas and key events plays out the same every time.
+    fn step(
+        &mut self,
+        delta: Duration,
+        input: &impl InputStateExt,
+        settings: &Settings,
+        audio: &mut Option<AudioManager>,
+    ) {
+        self.handle_timers(delta);
+        self.keyer
+            .update(delta, self.dit_duration, settings.weight_ratio());
+        self.player.update(delta, audio);
+        self.replaying &= self.player.is_playing();
+        self.hearing &= self.player.is_playing();
+        #[cfg(feature = "network")]
+        self.link.update(delta, settings.frequency, audio);
+
+        if !self.replaying && !self.tuning {
+            let bindings = &settings.bindings;
+            if settings.keyer_mode == KeyerMode::Straight {
+                self.handle_straight_key(input, bindings.key(Action::StraightKey), settings, audio);
+            } else {
+                let (dot, dash) = if settings.swap_paddles {
+                    (IambicKey::Dash, IambicKey::Dot)
+                } else {
+                    (IambicKey::Dot, IambicKey::Dash)
+                };
+                for (action, paddle) in [(Action::DotPaddle, dot), (Action::DashPaddle, dash)] {
+                    let key = bindings.key(action);
+                    let (pressed, released) =
+                        (input.key_just_pressed(key), input.key_just_released(key));
+                    // Same order as the straight key when both happen in one frame.
+                    let held = self.keyer.held(paddle);
+                    if released && held {
+                        self.keyer.release_key(paddle);
+                    }
+                    if pressed {
+                        self.keyer.press_key(paddle);
+                    }
+                    if released && !held {
+                        self.keyer.release_key(paddle);
+                    }
+                }
+            }
+        }
+        self.handle_keyer_events(audio);
+    }
+
+    fn handle_straight_key(
+        &mut self,
+        i: &impl InputStateExt,
+        key: egui::Key,
+        settings: &Settings,
+        audio: &mut Option<AudioManager>,
+    ) {
+        let (pressed, released) = (i.key_just_pressed(key), i.key_just_released(key));
+        // A press and a release in one frame are a tap if the key was up,
+        // or letting go and pressing again if it was down.
+        let held = self.pressed;
+        if released && held {
+            self.release_straight_key(settings, audio);
+        }
+        if pressed {
+            self.press_straight_key(audio);
+        }
+        if released && !held {
+            self.release_straight_key(settings, audio);
+        }
+    }
+
+    /// Wake up in time for the next tick, element or tone change.
+    /// Without any, the screen idles until the next input.
+    fn request_repaint(&self, ctx: &egui::Context, settings: &Settings) {
+        let next_tick =
+            (self.ticks < self.max_ticks()).then(|| self.dit_duration.saturating_sub(self.elapsed));
+        let next_change = [
+            next_tick,
+            self.next_gap(),
+            self.keyer
+                .next_change(self.dit_duration, settings.weight_ratio()),
+            self.player.next_change(),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        if self.timeline_open && self.timeline.is_moving() {
+            // The timeline scrolls with every frame.
+            ctx.request_repaint();
+        } else if let Some(after) = next_change {
+            ctx.request_repaint_after(after);
+        }
+    }
+
+    /// Time left until the silence completes the character or the word, if either is pending.
+    fn next_gap(&self) -> Option<Duration> {
+        if self.pressed {
+            return None;
+        }
+        let character = (!self.decoder.is_empty()).then_some(self.decoder.character_gap());
+        let word = self.word_pending().then_some(self.decoder.word_gap());
+        [character, word]
+            .into_iter()
+            .flatten()
+            .map(|units| self.dit_duration.mul_f32(units))
+            .filter(|gap| *gap > self.silence)
+            .map(|gap| gap - self.silence)
+            .min()
+    }
+
+    /// Whether the tone is on, keyed by the user, held for tuning or sent by the player.
+    ///
+    /// Doesn't depend on the audio, so the lamp and the vibration follow it in silent mode too.
+    pub fn is_tone_on(&self) -> bool {
+        self.pressed || self.tuning || self.player.is_tone_on()
+    }
+
+    /// Whether the text ends with a word that a long enough silence would complete.
+    fn word_pending(&self) -> bool {
+        self.transcript
+            .last_char()
+            .is_some_and(|ch| !ch.is_whitespace())
+    }
+
+    fn press_straight_key(&mut self, audio: &mut Option<AudioManager>) {
+        if self.pressed {
+            return;
+        }
+        tracing::debug!("Start emitting wave");
+        self.player.stop(audio);
+        self.start_element();
+        if let Some(audio) = audio {
+            audio.play();
+        }
+    }
+
+    fn release_straight_key(&mut self, settings: &Settings, audio: &mut Option<AudioManager>) {
+        if !self.pressed {
             return;
+        }
+        tracing::debug!("Stop emitting wave");
+        if let Some(audio) = audio {
+            audio.pause();
+        }
+        // Add dot or dash based on how long it was pressed
+        self.end_element(classify_press(
+            self.press_duration,
+            self.dit_duration,
+            settings.dash_threshold,
+        ));
+    }
+
+    /// Gate the tone and collect elements sent by the iambic keyer.
+    fn handle_keyer_events(&mut self, audio: &mut Option<AudioManager>) {
+        while let Some(event) = self.keyer.next_event() {
+            match event {
+                KeyerEvent::ToneOn => {
+                    self.player.stop(audio);
+                    self.start_element();
+                    if let Some(audio) = audio {
+                        audio.play();
+                    }
+                }
+                KeyerEvent::ToneOff(key) => {
+                    if let Some(audio) = audio {
+                        audio.pause();
+                    }
+                    self.end_element(key.symbol());
+                }
+            }
+        }
+    }
+
+    /// Mark the key as down, measuring the gap that preceded it.
+    fn start_element(&mut self) {
+        // Gaps between characters and words are left to the operator,
+        // only the gaps inside a character have a fixed length.
+        if !self.decoder.is_empty() {
+            self.speed.record(self.silence, 1);
+            self.fist.record_element_gap(self.silence);
+        } else {
+            let word_gap = self.silence >= self.dit_duration.mul_f32(self.decoder.word_gap());
+            if !word_gap && self.word_pending() {
+                self.fist.record_character_gap(self.silence);
+            }
+            if !self.recorder.is_empty() {
+                let gap = if !word_gap {
+                    ElementKind::CharacterGap
+                } else {
+                    ElementKind::WordGap
+                };
+                self.recorder.record(gap, self.silence);
+            }
+        }
+        self.pressed = true;
+        self.press_duration = Duration::ZERO;
+        self.timeline.key_down();
+        #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+        self.winkeyer.key(true);
+        #[cfg(feature = "network")]
+        self.link.key_down();
+        self.reset_timer();
+    }
+
+    /// Mark the key as up and add the element it produced.
+    fn end_element(&mut self, element: char) {
+        self.pressed = false;
+        #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+        self.winkeyer.key(false);
+        #[cfg(feature = "network")]
+        self.link.key_up(element, self.dit_duration);
+        self.timeline.key_up(element);
+        let units = if element == '.' { 1 } else { 3 };
+        self.speed.record(self.press_duration, units);
+        self.fist.record_element(element, self.press_duration);
+        let kind = if element == '.' {
+            ElementKind::Dit
+        } else {
+            ElementKind::Dash
         };
+        self.recorder.record(kind, self.press_duration);
+        self.decoder.on_element(element == '-');
+        self.reset_timer();
+    }
+
+    /// Switch keyer mode, cutting off anything that was being sent.
+    fn set_keyer_mode(&mut self, mode: KeyerMode, audio: &mut Option<AudioManager>) {
+        self.keyer.set_mode(mode);
+        self.reset_keying(audio);
+    }
+
+    /// Hold a steady tone to adjust the frequency and volume against, or release it.
+    fn set_tuning(&mut self, tuning: bool, audio: &mut Option<AudioManager>) {
+        self.tuning = tuning;
+        self.player.stop(audio);
+        self.replaying = false;
+        self.reset_keying(audio);
+        if tuning && let Some(audio) = audio {
+            audio.play();
+        }
+    }
+
+    /// Drop the element being keyed and silence the tone.
+    fn reset_keying(&mut self, audio: &mut Option<AudioManager>) {
+        self.keyer.reset();
+        self.fist.discard_character();
+        self.timeline.discard_tone();
+        #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+        if self.pressed {
+            self.winkeyer.key(false);
+        }
+        self.pressed = false;
+        self.reset_timer();
+        if let Some(audio) = audio {
+            audio.pause();
+        }
+    }
+
+    fn handle_timers(&mut self, delta: Duration) {
+        if self.pressed {
+            self.press_duration += delta;
+        } else {
+            self.silence += delta;
+        }
+
+        self.tick(delta);
 
         // If the key is being pressed, do not do anything.
         if self.pressed {
             return;
         }
 
-        if tick == 3 {
-            if let Some(ch) = morse_to_char(&self.buffer.iter().collect::<String>()) {
-                self.text.push(ch);
+        // Characters and words are complete once the key has been silent long enough,
+        // measured in dits so the boundaries follow the speed.
+        self.decoder
+            .on_gap(self.silence.as_secs_f32() / self.dit_duration.as_secs_f32());
+        while let Some(decoded) = self.decoder.take_decoded() {
+            self.apply_decoded(decoded);
+        }
+    }
+
+    /// Clear all text, asking first if there's any to lose.
+    fn request_clear(&mut self) {
+        if !self.transcript.is_empty() {
+            self.confirm_clear = true;
+        } else {
+            self.clear_all();
+        }
+    }
+
+    fn clear_all(&mut self) {
+        self.transcript.clear();
+        self.decoder.clear();
+        self.speed.clear();
+        self.recorder.clear();
+        self.timeline.clear();
+        self.last_decoded = None;
+        if let Some(drill) = &mut self.drill {
+            drill.clear();
+        }
+    }
+
+    /// Line to type text into and hear it sent, the character being sent highlighted.
+    fn render_type_to_hear(&mut self, ui: &mut egui::Ui, settings: &Settings) {
+        let Some(text) = &mut self.hear_text else {
+            return;
+        };
+        let mut send = false;
+        ui.horizontal(|ui| {
+            let line = ui.add(
+                egui::TextEdit::singleline(text)
+                    .hint_text(tr("Type text to hear it"))
+                    .desired_width(400.),
+            );
+            if !self.hear_live && line.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))
+            {
+                send = true;
+                line.request_focus();
+            }
+            ui.checkbox(&mut self.hear_live, tr("Live"))
+                .on_hover_text(tr("Send every character as it's typed"));
+            if !self.hear_live && ui.button(tr("Play")).clicked() {
+                send = true;
+            }
+        });
+
+        if self.hear_live {
+            // Start over if the line was edited before what's already sent.
+            if self.hear_queued > text.len() || !text.is_char_boundary(self.hear_queued) {
+                self.hear_queued = text.len();
+            }
+            let pending = &text[self.hear_queued..];
+            // Whitespace waits for the next character to become a word gap.
+            if pending.chars().any(|ch| !ch.is_whitespace()) {
+                #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+                self.winkeyer.queue_text(pending);
+                self.player.queue(
+                    pending,
+                    self.hear_queued,
+                    self.dit_duration,
+                    settings.weight_ratio(),
+                    &settings.characters,
+                );
+                self.hear_queued = text.len();
+                self.hearing = true;
+                self.replaying = false;
             }
-            for (prosign, seq) in crate::consts::PROSIGNS.iter() {
-                if &self.buffer.iter().collect::<String>() == seq {
-                    self.text.push_str(&prosign.to_string());
+        } else if send {
+            #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+            self.winkeyer.send_text(text);
+            self.player.play(
+                text,
+                self.dit_duration,
+                settings.weight_ratio(),
+                &settings.characters,
+            );
+            self.hearing = true;
+            self.replaying = false;
+        }
+
+        let current = self
+            .hearing
+            .then(|| self.player.current_character())
+            .flatten();
+        if let Some(index) = current
+            && let Some(ch) = text.get(index..).and_then(|rest| rest.chars().next())
+        {
+            let font = egui::FontId::monospace(24.);
+            let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
+            let mut highlighted = egui::TextFormat::simple(font, ui.visuals().strong_text_color());
+            highlighted.background = ui.visuals().selection.bg_fill;
+            let end = index + ch.len_utf8();
+            let mut job = egui::text::LayoutJob::default();
+            job.append(&text[..index], 0., normal.clone());
+            job.append(&text[index..end], 0., highlighted);
+            job.append(&text[end..], 0., normal);
+            ui.label(job);
+        }
+    }
+
+    /// Play back everything keyed so far, either as it was keyed
+    /// or with perfect timing to compare against.
+    fn replay(&mut self, settings: &Settings, perfect: bool) {
+        let session = self.recorder.session(settings);
+        if perfect {
+            self.player
+                .play_signal(session.perfect_signal(settings.weight_ratio()));
+        } else {
+            self.player.play_signal(session.signal());
+        }
+        self.replaying = true;
+        self.hearing = false;
+        self.tuning = false;
+    }
+
+    /// Let the user save everything keyed so far as JSON.
+    fn save_session(&mut self, ctx: &egui::Context, settings: &Settings) {
+        match self.recorder.session(settings).to_json() {
+            Ok(json) => {
+                self.pending_save = Some(files::save_file(
+                    ctx,
+                    "session.json",
+                    "Session",
+                    &["json"],
+                    json.into_bytes(),
+                ));
+            }
+            Err(err) => {
+                tracing::error!("Failed to serialize the session: {}", err);
+                self.save_message = Some(format!(
+                    "{}: {}",
+                    tr("Failed to serialize the session"),
+                    err
+                ));
+            }
+        }
+    }
+
+    /// Let the user save the decoded text, with a header describing the session if asked to.
+    fn save_transcript(&mut self, ctx: &egui::Context, settings: &Settings) {
+        let mut text = String::new();
+        if self.transcript_header {
+            text.push_str(&format!(
+                "MORSET transcript\nStarted: {}\nSpeed: {} WPM\nKeyer: {}\n\n",
+                self.stats.started_at_utc(),
+                settings.wpm,
+                settings.keyer_mode
+            ));
+        }
+        text.extend(self.transcript.entries().map(|entry| entry.ch));
+        text.push('\n');
+        self.pending_save = Some(files::save_file(
+            ctx,
+            "transcript.txt",
+            "Text",
+            &["txt"],
+            text.into_bytes(),
+        ));
+    }
+
+    /// Report how the save dialog went once it's closed.
+    fn poll_save(&mut self) {
+        let Some(result) = self.pending_save.as_ref().and_then(PendingSave::poll) else {
+            return;
+        };
+        self.pending_save = None;
+        self.save_message = Some(match result {
+            Ok(Some(name)) => format!("{} {}", tr("Saved"), name),
+            Ok(None) => tr("Saving was cancelled").to_string(),
+            Err(err) => err,
+        });
+    }
+
+    /// Append a completed character or space to the text.
+    fn apply_decoded(&mut self, decoded: Decoded) {
+        match decoded {
+            Decoded::Guess(ch) => self.last_decoded = Some(Decoded::Char(ch)),
+            Decoded::Unknown | Decoded::Space => {}
+            other => self.last_decoded = Some(other),
+        }
+        match decoded {
+            Decoded::Char(ch) => {
+                let error = self.fist.finish_character(ch, self.dit_duration);
+                self.stats.record_sent();
+                self.transcript.push(ch, false);
+                if let Some(drill) = &mut self.drill {
+                    drill.push(ch, error);
+                }
+            }
+            Decoded::Guess(ch) => {
+                let error = self.fist.finish_character(ch, self.dit_duration);
+                self.stats.record_sent();
+                self.transcript.push(ch, true);
+                if let Some(drill) = &mut self.drill {
+                    drill.push(ch, error);
+                }
+            }
+            Decoded::ProSign(ProSign::AA) => {
+                self.fist.discard_character();
+                self.stats.record_sent();
+                self.transcript.push('\n', false);
+            }
+            Decoded::ProSign(prosign) => {
+                self.fist.discard_character();
+                self.stats.record_sent();
+                for ch in prosign.to_string().chars() {
+                    self.transcript.push(ch, false);
                 }
             }
-            // No matter if we found a value or not,
-            // we need to clear up the buffer anyways.
-            self.buffer.clear();
-        } else if tick == 7 && !self.text.is_empty() && !self.text.ends_with(' ') {
-            self.text.push(' ');
+            Decoded::Custom(index) => {
+                self.fist.discard_character();
+                self.stats.record_sent();
+                if let Some(prosign) = self.decoder.custom_prosign(index) {
+                    for ch in prosign.tag.chars() {
+                        self.transcript.push(ch, false);
+                    }
+                }
+            }
+            Decoded::Unknown => self.fist.discard_character(),
+            // A word that was deleted isn't finished again.
+            Decoded::Space if self.word_pending() => {
+                self.transcript.push(' ', false);
+                // The word is complete, see how well it matches the target.
+                if let Some(drill) = &mut self.drill
+                    && let Some(grade) = drill.finish()
+                {
+                    self.stats.record_grade(grade);
+                }
+            }
+            Decoded::Space => {}
         }
     }
 
-    fn render_ui(&mut self, ctx: &egui::Context, audio: &mut Option<AudioManager>) {
+    /// Large buttons that are held like the keyboard keys they replace.
+    fn render_touch_keys(
+        &mut self,
+        ui: &mut egui::Ui,
+        settings: &Settings,
+        audio: &mut Option<AudioManager>,
+    ) {
+        let size = egui::vec2(160., 100.);
+        let enabled = !self.replaying && !self.tuning;
+        let key_button = |ui: &mut egui::Ui, label: &str| {
+            let button = egui::Button::new(RichText::new(label).size(30.))
+                .min_size(size)
+                .sense(egui::Sense::drag());
+            ui.add_enabled(enabled, button).is_pointer_button_down_on()
+        };
+
+        ui.horizontal_centered(|ui| {
+            if settings.keyer_mode == KeyerMode::Straight {
+                let held = key_button(ui, tr("Key"));
+                if held != self.touch_held[0] {
+                    self.touch_held[0] = held;
+                    if held {
+                        self.press_straight_key(audio);
+                    } else {
+                        self.release_straight_key(settings, audio);
+                    }
+                }
+            } else {
+                for (index, paddle, label) in
+                    [(1, IambicKey::Dot, "Dit"), (2, IambicKey::Dash, "Dah")]
+                {
+                    let held = key_button(ui, tr(label));
+                    if held != self.touch_held[index] {
+                        self.touch_held[index] = held;
+                        if held {
+                            self.keyer.press_key(paddle);
+                        } else {
+                            self.keyer.release_key(paddle);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn render_ui(
+        &mut self,
+        ctx: &egui::Context,
+        audio: &mut Option<AudioManager>,
+        settings: &mut Settings,
+    ) {
+        // Keys are shown as soon as the screen is touched.
+        if ctx.input(|i| i.any_touches()) {
+            settings.show_touch_keys = true;
+        }
         // Top panel with ticks
         egui::TopBottomPanel::top("Ticks").show(ctx, |ui| {
             ui.centered_and_justified(|ui| {
-                let ticks_info = (1..=7)
-                    .map(|i| if i <= self.ticks { '+' } else { '-' })
-                    .collect::<String>();
-                ui.label(RichText::new(ticks_info).size(25.));
+                let speed = match self.speed.wpm() {
+                    Some(wpm) => format!("{:.0} WPM", wpm),
+                    None => "-- WPM".to_string(),
+                };
+                let quick = match settings.quick_speed() {
+                    Some(name) => format!("    {} {} WPM", name, settings.wpm),
+                    None => String::new(),
+                };
+                let muted = if audio.as_ref().is_some_and(AudioManager::is_muted) {
+                    "    🔇 Muted"
+                } else {
+                    ""
+                };
+                let text = format!("{}{}{}", speed, quick, muted);
+                render_ticks(
+                    ui,
+                    self.max_ticks(),
+                    self.max_ticks() - self.ticks,
+                    &text,
+                    settings.high_contrast,
+                );
             });
         });
 
+        if self.timeline_open {
+            egui::TopBottomPanel::top("timeline").show(ctx, |ui| {
+                render_timeline(ui, &self.timeline, self.dit_duration);
+            });
+        }
+
+        if let Some(drill) = &mut self.drill {
+            egui::TopBottomPanel::top("drill").show(ctx, |ui| {
+                render_drill(ui, drill, &mut self.stats, settings);
+            });
+        }
+
+        if self.hear_text.is_some() {
+            egui::TopBottomPanel::top("type_to_hear").show(ctx, |ui| {
+                self.render_type_to_hear(ui, settings);
+            });
+        }
+
         // Bottom panel with controls
+        // Kept to part of the screen at large scales, scrolling through the rest.
         egui::TopBottomPanel::bottom("controls").show(ctx, |ui| {
-            ui.vertical_centered(|ui| {
-                ui.label("Controls:");
-                ui.horizontal(|ui| {
-                    ui.vertical(|ui| {
-                        for (key, value) in [
-                            ("Esc", "Return to Main Menu"),
-                            ("Bksp", "Clear text"),
-                            ("F1", "Decrease WPM"),
-                            ("F2", "Increase WPM"),
-                            ("F3", "Decrease frequency"),
-                            ("F4", "Increase frequency"),
-                            ("F5", "Decrease volume"),
-                            ("F6", "Increase volume"),
-                            ("C", "Toggle cheat sheet"),
-                            ("Space", "Emit a signal"),
-                        ] {
-                            ui.horizontal(|ui| {
-                                ui.label(format!("{:<8} - {}", key, value));
-                            });
-                        }
-                    });
-                    ui.vertical(|ui| {
-                        ui.label("Settings:");
-                        ui.horizontal(|ui| {
-                            ui.label("WPM:");
-                            let wpm = ui.add(egui::Slider::new(&mut self.wpm, MIN_WPM..=MAX_WPM));
-                            if wpm.changed() {
-                                self.normalize_values();
-                            }
-                        });
+            egui::ScrollArea::vertical()
+                .max_height(ctx.screen_rect().height() * 0.4)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(tr("Controls:"));
                         ui.horizontal(|ui| {
-                            ui.label("Frequency:");
-                            let frequency = ui.add(egui::Slider::new(
-                                &mut self.frequency,
-                                MIN_FREQUENCY..=MAX_FREQUENCY,
-                            ));
-                            if let Some(audio) = audio {
-                                if frequency.changed() {
-                                    audio.set_frequency(self.frequency as f32);
+                            ui.vertical(|ui| {
+                                for action in Action::ALL {
+                                    ui.horizontal(|ui| {
+                                        let capturing = self.rebinding == Some(action);
+                                        let label = if capturing {
+                                            tr("Press a key...").to_string()
+                                        } else {
+                                            settings.bindings.key(action).name().to_string()
+                                        };
+                                        let response = ui.selectable_label(capturing, label);
+                                        if response.clicked() {
+                                            // Keep the key being captured from activating the label again.
+                                            response.surrender_focus();
+                                            self.binding_conflict = None;
+                                            self.rebinding =
+                                                if capturing { None } else { Some(action) };
+                                        }
+                                        ui.label(format!("- {}", action.description()));
+                                    });
                                 }
-                            }
-                        });
-                        ui.horizontal(|ui| {
-                            ui.label("Volume:");
-                            let volume = ui
-                                .add(egui::Slider::new(&mut self.volume, MIN_VOLUME..=MAX_VOLUME));
-
-                            if let Some(audio) = audio {
-                                if volume.changed() {
-                                    audio.set_volume(self.volume as f32 * 0.01);
+                                if let Some(conflict) = &self.binding_conflict {
+                                    ui.colored_label(ui.visuals().warn_fg_color, conflict);
                                 }
-                            }
+                            });
+                            ui.vertical(|ui| {
+                                ui.label(tr("Settings:"));
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("WPM:"));
+                                    if wpm_slider(ui, settings) {
+                                        self.normalize_values(settings);
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Weight:"));
+                                    weight_slider(ui, settings);
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Dash threshold:"));
+                                    let threshold = ui.add(
+                                        egui::Slider::new(
+                                            &mut settings.dash_threshold,
+                                            MIN_DASH_THRESHOLD..=MAX_DASH_THRESHOLD,
+                                        )
+                                        .suffix(" dits"),
+                                    );
+                                    if threshold.changed() {
+                                        self.normalize_values(settings);
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Decode tolerance:"));
+                                    let tolerance = ui.add(egui::Slider::new(
+                                        &mut settings.decode_tolerance,
+                                        0..=MAX_DECODE_TOLERANCE,
+                                    ));
+                                    if tolerance.changed() {
+                                        self.decoder.set_tolerance(settings.decode_tolerance);
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut settings.show_timestamps, tr("Timestamps"));
+                                    ui.checkbox(&mut self.analysis_open, tr("Timing analysis"));
+                                    ui.checkbox(&mut self.timeline_open, tr("Timeline"));
+                                    ui.checkbox(&mut self.stats_open, tr("Stats"));
+                                    ui.checkbox(&mut self.help_open, tr("Shortcuts"));
+                                    ui.checkbox(&mut self.scope_open, tr("Scope"));
+                                    ui.checkbox(&mut self.spectrum_open, tr("Spectrum"));
+                                    render_timer(ui, &self.stats, settings.session_goal());
+                                    #[cfg(feature = "network")]
+                                    ui.checkbox(&mut self.link_open, tr("Partner"));
+                                    let mut drilling = self.drill.is_some();
+                                    if ui.checkbox(&mut drilling, tr("Sending drill")).changed() {
+                                        self.drill = drilling
+                                            .then(|| SendingDrill::new(settings.text_generator()));
+                                    }
+                                    let mut typing = self.hear_text.is_some();
+                                    if ui.checkbox(&mut typing, tr("Type to hear")).changed() {
+                                        self.hear_text = typing.then(String::new);
+                                        self.hear_queued = 0;
+                                    }
+                                    ui.checkbox(&mut settings.show_touch_keys, tr("Touch keys"));
+                                    extended_checkbox(ui, settings);
+                                    ui.label(tr("History:"));
+                                    let limit = ui.add(
+                                        egui::Slider::new(
+                                            &mut settings.history_limit,
+                                            MIN_HISTORY_LIMIT..=MAX_HISTORY_LIMIT,
+                                        )
+                                        .logarithmic(true),
+                                    );
+                                    if limit.changed() {
+                                        self.transcript.set_limit(settings.history_limit);
+                                    }
+                                    if ui.button(tr("Clear all")).clicked() {
+                                        self.request_clear();
+                                    }
+                                    let undo = egui::Button::new(tr("Undo"));
+                                    if ui.add_enabled(self.transcript.can_undo(), undo).clicked() {
+                                        self.transcript.undo();
+                                    }
+                                    let redo = egui::Button::new(tr("Redo"));
+                                    if ui.add_enabled(self.transcript.can_redo(), redo).clicked() {
+                                        self.transcript.redo();
+                                    }
+                                    let save = egui::Button::new(tr("Save session"));
+                                    if ui.add_enabled(!self.recorder.is_empty(), save).clicked() {
+                                        self.save_session(ctx, settings);
+                                    }
+                                    let save = egui::Button::new(tr("Save transcript"));
+                                    if ui.add_enabled(!self.transcript.is_empty(), save).clicked() {
+                                        self.save_transcript(ctx, settings);
+                                    }
+                                    ui.checkbox(&mut self.transcript_header, tr("With header"));
+                                });
+                                self.poll_save();
+                                if let Some(message) = &self.save_message {
+                                    ui.label(message);
+                                }
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Replay:"));
+                                    if self.replaying {
+                                        if ui.button(tr("Stop")).clicked() {
+                                            self.player.stop(audio);
+                                            self.replaying = false;
+                                            self.hearing = false;
+                                            self.tuning = false;
+                                        }
+                                    } else {
+                                        ui.add_enabled_ui(!self.recorder.is_empty(), |ui| {
+                                            if ui.button(tr("As keyed")).clicked() {
+                                                self.replay(settings, false);
+                                            }
+                                            if ui.button(tr("Perfect timing")).clicked() {
+                                                self.replay(settings, true);
+                                            }
+                                        });
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Frequency:"));
+                                    if frequency_slider(ui, settings)
+                                        && let Some(audio) = audio
+                                    {
+                                        audio.set_frequency(settings.frequency as f32);
+                                    }
+                                    let mut tuning = self.tuning;
+                                    if ui.toggle_value(&mut tuning, tr("Tune")).changed() {
+                                        self.set_tuning(tuning, audio);
+                                    }
+                                });
+                                if frequency_presets(ui, settings)
+                                    && let Some(audio) = audio
+                                {
+                                    audio.set_frequency(settings.frequency as f32);
+                                }
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Metronome:"));
+                                    egui::ComboBox::from_id_salt("metronome")
+                                        .selected_text(settings.metronome.to_string())
+                                        .show_ui(ui, |ui| {
+                                            for option in MetronomeMode::ALL {
+                                                ui.selectable_value(
+                                                    &mut settings.metronome,
+                                                    option,
+                                                    option.to_string(),
+                                                );
+                                            }
+                                        });
+                                    ui.add_enabled(
+                                        settings.metronome != MetronomeMode::Off,
+                                        egui::Slider::new(
+                                            &mut settings.metronome_volume,
+                                            MIN_METRONOME_VOLUME..=MAX_METRONOME_VOLUME,
+                                        )
+                                        .suffix("%"),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Volume:"));
+                                    if volume_slider(ui, settings)
+                                        && let Some(audio) = audio
+                                    {
+                                        audio.set_volume(settings.gain());
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Waveform:"));
+                                    if waveform_combo(ui, &mut settings.waveform)
+                                        && let Some(audio) = audio
+                                    {
+                                        audio.set_waveform(settings.waveform);
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Alphabet:"));
+                                    alphabet_combo(ui, settings);
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Theme:"));
+                                    theme_combo(ui, &mut settings.theme);
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Keyer:"));
+                                    if keyer_combo(ui, &mut settings.keyer_mode) {
+                                        self.set_keyer_mode(settings.keyer_mode, audio);
+                                    }
+                                });
+                                if ui
+                                    .checkbox(&mut settings.swap_paddles, tr("Swap paddles"))
+                                    .changed()
+                                {
+                                    // Held paddles would be released as the other element,
+                                    // so let the current element finish and start over.
+                                    self.keyer.release_all();
+                                }
+                                if self.output_devices.is_none() {
+                                    self.refresh_output_devices(settings, audio);
+                                }
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Output:"));
+                                    let before = settings.output_device.clone();
+                                    egui::ComboBox::from_id_salt("output_device")
+                                        .selected_text(
+                                            settings
+                                                .output_device
+                                                .as_deref()
+                                                .unwrap_or(tr("Default")),
+                                        )
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(
+                                                &mut settings.output_device,
+                                                None,
+                                                tr("Default"),
+                                            );
+                                            for device in self.output_devices.iter().flatten() {
+                                                ui.selectable_value(
+                                                    &mut settings.output_device,
+                                                    Some(device.clone()),
+                                                    device,
+                                                );
+                                            }
+                                        });
+                                    if ui.button(tr("Refresh")).clicked() {
+                                        self.refresh_output_devices(settings, audio);
+                                    }
+                                    if settings.output_device != before {
+                                        self.device_warning = None;
+                                        self.apply_output_device(settings, audio);
+                                    }
+                                });
+                                if let Some(warning) = &self.device_warning {
+                                    ui.colored_label(ui.visuals().warn_fg_color, warning);
+                                }
+                                #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+                                if let Some(error) = self.midi.error() {
+                                    ui.colored_label(ui.visuals().warn_fg_color, error);
+                                }
+                                #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+                                if let Some(error) = self.serial.error() {
+                                    ui.colored_label(ui.visuals().warn_fg_color, error);
+                                }
+                                #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+                                if let Some(error) = self.winkeyer.error() {
+                                    ui.colored_label(ui.visuals().warn_fg_color, error);
+                                }
+                            });
                         });
                     });
                 });
-            });
         });
 
+        if self.scope_open || self.spectrum_open {
+            egui::SidePanel::right("scope")
+                .resizable(false)
+                .exact_width(SCOPE_WIDTH)
+                .show(ctx, |ui| {
+                    if self.scope_open {
+                        render_scope(ui, audio.as_ref());
+                    }
+                    if self.spectrum_open {
+                        match audio {
+                            Some(audio) => render_spectrum(
+                                ui,
+                                &audio.spectrum(),
+                                SPECTRUM_BIN,
+                                settings.frequency as f32,
+                            ),
+                            None => {
+                                ui.heading(tr("Spectrum"));
+                                ui.label(tr("No audio output"));
+                            }
+                        }
+                    }
+                });
+        }
+
         // Main text area
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical_centered(|ui| {
-                let buff = self.buffer.iter().collect::<String>();
-                ui.label(egui::RichText::new(format!("{}{}|", self.text, buff)).size(32.));
-            });
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    let font = egui::FontId::proportional(settings.transcript_size);
+                    let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
+                    let dimmed = egui::TextFormat::simple(font, ui.visuals().weak_text_color());
+
+                    if settings.show_timestamps {
+                        ui.horizontal_wrapped(|ui| {
+                            let entries = self.transcript.entries().collect::<Vec<_>>();
+                            for word in entries.split_inclusive(|entry| entry.ch.is_whitespace()) {
+                                let (word, line_break) = match word.split_last() {
+                                    Some((last, rest)) if last.ch.is_whitespace() => {
+                                        (rest, last.ch == '\n')
+                                    }
+                                    _ => (word, false),
+                                };
+                                if let Some(first) = word.first() {
+                                    let offset = self.transcript.offset(first).as_secs();
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "{:02}:{:02}",
+                                            offset / 60,
+                                            offset % 60
+                                        ))
+                                        .small()
+                                        .weak(),
+                                    );
+                                    ui.label(transcript_job(
+                                        word.iter().copied(),
+                                        &normal,
+                                        &dimmed,
+                                    ));
+                                }
+                                // The AA prosign starts a new line.
+                                if line_break {
+                                    ui.end_row();
+                                }
+                            }
+                            let mut current = egui::text::LayoutJob::default();
+                            append_buffer(
+                                &mut current,
+                                self.decoder.buffer(),
+                                &normal,
+                                ui.visuals(),
+                            );
+                            ui.label(current);
+                        });
+                    } else {
+                        ui.vertical_centered(|ui| {
+                            let mut job =
+                                transcript_job(self.transcript.entries(), &normal, &dimmed);
+                            append_buffer(&mut job, self.decoder.buffer(), &normal, ui.visuals());
+                            ui.label(job);
+                        });
+                    }
+                });
         });
 
+        if settings.show_touch_keys {
+            egui::TopBottomPanel::bottom("touch_keys").show(ctx, |ui| {
+                self.render_touch_keys(ui, settings, audio);
+            });
+        }
+
+        // Timing analysis window
+        egui::Window::new(tr("Timing analysis"))
+            .open(&mut self.analysis_open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let fist = &self.fist;
+                match fist.consistency() {
+                    Some(consistency) => {
+                        ui.label(
+                            RichText::new(format!("Consistency: {:.0}%", consistency)).size(20.),
+                        );
+                    }
+                    None => {
+                        ui.label(tr("Key some characters to see how even your timing is."));
+                    }
+                }
+
+                egui::Grid::new("fist_stats").striped(true).show(ui, |ui| {
+                    for (name, stats) in [
+                        ("Dits", fist.dits()),
+                        ("Dashes", fist.dashes()),
+                        ("Element gaps", fist.element_gaps()),
+                        ("Character gaps", fist.character_gaps()),
+                    ] {
+                        ui.label(tr(name));
+                        ui.label(format_stats(stats));
+                        ui.end_row();
+                    }
+                    if let Some(ratio) = fist.ratio() {
+                        ui.label(tr("Dash:dit ratio"));
+                        ui.label(format!("{:.1}:1 ({})", ratio, tr("ideal 3:1")));
+                        ui.end_row();
+                    }
+                });
+
+                let warn = ui.visuals().warn_fg_color;
+                if let Some(ratio) = fist.ratio() {
+                    if ratio < 3. - RATIO_TOLERANCE {
+                        ui.colored_label(warn, tr("Dashes are too short"));
+                    } else if ratio > 3. + RATIO_TOLERANCE {
+                        ui.colored_label(warn, tr("Dashes are too long"));
+                    }
+                }
+                if let Some(gap) = fist.character_gap_units()
+                    && gap > MAX_CHARACTER_GAP
+                {
+                    ui.colored_label(warn, tr("Gaps between characters are too long"));
+                }
+
+                let worst = fist.worst_characters(5);
+                if !worst.is_empty() {
+                    ui.separator();
+                    ui.label(tr("Least accurate characters:"));
+                    for (ch, error) in worst {
+                        ui.label(
+                            RichText::new(format!("{}: {:.0}% off", ch, error * 100.)).monospace(),
+                        );
+                    }
+                }
+                if ui.button(tr("Reset")).clicked() {
+                    self.fist.clear();
+                }
+            });
+
+        if self.confirm_clear {
+            egui::Window::new(tr("Clear all text?"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+                .show(ctx, |ui| {
+                    ui.label(tr("The text can be brought back with Undo (Ctrl+Z)."));
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Clear")).clicked() {
+                            self.confirm_clear = false;
+                            self.clear_all();
+                        }
+                        if ui.button(tr("Cancel")).clicked() {
+                            self.confirm_clear = false;
+                        }
+                    });
+                });
+        }
+
+        #[cfg(feature = "network")]
+        egui::Window::new(tr("Partner"))
+            .open(&mut self.link_open)
+            .collapsible(false)
+            .show(ctx, |ui| render_link(ui, &mut self.link, settings, audio));
+
+        egui::Window::new(tr("Session stats"))
+            .open(&mut self.stats_open)
+            .collapsible(false)
+            .show(ctx, |ui| render_stats(ui, &self.stats));
+
+        egui::Window::new(tr("Keyboard shortcuts"))
+            .open(&mut self.help_open)
+            .collapsible(false)
+            .show(ctx, |ui| render_shortcuts(ui, &settings.bindings));
+
         // Cheat sheet window
-        egui::Window::new("Cheatsheet")
+        egui::Window::new(tr("Cheatsheet"))
             .collapsible(true)
             .open(&mut self.cheat_sheet_open)
             .collapsible(false)
+            .vscroll(true)
             .show(ctx, |ui| {
+                if self.cheat_sheet.0 != settings.characters {
+                    let characters = settings.characters.clone();
+                    let entries = cheat_sheet_entries(&characters, &settings.custom_prosigns);
+                    self.cheat_sheet = (characters, entries);
+                }
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.cheat_sheet_search)
+                        .hint_text(tr("Character or pattern, e.g. -.")),
+                );
+                let query = self.cheat_sheet_search.trim();
+                let (prosigns, characters): (Vec<_>, Vec<_>) = self
+                    .cheat_sheet
+                    .1
+                    .iter()
+                    .filter(|entry| entry.matches(query))
+                    .partition(|entry| !matches!(entry.decoded, Decoded::Char(_)));
+                // While keying, the entries the buffer is building toward are bold
+                // and the one it matches is highlighted, otherwise the last decoded one is.
+                let buffer = self.decoder.buffer();
+                // Entries are played on click, unless there's no audio to play them on.
+                let playable = audio.is_some();
+                let mut clicked = None;
+                let mut label = |ui: &mut egui::Ui, entry: &CheatSheetEntry| {
+                    let mut text = RichText::new(&entry.line).monospace().size(20.);
+                    let highlighted = if buffer.is_empty() {
+                        self.last_decoded == Some(entry.decoded)
+                    } else {
+                        entry.sequence == buffer
+                    };
+                    if highlighted {
+                        text = text
+                            .strong()
+                            .background_color(ui.visuals().selection.bg_fill);
+                    } else if !buffer.is_empty() && entry.sequence.starts_with(buffer) {
+                        text = text.strong();
+                    }
+                    if !playable {
+                        ui.label(text);
+                        return;
+                    }
+                    let row = ui
+                        .horizontal(|ui| {
+                            ui.label(RichText::new("🔊").size(14.).weak());
+                            ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+                        })
+                        .inner
+                        .on_hover_cursor(egui::CursorIcon::PointingHand);
+                    if row.clicked() {
+                        clicked = Some(entry.sequence.clone());
+                    }
+                };
+                // Two columns side by side only while they fit on the screen.
+                let widest = characters.iter().map(|entry| entry.line.chars().count());
+                let glyph = ui.fonts(|fonts| fonts.glyph_width(&egui::FontId::monospace(20.), 'M'));
+                let column_width = glyph * (widest.max().unwrap_or_default() + 2) as f32;
+                let columns = if 2. * column_width < ui.ctx().screen_rect().width() * 0.8 {
+                    2
+                } else {
+                    1
+                };
                 ui.horizontal(|ui| {
-                    let codes = crate::consts::ABC
-                        .iter()
-                        .chain(crate::consts::NUMBERS.iter())
-                        .chain(crate::consts::SIGNS.iter())
-                        .collect::<Vec<_>>();
-                    let middle = codes.len() / 2;
-
-                    ui.vertical(|ui| {
-                        for (id, (ch, seq)) in codes.iter().enumerate() {
-                            if id <= middle {
-                                ui.label(
-                                    RichText::new(format!("{}: {}", ch, seq))
-                                        .monospace()
-                                        .size(20.),
-                                );
+                    for column in characters.chunks(characters.len().div_ceil(columns).max(1)) {
+                        ui.vertical(|ui| {
+                            for entry in column {
+                                label(ui, entry);
                             }
-                        }
-                    });
-                    ui.vertical(|ui| {
-                        for (id, (ch, seq)) in codes.iter().enumerate() {
-                            if id > middle {
-                                ui.label(
-                                    RichText::new(format!("{}: {}", ch, seq))
-                                        .monospace()
-                                        .size(20.),
-                                );
+                        });
+                    }
+                });
+                if !prosigns.is_empty() {
+                    ui.separator();
+                    ui.label(RichText::new(tr("Prosigns")).strong());
+                    for entry in &prosigns {
+                        label(ui, entry);
+                    }
+                }
+                if characters.is_empty() && prosigns.is_empty() {
+                    ui.label(tr("Nothing matches the search."));
+                }
+                if let Some(sequence) = clicked {
+                    self.player.play_signal(sequence_signal(
+                        &sequence,
+                        self.dit_duration,
+                        settings.weight_ratio(),
+                    ));
+                    self.replaying = false;
+                }
+
+                egui::CollapsingHeader::new(tr("Custom prosigns")).show(ui, |ui| {
+                    if custom_prosign_editor(
+                        ui,
+                        &mut settings.custom_prosigns,
+                        &mut self.new_prosign,
+                        &settings.characters,
+                    ) {
+                        self.decoder
+                            .set_custom_prosigns(settings.custom_prosigns.clone());
+                        self.cheat_sheet.1 =
+                            cheat_sheet_entries(&settings.characters, &settings.custom_prosigns);
+                    }
+                });
+
+                egui::CollapsingHeader::new(tr("Abbreviations")).show(ui, |ui| {
+                    egui::Grid::new("abbreviations")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (abbreviation, meaning, seq) in consts::ABBREVIATIONS {
+                                if ui.small_button("▶").clicked() {
+                                    self.player.play(
+                                        abbreviation,
+                                        self.dit_duration,
+                                        settings.weight_ratio(),
+                                        &settings.characters,
+                                    );
+                                    self.replaying = false;
+                                }
+                                ui.label(RichText::new(abbreviation).monospace().strong());
+                                ui.label(RichText::new(seq).monospace());
+                                ui.label(meaning);
+                                ui.end_row();
                             }
+                        });
+                });
+
+                egui::CollapsingHeader::new(tr("Cut numbers")).show(ui, |ui| {
+                    ui.label(tr(
+                        "Letters sent in place of digits in fast exchanges, like 5NN for 599.",
+                    ));
+                    ui.horizontal_wrapped(|ui| {
+                        for (digit, letter) in consts::CUT_NUMBERS {
+                            ui.label(
+                                RichText::new(format!("{}: {}", digit, letter))
+                                    .monospace()
+                                    .size(20.),
+                            );
                         }
                     });
                 });
+
+                if settings.extended_characters && settings.alphabet == Alphabet::Latin {
+                    ui.separator();
+                    ui.label(RichText::new(tr("Accented letters")).strong());
+                    ui.horizontal_wrapped(|ui| {
+                        for (ch, seq) in consts::EXTENDED.iter() {
+                            ui.label(
+                                RichText::new(format!("{}: {}", ch, seq))
+                                    .monospace()
+                                    .size(20.),
+                            );
+                        }
+                    });
+                }
+            });
+    }
+}
+
+/// Tick indicator of `total` blocks, a filled block for every tick left followed
+/// by an empty one for every tick passed, with the text after it. The blocks are told
+/// apart by their shape, colored from a colorblind-safe palette or in the text color
+/// for high contrast.
+fn render_ticks(ui: &mut egui::Ui, total: usize, left: usize, text: &str, high_contrast: bool) {
+    let visuals = ui.visuals().clone();
+    let (filled, empty) = if high_contrast {
+        let strong = visuals.strong_text_color();
+        (strong, egui::Stroke::new(2., strong))
+    } else if visuals.dark_mode {
+        // Sky blue and blue of the Okabe-Ito palette
+        (
+            egui::Color32::from_rgb(86, 180, 233),
+            egui::Stroke::new(1., visuals.weak_text_color()),
+        )
+    } else {
+        (
+            egui::Color32::from_rgb(0, 114, 178),
+            egui::Stroke::new(1., visuals.weak_text_color()),
+        )
+    };
+    let galley = ui.painter().layout_no_wrap(
+        format!("    {}", text),
+        egui::FontId::proportional(TICKS_TEXT_SIZE),
+        visuals.text_color(),
+    );
+    let side = galley.size().y * 0.7;
+    let step = side * 1.3;
+    let size = egui::vec2(total as f32 * step + galley.size().x, galley.size().y);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter();
+    for tick in 0..total {
+        let center = egui::pos2(rect.left() + (tick as f32 + 0.5) * step, rect.center().y);
+        let block = egui::Rect::from_center_size(center, egui::vec2(side, side));
+        if tick < left {
+            painter.rect_filled(block, 2., filled);
+        } else {
+            painter.rect_stroke(block, 2., empty, egui::StrokeKind::Inside);
+        }
+    }
+    let text_pos = egui::pos2(rect.left() + total as f32 * step, rect.top());
+    painter.galley(text_pos, galley, visuals.text_color());
+}
+
+/// List the shortcuts grouped by category, with the keys currently bound.
+fn render_shortcuts(ui: &mut egui::Ui, bindings: &KeyBindings) {
+    for category in ActionCategory::ALL {
+        ui.strong(category.to_string());
+        egui::Grid::new(("shortcuts", category as usize))
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for action in Action::ALL.into_iter().filter(|a| a.category() == category) {
+                    ui.monospace(bindings.key(action).name());
+                    ui.label(action.description());
+                    ui.end_row();
+                }
+                for (_, keys, description) in FIXED_SHORTCUTS
+                    .into_iter()
+                    .filter(|(c, _, _)| *c == category)
+                {
+                    ui.monospace(keys);
+                    ui.label(description);
+                    ui.end_row();
+                }
             });
+        ui.add_space(6.);
+    }
+    ui.weak(tr("Press Escape to close."));
+}
+
+/// Target word of the sending drill along with the grade of the last attempt.
+fn render_drill(
+    ui: &mut egui::Ui,
+    drill: &mut SendingDrill,
+    stats: &mut SessionStats,
+    settings: &mut Settings,
+) {
+    ui.vertical_centered(|ui| {
+        if text_source_settings(ui, settings) {
+            drill.set_generator(settings.text_generator());
+        }
+        ui.label(
+            RichText::new(format!("Send: {}", drill.target()))
+                .size(30.)
+                .strong(),
+        );
+        if !drill.sent().is_empty() {
+            ui.label(RichText::new(drill.sent()).monospace().size(20.));
+        }
+
+        if let Some(grade) = drill.last_grade() {
+            let font = egui::FontId::monospace(24.);
+            let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
+            let wrong = egui::TextFormat::simple(font.clone(), ui.visuals().error_fg_color);
+            let mut missed = egui::TextFormat::simple(font, ui.visuals().warn_fg_color);
+            missed.strikethrough = egui::Stroke::new(2., ui.visuals().warn_fg_color);
+
+            let mut job = egui::text::LayoutJob::default();
+            for mark in &grade.marks {
+                match *mark {
+                    Mark::Correct(ch) => job.append(&ch.to_string(), 0., normal.clone()),
+                    Mark::Substituted { sent, .. } => {
+                        job.append(&sent.to_string(), 0., wrong.clone())
+                    }
+                    Mark::Missed(ch) => job.append(&ch.to_string(), 0., missed.clone()),
+                    Mark::Extra(ch) => {
+                        let mut extra = wrong.clone();
+                        extra.underline = egui::Stroke::new(2., ui.visuals().error_fg_color);
+                        job.append(&ch.to_string(), 0., extra)
+                    }
+                }
+            }
+            ui.label(job);
+
+            let timing = grade
+                .timing
+                .map(|timing| format!(", timing {:.0}%", timing * 100.))
+                .unwrap_or_default();
+            let verdict = if grade.passed() {
+                "Well done!"
+            } else {
+                "Try again"
+            };
+            ui.label(format!(
+                "{} Accuracy {:.0}%{}",
+                verdict,
+                grade.accuracy * 100.,
+                timing
+            ));
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button(tr("Check")).clicked()
+                && let Some(grade) = drill.finish()
+            {
+                stats.record_grade(grade);
+            }
+            if ui.button(tr("Skip")).clicked() {
+                drill.skip();
+            }
+        });
+    });
+}
+
+/// Recent tones scrolling to the left, over faint bars of their ideal length at the speed.
+/// Relay to connect to, the state of the link and what the partner sent.
+#[cfg(feature = "network")]
+fn render_link(
+    ui: &mut egui::Ui,
+    link: &mut crate::network::Link,
+    settings: &mut Settings,
+    audio: &mut Option<AudioManager>,
+) {
+    use crate::network::LinkState;
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Relay:"));
+        ui.add(
+            egui::TextEdit::singleline(&mut settings.relay_url)
+                .hint_text("ws://host:port")
+                .desired_width(250.),
+        );
+        if matches!(link.state(), LinkState::Disconnected | LinkState::Failed(_)) {
+            let url = settings.relay_url.trim();
+            if ui
+                .add_enabled(!url.is_empty(), egui::Button::new(tr("Connect")))
+                .clicked()
+            {
+                link.connect(ui.ctx(), url, audio);
+            }
+        } else if ui.button(tr("Disconnect")).clicked() {
+            link.disconnect(audio);
+        }
+    });
+    match link.state() {
+        LinkState::Disconnected => ui.label(tr("Not connected")),
+        LinkState::Connecting => ui.label(tr("Connecting...")),
+        LinkState::Open => ui.label(tr("Connected, your keying is sent to the partner")),
+        LinkState::Failed(err) => ui.colored_label(ui.visuals().warn_fg_color, err),
+    };
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.label(tr("Partner sent:"));
+        if ui.button(tr("Clear")).clicked() {
+            link.clear_text();
+        }
+    });
+    egui::ScrollArea::vertical()
+        .max_height(150.)
+        .stick_to_bottom(true)
+        .show(ui, |ui| ui.label(RichText::new(link.text()).size(20.)));
+}
+
+fn render_timeline(ui: &mut egui::Ui, timeline: &Timeline, dit_duration: Duration) {
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), 40.), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let now = timeline.now();
+    let span = TIMELINE_SPAN.as_secs_f32();
+    let x = |time: Duration| {
+        let age = now.saturating_sub(time).as_secs_f32();
+        rect.right() - age / span * rect.width()
+    };
+
+    let ideal_color = ui.visuals().weak_text_color().gamma_multiply(0.4);
+    let tone_color = ui.visuals().strong_text_color();
+    // Keyed tones on the upper half, ideal ones on the lower
+    let top = rect.top()..=rect.center().y - 2.0;
+    let bottom = rect.center().y + 2.0..=rect.bottom();
+    for tone in timeline.tones() {
+        let start = x(tone.start);
+        if let Some(element) = tone.element {
+            let units = if element == '.' { 1 } else { 3 };
+            let end = x(tone.start + dit_duration * units);
+            painter.rect_filled(
+                egui::Rect::from_x_y_ranges(start..=end, bottom.clone()),
+                2.,
+                ideal_color,
+            );
+        }
+        let end = x(tone.end.unwrap_or(now));
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(start..=end.max(start + 1.), top.clone()),
+            2.,
+            tone_color,
+        );
+    }
+}
+
+/// Average and spread of the durations in milliseconds.
+fn format_stats(stats: Option<Stats>) -> String {
+    match stats {
+        Some(stats) => format!(
+            "{:.0} ms ± {:.0} ms",
+            stats.mean * 1000.,
+            stats.deviation * 1000.
+        ),
+        None => "-".to_string(),
+    }
+}
+
+/// "Character: code" lines of the cheat sheet for the characters currently in use,
+/// followed by the built-in and custom prosigns.
+fn cheat_sheet_entries(
+    characters: &CharacterTable,
+    custom: &[CustomProSign],
+) -> Vec<CheatSheetEntry> {
+    let characters = characters.iter().map(|(ch, seq)| CheatSheetEntry {
+        decoded: Decoded::Char(ch),
+        name: ch.to_string(),
+        sequence: seq.to_string(),
+        line: format!("{}: {}", ch, seq),
+    });
+    let prosigns = consts::PROSIGNS
+        .iter()
+        .map(|(prosign, seq)| CheatSheetEntry {
+            decoded: Decoded::ProSign(*prosign),
+            name: prosign.tag().to_string(),
+            sequence: seq.to_string(),
+            line: format!("{}: {}", prosign.to_string().trim_end(), seq),
+        });
+    let custom = custom
+        .iter()
+        .enumerate()
+        .map(|(index, prosign)| CheatSheetEntry {
+            decoded: Decoded::Custom(index),
+            name: prosign.tag.clone(),
+            sequence: prosign.sequence.clone(),
+            line: format!("{} (Custom): {}", prosign.tag, prosign.sequence),
+        });
+    characters.chain(prosigns).chain(custom).collect()
+}
+
+/// Character or prosign of the cheat sheet with its line
+/// Tone on and off durations of a sequence of dots and dashes.
+fn sequence_signal(sequence: &str, dit_duration: Duration, weight: f32) -> Vec<(bool, Duration)> {
+    let mut signal = Vec::new();
+    for (index, element) in sequence.chars().enumerate() {
+        if index > 0 {
+            let gap = Timing {
+                on: false,
+                units: 1,
+            };
+            signal.push((false, gap.duration(dit_duration, weight)));
+        }
+        let units = if element == '-' { 3 } else { 1 };
+        let tone = Timing { on: true, units };
+        signal.push((true, tone.duration(dit_duration, weight)));
+    }
+    signal
+}
+
+struct CheatSheetEntry {
+    decoded: Decoded,
+    /// Character or tag the entry is searched by
+    name: String,
+    sequence: String,
+    line: String,
+}
+
+impl CheatSheetEntry {
+    /// Whether the entry is found by the query, a pattern the sequence starts with
+    /// or the characters looked for.
+    fn matches(&self, query: &str) -> bool {
+        if query.is_empty() {
+            return true;
+        }
+        if query.chars().all(|ch| ch == '.' || ch == '-') {
+            return self.sequence.starts_with(query);
+        }
+        let query = query.to_uppercase();
+        match self.decoded {
+            Decoded::Char(ch) | Decoded::Guess(ch) => query.contains(ch),
+            _ => self.name.contains(&query),
+        }
+    }
+}
+
+/// List the custom prosigns with a button to remove each and fields to add one.
+/// Returns whether the list changed.
+fn custom_prosign_editor(
+    ui: &mut egui::Ui,
+    prosigns: &mut Vec<CustomProSign>,
+    new_prosign: &mut (String, String),
+    characters: &CharacterTable,
+) -> bool {
+    let mut removed = None;
+    for (index, prosign) in prosigns.iter().enumerate() {
+        ui.horizontal(|ui| {
+            if ui.small_button("🗑").on_hover_text(tr("Remove")).clicked() {
+                removed = Some(index);
+            }
+            ui.monospace(format!("{}: {}", prosign.tag, prosign.sequence));
+            if let Some(taken) = prosign.shadowed_by(characters) {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("Decoded as {} instead", taken),
+                );
+            }
+        });
+    }
+    if let Some(index) = removed {
+        prosigns.remove(index);
+        return true;
+    }
+
+    let (tag, sequence) = new_prosign;
+    let mut added = false;
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(tag)
+                .hint_text("<BT>")
+                .desired_width(60.),
+        );
+        ui.add(
+            egui::TextEdit::singleline(sequence)
+                .hint_text("-...-")
+                .desired_width(100.),
+        );
+        let prosign = CustomProSign::new(tag, sequence).and_then(|prosign| {
+            match prosigns
+                .iter()
+                .find(|other| other.sequence == prosign.sequence)
+            {
+                Some(other) => Err(format!("Already added as {}", other.tag)),
+                None => Ok(prosign),
+            }
+        });
+        let can_add = prosign.is_ok() && prosigns.len() < MAX_CUSTOM_PROSIGNS;
+        if ui
+            .add_enabled(can_add, egui::Button::new(tr("Add")))
+            .clicked()
+            && let Ok(prosign) = prosign.clone()
+        {
+            prosigns.push(prosign);
+            tag.clear();
+            sequence.clear();
+            added = true;
+        }
+        match prosign {
+            Err(err) if !tag.is_empty() || !sequence.is_empty() => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            Ok(prosign) if !added => {
+                if let Some(taken) = prosign.shadowed_by(characters) {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("{} is already {}", prosign.sequence, taken),
+                    );
+                }
+            }
+            _ => {}
+        }
+    });
+    added
+}
+
+/// Lay out the character being keyed followed by a cursor,
+/// giving its elements their own colors.
+fn append_buffer(
+    job: &mut egui::text::LayoutJob,
+    buffer: &str,
+    normal: &egui::TextFormat,
+    visuals: &egui::Visuals,
+) {
+    for element in buffer.chars() {
+        let mut format = normal.clone();
+        format.color = element_color(visuals, element);
+        job.append(element.encode_utf8(&mut [0; 4]), 0., format);
+    }
+    job.append("|", 0., normal.clone());
+}
+
+/// Color of a dit or dash, readable on both dark and light backgrounds.
+fn element_color(visuals: &egui::Visuals, element: char) -> egui::Color32 {
+    match (element, visuals.dark_mode) {
+        ('.', true) => egui::Color32::from_rgb(110, 180, 255),
+        ('.', false) => egui::Color32::from_rgb(20, 90, 200),
+        (_, true) => egui::Color32::from_rgb(255, 170, 60),
+        (_, false) => egui::Color32::from_rgb(180, 80, 0),
+    }
+}
+
+/// Lay out decoded characters, dimming the guessed ones so it's clear they may be wrong.
+fn transcript_job<'a>(
+    entries: impl Iterator<Item = &'a TranscriptEntry>,
+    normal: &egui::TextFormat,
+    dimmed: &egui::TextFormat,
+) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    let mut run = String::new();
+    let mut run_guessed = false;
+    for entry in entries {
+        if entry.guessed != run_guessed && !run.is_empty() {
+            job.append(&run, 0., if run_guessed { dimmed } else { normal }.clone());
+            run.clear();
+        }
+        run_guessed = entry.guessed;
+        run.push(entry.ch);
+    }
+    job.append(&run, 0., if run_guessed { dimmed } else { normal }.clone());
+    job
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Longest step the screen is advanced by at once
+    const FRAME: Duration = Duration::from_millis(1);
+
+    fn step_with(
+        screen: &mut WritingScreen,
+        settings: &Settings,
+        pressed: &[egui::Key],
+        released: &[egui::Key],
+    ) {
+        let events = KeyEvents {
+            pressed: pressed.to_vec(),
+            released: released.to_vec(),
+        };
+        screen.step(Duration::ZERO, &events, settings, &mut None);
+    }
+
+    /// Let `units` dits pass without any input, a frame at a time.
+    fn wait(screen: &mut WritingScreen, settings: &Settings, units: u32) {
+        let mut left = screen.dit_duration * units;
+        while !left.is_zero() {
+            let delta = left.min(FRAME);
+            screen.step(delta, &KeyEvents::default(), settings, &mut None);
+            left -= delta;
+        }
+    }
+
+    /// Hold the straight key for `units` dits, then let it go.
+    fn key_element(screen: &mut WritingScreen, settings: &Settings, units: u32) {
+        let key = settings.bindings.key(Action::StraightKey);
+        step_with(screen, settings, &[key], &[]);
+        wait(screen, settings, units);
+        step_with(screen, settings, &[], &[key]);
+    }
+
+    /// Key the morse with the straight key, elements followed by an element gap.
+    fn key_morse(screen: &mut WritingScreen, settings: &Settings, morse: &str) {
+        for element in morse.chars() {
+            key_element(screen, settings, if element == '.' { 1 } else { 3 });
+            wait(screen, settings, 1);
+        }
+    }
+
+    /// Key the morse with the paddles, holding each one just into its element.
+    fn key_paddles(screen: &mut WritingScreen, settings: &Settings, morse: &str) {
+        for element in morse.chars() {
+            let (action, units) = if element == '.' {
+                (Action::DotPaddle, 1)
+            } else {
+                (Action::DashPaddle, 3)
+            };
+            let key = settings.bindings.key(action);
+            step_with(screen, settings, &[key], &[]);
+            wait(screen, settings, 1);
+            step_with(screen, settings, &[], &[key]);
+            wait(screen, settings, units);
+        }
+    }
+
+    fn text(screen: &WritingScreen) -> String {
+        screen.transcript.entries().map(|entry| entry.ch).collect()
+    }
+
+    #[test]
+    fn silence_separates_characters_and_words() {
+        for wpm in [5, 20, 35] {
+            let settings = Settings {
+                wpm,
+                ..Settings::default()
+            };
+            for (gap, expected) in [
+                (1, "A"),
+                (2, "A"),
+                (3, "ET"),
+                (5, "ET"),
+                (7, "E T"),
+                (12, "E T"),
+            ] {
+                let mut screen = WritingScreen::new(&settings);
+                key_element(&mut screen, &settings, 1);
+                wait(&mut screen, &settings, gap);
+                key_element(&mut screen, &settings, 3);
+                wait(&mut screen, &settings, 4);
+                assert_eq!(
+                    text(&screen),
+                    expected,
+                    "{} dits of silence at {} WPM",
+                    gap,
+                    wpm
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tone_follows_the_keys_without_audio() {
+        let settings = Settings::default();
+        let mut screen = WritingScreen::new(&settings);
+        let key = settings.bindings.key(Action::StraightKey);
+        step_with(&mut screen, &settings, &[key], &[]);
+        assert!(screen.is_tone_on());
+        wait(&mut screen, &settings, 1);
+        step_with(&mut screen, &settings, &[], &[key]);
+        assert!(!screen.is_tone_on());
+
+        let dot = settings.bindings.key(Action::DotPaddle);
+        let settings = Settings {
+            keyer_mode: KeyerMode::IambicB,
+            ..settings
+        };
+        let mut screen = WritingScreen::new(&settings);
+        step_with(&mut screen, &settings, &[dot], &[]);
+        assert!(screen.is_tone_on());
+        step_with(&mut screen, &settings, &[], &[dot]);
+        wait(&mut screen, &settings, 2);
+        assert!(!screen.is_tone_on());
+    }
+
+    #[test]
+    fn long_silence_adds_a_single_space() {
+        let settings = Settings::default();
+        let mut screen = WritingScreen::new(&settings);
+        key_morse(&mut screen, &settings, "...");
+        wait(&mut screen, &settings, 30);
+        key_morse(&mut screen, &settings, "---");
+        wait(&mut screen, &settings, 30);
+        assert_eq!(text(&screen), "S O ");
+    }
+
+    #[test]
+    fn silence_before_any_text_adds_nothing() {
+        let settings = Settings::default();
+        let mut screen = WritingScreen::new(&settings);
+        wait(&mut screen, &settings, 20);
+        assert_eq!(text(&screen), "");
+    }
+
+    #[test]
+    fn ticks_follow_the_decode_thresholds() {
+        let settings = Settings::default();
+        for (character_gap, word_gap) in [(3., 7.), (2., 5.), (3., 10.), (4., 12.)] {
+            let mut screen = WritingScreen::new(&settings);
+            screen.decoder.set_gaps(character_gap, word_gap);
+            assert_eq!(screen.max_ticks(), word_gap as usize);
+            key_element(&mut screen, &settings, 1);
+            // The character is decoded as the indicator reaches the character gap,
+            // and the word as it runs out.
+            for _ in 0..screen.dit_duration.as_millis() * (word_gap as u128 + 3) {
+                screen.step(FRAME, &KeyEvents::default(), &settings, &mut None);
+                let ticks = screen.ticks;
+                assert!(ticks <= screen.max_ticks());
+                assert_eq!(
+                    text(&screen).starts_with('E'),
+                    ticks >= character_gap as usize,
+                    "{} ticks with gaps of {} and {}",
+                    ticks,
+                    character_gap,
+                    word_gap
+                );
+                assert_eq!(
+                    text(&screen).ends_with(' '),
+                    ticks == screen.max_ticks(),
+                    "{} ticks with gaps of {} and {}",
+                    ticks,
+                    character_gap,
+                    word_gap
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn taps_within_one_frame_key_dits() {
+        let settings = Settings::default();
+        let mut screen = WritingScreen::new(&settings);
+        let key = settings.bindings.key(Action::StraightKey);
+        for _ in 0..3 {
+            step_with(&mut screen, &settings, &[key], &[key]);
+            assert!(!screen.pressed);
+            wait(&mut screen, &settings, 1);
+        }
+        wait(&mut screen, &settings, 3);
+        assert_eq!(text(&screen), "S");
+    }
+
+    #[test]
+    fn release_and_press_within_one_frame_keep_the_key_down() {
+        let settings = Settings::default();
+        let mut screen = WritingScreen::new(&settings);
+        let key = settings.bindings.key(Action::StraightKey);
+        step_with(&mut screen, &settings, &[key], &[]);
+        wait(&mut screen, &settings, 1);
+        step_with(&mut screen, &settings, &[key], &[key]);
+        assert!(screen.pressed);
+        wait(&mut screen, &settings, 3);
+        step_with(&mut screen, &settings, &[], &[key]);
+        wait(&mut screen, &settings, 3);
+        assert_eq!(text(&screen), "A");
+    }
+
+    #[test]
+    fn aa_starts_a_new_line() {
+        let settings = Settings::default();
+        let mut screen = WritingScreen::new(&settings);
+        key_morse(&mut screen, &settings, "....");
+        wait(&mut screen, &settings, 2);
+        key_morse(&mut screen, &settings, "..");
+        wait(&mut screen, &settings, 2);
+        key_morse(&mut screen, &settings, ".-.-");
+        // The new line ends the word, the silence doesn't add a space after it.
+        wait(&mut screen, &settings, 10);
+        key_morse(&mut screen, &settings, "-");
+        wait(&mut screen, &settings, 2);
+        assert_eq!(text(&screen), "HI\nT");
+        assert!(!text(&screen).contains("<AA>"));
+    }
+
+    #[test]
+    fn keys_sos_with_the_straight_key() {
+        let settings = Settings::default();
+        let mut screen = WritingScreen::new(&settings);
+        for morse in ["...", "---", "..."] {
+            key_morse(&mut screen, &settings, morse);
+            wait(&mut screen, &settings, 2);
+        }
+        wait(&mut screen, &settings, 4);
+        assert_eq!(text(&screen), "SOS ");
+    }
+
+    #[test]
+    fn keys_cq_with_the_paddles() {
+        for keyer_mode in [KeyerMode::IambicA, KeyerMode::IambicB] {
+            let settings = Settings {
+                keyer_mode,
+                ..Settings::default()
+            };
+            let mut screen = WritingScreen::new(&settings);
+            key_paddles(&mut screen, &settings, "-.-.");
+            wait(&mut screen, &settings, 2);
+            key_paddles(&mut screen, &settings, "--.-");
+            wait(&mut screen, &settings, 6);
+            assert_eq!(text(&screen), "CQ ", "keyed with {:?}", keyer_mode);
+        }
+    }
+
+    /// Move the speed slider to `wpm`, the way the settings panel does.
+    fn slide_wpm(screen: &mut WritingScreen, settings: &mut Settings, wpm: u8) {
+        settings.wpm = wpm;
+        screen.normalize_values(settings);
+    }
+
+    #[test]
+    fn speed_change_in_a_gap_keeps_the_character() {
+        for (from, to) in [(10, 30), (30, 10), (20, 25)] {
+            let mut settings = Settings {
+                wpm: from,
+                ..Settings::default()
+            };
+            let mut screen = WritingScreen::new(&settings);
+            key_morse(&mut screen, &settings, "-.");
+            // Halfway through an element gap, which doesn't end the character at the new speed.
+            slide_wpm(&mut screen, &mut settings, to);
+            key_morse(&mut screen, &settings, "-");
+            wait(&mut screen, &settings, 4);
+            assert_eq!(text(&screen), "K", "from {} to {} WPM", from, to);
+        }
+    }
+
+    #[test]
+    fn speed_change_during_an_element_keeps_the_character() {
+        for (from, to) in [(10, 30), (30, 10), (20, 25)] {
+            let mut settings = Settings {
+                wpm: from,
+                ..Settings::default()
+            };
+            let mut screen = WritingScreen::new(&settings);
+            key_morse(&mut screen, &settings, "-.");
+            // A dash held for two dits at the old speed and one at the new.
+            let key = settings.bindings.key(Action::StraightKey);
+            step_with(&mut screen, &settings, &[key], &[]);
+            wait(&mut screen, &settings, 2);
+            slide_wpm(&mut screen, &mut settings, to);
+            wait(&mut screen, &settings, 1);
+            step_with(&mut screen, &settings, &[], &[key]);
+            wait(&mut screen, &settings, 4);
+            assert_eq!(text(&screen), "K", "from {} to {} WPM", from, to);
+        }
     }
 }
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::inputs::{Action, KeyBindings, KeyEvents};
+
+/// How often the lines of the port are read
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Input line of a serial port a key or paddle is wired to.
+///
+/// DTR and RTS are outputs, they're held high to supply the key,
+/// which closes onto one of these lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerialLine {
+    Cts,
+    Dsr,
+    Dcd,
+}
+
+impl SerialLine {
+    pub const ALL: [SerialLine; 3] = [SerialLine::Cts, SerialLine::Dsr, SerialLine::Dcd];
+
+    fn index(self) -> usize {
+        match self {
+            SerialLine::Cts => 0,
+            SerialLine::Dsr => 1,
+            SerialLine::Dcd => 2,
+        }
+    }
+}
+
+impl fmt::Display for SerialLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerialLine::Cts => write!(f, "CTS"),
+            SerialLine::Dsr => write!(f, "DSR"),
+            SerialLine::Dcd => write!(f, "DCD"),
+        }
+    }
+}
+
+/// Lines of the serial port that key the straight key and the paddles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerialMapping {
+    pub straight_key: SerialLine,
+    pub dot_paddle: SerialLine,
+    pub dash_paddle: SerialLine,
+}
+
+impl Default for SerialMapping {
+    fn default() -> Self {
+        Self {
+            straight_key: SerialLine::Cts,
+            dot_paddle: SerialLine::Cts,
+            dash_paddle: SerialLine::Dsr,
+        }
+    }
+}
+
+impl SerialMapping {
+    fn actions(&self) -> [(Action, SerialLine); 3] {
+        [
+            (Action::StraightKey, self.straight_key),
+            (Action::DotPaddle, self.dot_paddle),
+            (Action::DashPaddle, self.dash_paddle),
+        ]
+    }
+}
+
+/// Names of the serial ports
+pub fn list_ports() -> Vec<String> {
+    match serialport::available_ports() {
+        Ok(ports) => ports.into_iter().map(|port| port.port_name).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to list serial ports: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Keying from the serial port selected in the settings, read on a background thread
+pub struct SerialKeying {
+    /// Port the thread was started for
+    port: Option<String>,
+    /// Tells the thread to close the port
+    stop: Arc<AtomicBool>,
+    /// States of the CTS, DSR and DCD lines whenever one changes
+    receiver: Option<Receiver<[bool; 3]>>,
+    /// Whether the straight key and the paddles are held
+    held: [bool; 3],
+    error: Option<String>,
+}
+
+impl SerialKeying {
+    pub fn new() -> Self {
+        Self {
+            port: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            receiver: None,
+            held: [false; 3],
+            error: None,
+        }
+    }
+
+    /// Why the selected port couldn't be opened.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Follow the port selected in the settings, opening it when it changes.
+    pub fn select(&mut self, ctx: &egui::Context, port: Option<&str>) {
+        if self.port.as_deref() == port {
+            return;
+        }
+        self.stop.store(true, Ordering::Relaxed);
+        self.port = port.map(str::to_string);
+        self.receiver = None;
+        self.held = [false; 3];
+        self.error = None;
+        if let Some(name) = port
+            && let Err(err) = self.open(ctx, name)
+        {
+            tracing::warn!("{}", err);
+            self.error = Some(err);
+        }
+    }
+
+    fn open(&mut self, ctx: &egui::Context, name: &str) -> Result<(), String> {
+        let mut port = serialport::new(name, 9600)
+            .open()
+            .map_err(|e| format!("Failed to open serial port \"{}\": {}", name, e))?;
+        // Supply the key from the output lines.
+        port.write_data_terminal_ready(true)
+            .and_then(|_| port.write_request_to_send(true))
+            .map_err(|e| format!("Failed to set the lines of \"{}\": {}", name, e))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        self.stop = stop.clone();
+        self.receiver = Some(receiver);
+        let ctx = ctx.clone();
+        let name = name.to_string();
+        thread::spawn(move || {
+            let mut last = [false; 3];
+            while !stop.load(Ordering::Relaxed) {
+                let lines = port.read_clear_to_send().and_then(|cts| {
+                    Ok([
+                        cts,
+                        port.read_data_set_ready()?,
+                        port.read_carrier_detect()?,
+                    ])
+                });
+                match lines {
+                    Ok(lines) if lines != last => {
+                        last = lines;
+                        if sender.send(lines).is_err() {
+                            break;
+                        }
+                        // The app idles between inputs, the port isn't one egui sees.
+                        ctx.request_repaint();
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!("Stopped reading serial port \"{}\": {}", name, e);
+                        break;
+                    }
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+        Ok(())
+    }
+
+    /// Add the line changes read since the last call as presses and releases
+    /// of the keys bound to the actions they're mapped to.
+    pub fn key_events(
+        &mut self,
+        mapping: &SerialMapping,
+        bindings: &KeyBindings,
+        events: &mut KeyEvents,
+    ) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        for lines in receiver.try_iter() {
+            for (index, (action, line)) in mapping.actions().into_iter().enumerate() {
+                let on = lines[line.index()];
+                if self.held[index] == on {
+                    continue;
+                }
+                self.held[index] = on;
+                if on {
+                    events.pressed.push(bindings.key(action));
+                } else {
+                    events.released.push(bindings.key(action));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SerialKeying {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
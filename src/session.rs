@@ -0,0 +1,205 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+use morset::{
+    encoder::Timing,
+    utils::{CharacterTable, wpm_to_dit_duration},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{keyer::KeyerMode, settings::Settings};
+
+/// Version written into saved sessions.
+///
+/// Bump it when the format changes in a way older versions can't read,
+/// new optional fields only need a `#[serde(default)]`.
+pub static SESSION_VERSION: u32 = 1;
+
+/// Something the user keyed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementKind {
+    Dit,
+    Dash,
+    CharacterGap,
+    WordGap,
+}
+
+impl ElementKind {
+    pub fn is_tone(self) -> bool {
+        matches!(self, ElementKind::Dit | ElementKind::Dash)
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ElementKind::Dit => ".",
+            ElementKind::Dash => "-",
+            ElementKind::CharacterGap => " ",
+            ElementKind::WordGap => " / ",
+        }
+    }
+}
+
+/// Element with the time it started since the beginning of the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedElement {
+    pub kind: ElementKind,
+    pub start_ms: u64,
+    pub duration_ms: u64,
+}
+
+impl RecordedElement {
+    pub fn start(&self) -> Duration {
+        Duration::from_millis(self.start_ms)
+    }
+
+    pub fn end(&self) -> Duration {
+        Duration::from_millis(self.start_ms + self.duration_ms)
+    }
+}
+
+/// Keying saved to a file, along with the settings it was keyed with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    pub wpm: u8,
+    pub keyer_mode: KeyerMode,
+    pub frequency: usize,
+    pub elements: Vec<RecordedElement>,
+}
+
+impl Session {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|err| err.to_string())
+    }
+
+    /// Read a saved session, refusing ones written by a newer version.
+    pub fn from_json(bytes: &[u8]) -> Result<Self, String> {
+        let session = serde_json::from_slice::<Self>(bytes).map_err(|err| err.to_string())?;
+        if session.version > SESSION_VERSION {
+            return Err(format!(
+                "Session format version {} is newer than the supported {}",
+                session.version, SESSION_VERSION
+            ));
+        }
+        Ok(session)
+    }
+
+    /// Tone on and off durations to play the session back as it was keyed.
+    pub fn signal(&self) -> Vec<(bool, Duration)> {
+        let mut signal = Vec::new();
+        let mut position = Duration::ZERO;
+        for element in self
+            .elements
+            .iter()
+            .filter(|element| element.kind.is_tone())
+        {
+            if element.start() > position {
+                signal.push((false, element.start() - position));
+            }
+            signal.push((true, element.end() - element.start()));
+            position = position.max(element.end());
+        }
+        signal
+    }
+
+    /// Tone on and off durations to play the session back with perfect timing
+    /// at its speed, keeping the elements and where the gaps fell.
+    pub fn perfect_signal(&self, weight: f32) -> Vec<(bool, Duration)> {
+        let mut timings = Vec::new();
+        let mut gap = None;
+        for element in &self.elements {
+            let units = match element.kind {
+                ElementKind::Dit => 1,
+                ElementKind::Dash => 3,
+                ElementKind::CharacterGap => {
+                    gap = Some(3);
+                    continue;
+                }
+                ElementKind::WordGap => {
+                    gap = Some(7);
+                    continue;
+                }
+            };
+            match gap.take() {
+                Some(units) => timings.push(Timing { on: false, units }),
+                None if !timings.is_empty() => timings.push(Timing {
+                    on: false,
+                    units: 1,
+                }),
+                None => {}
+            }
+            timings.push(Timing { on: true, units });
+        }
+        let dit_duration = wpm_to_dit_duration(self.wpm);
+        timings
+            .into_iter()
+            .map(|timing| (timing.on, timing.duration(dit_duration, weight)))
+            .collect()
+    }
+
+    /// Decode the keyed elements into text.
+    pub fn text(&self, characters: &CharacterTable) -> String {
+        let mut text = String::new();
+        let mut sequence = String::new();
+        for element in &self.elements {
+            if element.kind.is_tone() {
+                sequence.push_str(element.kind.symbol());
+                continue;
+            }
+            text.extend(characters.morse_to_char(&sequence));
+            sequence.clear();
+            if element.kind == ElementKind::WordGap {
+                text.push(' ');
+            }
+        }
+        text.extend(characters.morse_to_char(&sequence));
+        text
+    }
+}
+
+/// Collects the elements keyed on the Writing screen
+pub struct SessionRecorder {
+    started: Instant,
+    elements: Vec<RecordedElement>,
+}
+
+impl SessionRecorder {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            elements: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Record an element that has just ended after lasting `duration`.
+    pub fn record(&mut self, kind: ElementKind, duration: Duration) {
+        let end = self.started.elapsed();
+        let start = end.saturating_sub(duration);
+        self.elements.push(RecordedElement {
+            kind,
+            start_ms: start.as_millis() as u64,
+            duration_ms: duration.as_millis() as u64,
+        });
+    }
+
+    pub fn session(&self, settings: &Settings) -> Session {
+        Session {
+            version: SESSION_VERSION,
+            wpm: settings.wpm,
+            keyer_mode: settings.keyer_mode,
+            frequency: settings.frequency,
+            elements: self.elements.clone(),
+        }
+    }
+}
@@ -0,0 +1,616 @@
+use std::fmt;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+use morset::{
+    consts::Alphabet,
+    decoder::CustomProSign,
+    utils::{CharacterTable, DEFAULT_DASH_THRESHOLD, farnsworth_spacing, wpm_to_dit_duration},
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+use crate::gamepad::GamepadMapping;
+#[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+use crate::midi::MidiMapping;
+#[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+use crate::serial_key::SerialMapping;
+use crate::{
+    audio::{AudioManager, Waveform},
+    callsign::Region,
+    code_groups::{GroupCharacters, MAX_CODE_GROUPS, MIN_CODE_GROUPS},
+    custom_alphabet::CustomAlphabet,
+    exchange::ExchangeFormat,
+    i18n::{self, Language},
+    inputs::KeyBindings,
+    keyer::KeyerMode,
+    koch,
+    records::{CharacterAccuracy, PracticeLog, Records},
+    text_source::{TextGenerator, TextSource},
+    transcript::DEFAULT_HISTORY_LIMIT,
+};
+
+pub static MAX_WPM: u8 = 40;
+pub static MIN_WPM: u8 = 1;
+
+pub static MAX_FREQUENCY: usize = 1200;
+pub static MIN_FREQUENCY: usize = 300;
+
+/// Common sidetone pitches offered as presets
+pub static FREQUENCY_PRESETS: [usize; 4] = [500, 600, 700, 800];
+
+/// Most frequencies the user can save as presets
+pub static MAX_CUSTOM_FREQUENCIES: usize = 8;
+
+pub static MAX_DASH_THRESHOLD: f32 = 3.0;
+pub static MIN_DASH_THRESHOLD: f32 = 1.2;
+
+pub static MAX_DECODE_TOLERANCE: usize = 2;
+
+/// Most prosigns the user can add to the decoder
+pub static MAX_CUSTOM_PROSIGNS: usize = 20;
+
+pub static MAX_HISTORY_LIMIT: usize = 10000;
+pub static MIN_HISTORY_LIMIT: usize = 100;
+
+pub static MAX_VOLUME: usize = 100;
+pub static MIN_VOLUME: usize = 0;
+
+pub static MAX_FADING_DEPTH: usize = 100;
+pub static MIN_FADING_DEPTH: usize = 10;
+
+pub static MAX_PITCH_SPREAD: usize = MAX_FREQUENCY - MIN_FREQUENCY;
+pub static MIN_PITCH_SPREAD: usize = 50;
+
+pub static MAX_ADAPTIVE_STEP: u8 = 5;
+pub static MIN_ADAPTIVE_STEP: u8 = 1;
+
+pub static MAX_ADAPTIVE_STREAK: usize = 20;
+pub static MIN_ADAPTIVE_STREAK: usize = 1;
+
+pub static MAX_SNR: i32 = 30;
+pub static MIN_SNR: i32 = 0;
+
+pub static MAX_WEIGHT: u8 = 75;
+pub static MIN_WEIGHT: u8 = 25;
+
+pub static MAX_PAN: f32 = 1.0;
+pub static MIN_PAN: f32 = -1.0;
+
+/// Ramps shorter than a few milliseconds click, longer ones blur fast characters
+pub static MAX_RAMP_TIME: u32 = 20;
+pub static MIN_RAMP_TIME: u32 = 0;
+
+/// Zoom of the whole interface
+pub static MAX_UI_SCALE: f32 = 2.0;
+pub static MIN_UI_SCALE: f32 = 0.75;
+
+/// Font size of the transcript, in points before the interface scale
+pub static MAX_TRANSCRIPT_SIZE: f32 = 96.0;
+pub static MIN_TRANSCRIPT_SIZE: f32 = 16.0;
+
+/// Minutes of the practice session goal, zero for none
+pub static MAX_SESSION_GOAL: u8 = 120;
+
+pub static MAX_METRONOME_VOLUME: usize = 100;
+pub static MIN_METRONOME_VOLUME: usize = 5;
+
+/// Dits in a word, the length of PARIS with the gap after it
+static DITS_PER_WORD: u32 = 50;
+
+/// How often the metronome clicks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MetronomeMode {
+    #[default]
+    Off,
+    Dit,
+    Word,
+}
+
+impl MetronomeMode {
+    pub const ALL: [MetronomeMode; 3] =
+        [MetronomeMode::Off, MetronomeMode::Dit, MetronomeMode::Word];
+}
+
+impl fmt::Display for MetronomeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetronomeMode::Off => write!(f, "Off"),
+            MetronomeMode::Dit => write!(f, "Every dit"),
+            MetronomeMode::Word => write!(f, "Every word"),
+        }
+    }
+}
+
+/// Color scheme of the interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Theme {
+    /// Follow the operating system, or the browser on the web
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    pub fn preference(self) -> egui::ThemePreference {
+        match self {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::Dark => egui::ThemePreference::Dark,
+        }
+    }
+
+    /// Switch the interface over to the theme.
+    pub fn apply(self, ctx: &egui::Context) {
+        ctx.set_theme(self.preference());
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::System => write!(f, "System"),
+            Theme::Light => write!(f, "Light"),
+            Theme::Dark => write!(f, "Dark"),
+        }
+    }
+}
+
+/// Settings shared by all screens and kept between launches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: Theme,
+    /// Zoom of the interface, the tick indicator included
+    pub ui_scale: f32,
+    /// Draw the tick indicator in the text colors instead of the palette
+    pub high_contrast: bool,
+    /// Font size of the transcript, on top of the interface scale
+    pub transcript_size: f32,
+    pub wpm: u8,
+    /// Slow speed switched to with one key to copy something hard
+    pub qrs_wpm: u8,
+    /// Fast speed switched back to with one key
+    pub qrq_wpm: u8,
+    /// Speed the Listening screen sends at, apart from the keying speed
+    pub listening_wpm: u8,
+    /// Stretch the gaps of listening practice to the Farnsworth speed
+    pub farnsworth: bool,
+    /// Overall speed of listening practice with Farnsworth timing
+    pub farnsworth_wpm: u8,
+    /// Slow listening practice down after wrong answers and speed it up after right ones
+    pub adaptive_speed: bool,
+    /// WPM the adaptive speed changes by at once
+    pub adaptive_step: u8,
+    /// Right answers in a row before the adaptive speed goes up
+    pub adaptive_streak: usize,
+    /// Range the adaptive speed stays in
+    pub adaptive_min_wpm: u8,
+    pub adaptive_max_wpm: u8,
+    pub frequency: usize,
+    /// Frequencies saved by the user next to the presets
+    pub custom_frequencies: Vec<usize>,
+    pub volume: usize,
+    pub waveform: Waveform,
+    /// Time the tone takes to rise when keyed and to fall when released, in milliseconds
+    pub rise_time: u32,
+    pub fall_time: u32,
+    /// Position of the sidetone from -1 (left) to 1 (right)
+    pub pan: f32,
+    /// Selected output device, `None` meaning the system default
+    pub output_device: Option<String>,
+    pub bindings: KeyBindings,
+    pub keyer_mode: KeyerMode,
+    /// Send dashes with the dot paddle and dots with the dash paddle
+    pub swap_paddles: bool,
+    /// Straight key presses at least this long (in dits) are dashes
+    pub dash_threshold: f32,
+    /// How many wrong elements are tolerated when decoding, 0 being strict
+    pub decode_tolerance: usize,
+    /// Prosigns added by the user to the decoder
+    pub custom_prosigns: Vec<CustomProSign>,
+    pub show_timestamps: bool,
+    /// Maximum number of characters kept in the transcript
+    pub history_limit: usize,
+    /// Show onscreen keys for devices without a keyboard
+    pub show_touch_keys: bool,
+    pub alphabet: Alphabet,
+    /// Language of the interface
+    pub language: Language,
+    /// Encode and decode accented letters
+    pub extended_characters: bool,
+    /// Characters loaded from a file on top of or instead of the built-in ones
+    pub custom_alphabet: Option<CustomAlphabet>,
+    /// Lesson reached in the Koch trainer
+    pub koch_lesson: usize,
+    /// Percentage of a dit plus its gap during which the tone is on, 50 being standard
+    pub weight: u8,
+    /// Let the tone fade in and out on the Listening screen
+    pub fading: bool,
+    /// How far the tone fades, in percent
+    pub fading_depth: usize,
+    /// Play band noise under the tone on the Listening screen
+    pub noise: bool,
+    /// Loudness of the tone over the noise, in dB
+    pub snr: i32,
+    /// Key a second station at another pitch on the Listening screen
+    pub interference: bool,
+    /// Send every listening drill item at a random pitch
+    pub random_pitch: bool,
+    /// Width of the range random pitches are picked from, centered on the frequency
+    pub pitch_spread: usize,
+    /// Flash a lamp in time with the tone while practicing
+    pub flash: bool,
+    /// Colors of the lamp while the tone is on and off
+    pub flash_on_color: [u8; 3],
+    pub flash_off_color: [u8; 3],
+    /// Vibrate the device in time with the tone, on mobile browsers
+    pub vibration: bool,
+    /// Practice without opening any audio output
+    pub silent: bool,
+    /// MIDI device keying the straight key and the paddles
+    #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+    pub midi_device: Option<String>,
+    #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+    pub midi_mapping: MidiMapping,
+    /// Serial port a key or paddle is wired to
+    #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+    pub serial_port: Option<String>,
+    #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+    pub serial_mapping: SerialMapping,
+    /// Gamepad buttons that key the straight key and the paddles
+    #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+    pub gamepad_mapping: GamepadMapping,
+    /// Serial port of a WinKeyer that sends what's keyed and typed
+    #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+    pub winkeyer_port: Option<String>,
+    /// WebSocket relay shared with a practice partner
+    #[cfg(feature = "network")]
+    pub relay_url: String,
+    /// Say the name of every Koch character after its morse
+    #[cfg(feature = "speech")]
+    pub announce_characters: bool,
+    /// Best results of the listening drills
+    pub records: Records,
+    /// Practice time of every day
+    pub practice: PracticeLog,
+    /// Answers of every character over all sessions
+    pub accuracy: CharacterAccuracy,
+    /// Minutes after which the session summary is shown, zero for none
+    pub session_goal: u8,
+    /// What the sending drill and head copy send
+    pub text_source: TextSource,
+    /// Limit drill text to the characters unlocked in the Koch trainer
+    pub unlocked_only: bool,
+    /// Regions the prefixes of drill callsigns come from
+    pub callsign_regions: Vec<Region>,
+    /// Number of code groups sent in a run
+    pub code_group_count: usize,
+    pub code_group_characters: GroupCharacters,
+    /// Speed code groups are sent at
+    pub code_group_wpm: u8,
+    /// What the exchange trainer sends after the signal report
+    pub exchange_format: ExchangeFormat,
+    /// Send T, N and A for 0, 9 and 1 in exchanges, like contesters do
+    pub cut_numbers: bool,
+    /// Click at the speed on the Writing screen
+    pub metronome: MetronomeMode,
+    /// Loudness of the clicks compared to the tone, in percent
+    pub metronome_volume: usize,
+    /// Characters encoded and decoded, rebuilt from the settings above by
+    /// [`Settings::apply_characters`]
+    #[serde(skip)]
+    pub characters: CharacterTable,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            ui_scale: 1.0,
+            high_contrast: false,
+            transcript_size: 32.0,
+            wpm: 10,
+            qrs_wpm: 5,
+            qrq_wpm: 20,
+            listening_wpm: 15,
+            farnsworth: false,
+            farnsworth_wpm: 10,
+            adaptive_speed: false,
+            adaptive_step: 1,
+            adaptive_streak: 5,
+            adaptive_min_wpm: 5,
+            adaptive_max_wpm: 30,
+            frequency: 550,
+            custom_frequencies: Vec::new(),
+            volume: 45,
+            waveform: Waveform::default(),
+            rise_time: 5,
+            fall_time: 5,
+            pan: 0.,
+            output_device: None,
+            bindings: KeyBindings::default(),
+            keyer_mode: KeyerMode::default(),
+            swap_paddles: false,
+            dash_threshold: DEFAULT_DASH_THRESHOLD,
+            decode_tolerance: 1,
+            custom_prosigns: Vec::new(),
+            show_timestamps: false,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            show_touch_keys: false,
+            alphabet: Alphabet::default(),
+            language: Language::default(),
+            extended_characters: false,
+            custom_alphabet: None,
+            koch_lesson: 1,
+            weight: 50,
+            fading: false,
+            fading_depth: 50,
+            noise: false,
+            snr: 20,
+            interference: false,
+            random_pitch: false,
+            pitch_spread: 200,
+            flash: false,
+            flash_on_color: [255, 220, 120],
+            flash_off_color: [24, 24, 24],
+            vibration: false,
+            silent: false,
+            #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+            midi_device: None,
+            #[cfg(all(feature = "midi", not(target_arch = "wasm32")))]
+            midi_mapping: MidiMapping::default(),
+            #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+            serial_port: None,
+            #[cfg(all(feature = "serial", not(target_arch = "wasm32")))]
+            serial_mapping: SerialMapping::default(),
+            #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+            gamepad_mapping: GamepadMapping::default(),
+            #[cfg(all(feature = "winkeyer", not(target_arch = "wasm32")))]
+            winkeyer_port: None,
+            #[cfg(feature = "network")]
+            relay_url: String::new(),
+            #[cfg(feature = "speech")]
+            announce_characters: false,
+            records: Records::default(),
+            practice: PracticeLog::default(),
+            accuracy: CharacterAccuracy::default(),
+            session_goal: 0,
+            text_source: TextSource::default(),
+            unlocked_only: false,
+            callsign_regions: Region::ALL.to_vec(),
+            code_group_count: 10,
+            code_group_characters: GroupCharacters::default(),
+            code_group_wpm: 15,
+            exchange_format: ExchangeFormat::default(),
+            cut_numbers: false,
+            metronome: MetronomeMode::default(),
+            metronome_volume: 30,
+            characters: CharacterTable::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// This function just verifies that all values are within bounds.
+    pub fn normalize(&mut self) {
+        self.wpm = self.wpm.clamp(MIN_WPM, MAX_WPM);
+        self.qrs_wpm = self.qrs_wpm.clamp(MIN_WPM, MAX_WPM);
+        self.qrq_wpm = self.qrq_wpm.clamp(MIN_WPM, MAX_WPM);
+        self.listening_wpm = self.listening_wpm.clamp(MIN_WPM, MAX_WPM);
+        self.farnsworth_wpm = self.farnsworth_wpm.clamp(MIN_WPM, self.listening_wpm);
+        self.adaptive_step = self
+            .adaptive_step
+            .clamp(MIN_ADAPTIVE_STEP, MAX_ADAPTIVE_STEP);
+        self.adaptive_streak = self
+            .adaptive_streak
+            .clamp(MIN_ADAPTIVE_STREAK, MAX_ADAPTIVE_STREAK);
+        self.adaptive_min_wpm = self.adaptive_min_wpm.clamp(MIN_WPM, MAX_WPM);
+        self.adaptive_max_wpm = self.adaptive_max_wpm.clamp(self.adaptive_min_wpm, MAX_WPM);
+        self.frequency = self.frequency.clamp(MIN_FREQUENCY, MAX_FREQUENCY);
+        for frequency in &mut self.custom_frequencies {
+            *frequency = (*frequency).clamp(MIN_FREQUENCY, MAX_FREQUENCY);
+        }
+        self.custom_frequencies.dedup();
+        self.custom_frequencies.truncate(MAX_CUSTOM_FREQUENCIES);
+        self.callsign_regions.dedup();
+        if self.callsign_regions.is_empty() {
+            self.callsign_regions = Region::ALL.to_vec();
+        }
+        self.volume = self.volume.clamp(MIN_VOLUME, MAX_VOLUME);
+        self.weight = self.weight.clamp(MIN_WEIGHT, MAX_WEIGHT);
+        self.rise_time = self.rise_time.clamp(MIN_RAMP_TIME, MAX_RAMP_TIME);
+        self.fall_time = self.fall_time.clamp(MIN_RAMP_TIME, MAX_RAMP_TIME);
+        self.pan = self.pan.clamp(MIN_PAN, MAX_PAN);
+        self.fading_depth = self.fading_depth.clamp(MIN_FADING_DEPTH, MAX_FADING_DEPTH);
+        self.snr = self.snr.clamp(MIN_SNR, MAX_SNR);
+        self.code_group_count = self
+            .code_group_count
+            .clamp(MIN_CODE_GROUPS, MAX_CODE_GROUPS);
+        self.code_group_wpm = self.code_group_wpm.clamp(MIN_WPM, MAX_WPM);
+        self.metronome_volume = self
+            .metronome_volume
+            .clamp(MIN_METRONOME_VOLUME, MAX_METRONOME_VOLUME);
+        self.pitch_spread = self.pitch_spread.clamp(MIN_PITCH_SPREAD, MAX_PITCH_SPREAD);
+        self.dash_threshold = self
+            .dash_threshold
+            .clamp(MIN_DASH_THRESHOLD, MAX_DASH_THRESHOLD);
+        self.decode_tolerance = self.decode_tolerance.min(MAX_DECODE_TOLERANCE);
+        self.custom_prosigns = std::mem::take(&mut self.custom_prosigns)
+            .into_iter()
+            .filter_map(|prosign| CustomProSign::new(&prosign.tag, &prosign.sequence).ok())
+            .collect();
+        self.custom_prosigns.truncate(MAX_CUSTOM_PROSIGNS);
+        self.history_limit = self
+            .history_limit
+            .clamp(MIN_HISTORY_LIMIT, MAX_HISTORY_LIMIT);
+        self.session_goal = self.session_goal.min(MAX_SESSION_GOAL);
+        self.ui_scale = self.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+        self.transcript_size = self
+            .transcript_size
+            .clamp(MIN_TRANSCRIPT_SIZE, MAX_TRANSCRIPT_SIZE);
+    }
+
+    /// Name of the quick speed being keyed at, if the speed is one of them.
+    pub fn quick_speed(&self) -> Option<&'static str> {
+        if self.wpm == self.qrs_wpm {
+            Some("QRS")
+        } else if self.wpm == self.qrq_wpm {
+            Some("QRQ")
+        } else {
+            None
+        }
+    }
+
+    pub fn dit_duration(&self) -> Duration {
+        wpm_to_dit_duration(self.wpm)
+    }
+
+    pub fn listening_dit_duration(&self) -> Duration {
+        wpm_to_dit_duration(self.listening_wpm)
+    }
+
+    /// Unit of the gaps between characters and words in listening practice,
+    /// `None` when they aren't stretched.
+    pub fn listening_spacing(&self) -> Option<Duration> {
+        (self.farnsworth && self.farnsworth_wpm < self.listening_wpm)
+            .then(|| farnsworth_spacing(self.listening_wpm, self.farnsworth_wpm))
+    }
+
+    /// Length of a practice session, if there's a goal.
+    pub fn session_goal(&self) -> Option<Duration> {
+        (self.session_goal > 0).then(|| Duration::from_secs(u64::from(self.session_goal) * 60))
+    }
+
+    /// Gain for the volume setting, following how loud it sounds rather than the amplitude,
+    /// so the lower half of the slider stays usable. 0 is silence and 100 full amplitude.
+    pub fn gain(&self) -> f32 {
+        (self.volume as f32 * 0.01).powi(2)
+    }
+
+    /// Weight as a fraction, see [`morset::encoder::Timing::duration`].
+    pub fn weight_ratio(&self) -> f32 {
+        self.weight as f32 * 0.01
+    }
+
+    /// Fading depth from 0 to 1, 0 when fading is turned off.
+    pub fn fading_ratio(&self) -> f32 {
+        if self.fading {
+            self.fading_depth as f32 * 0.01
+        } else {
+            0.
+        }
+    }
+
+    /// Loudness of the noise compared to the tone, 0 when noise is turned off.
+    pub fn noise_level(&self) -> f32 {
+        if self.noise {
+            10f32.powf(-self.snr as f32 / 20.)
+        } else {
+            0.
+        }
+    }
+
+    /// Pitch for a listening drill item, random around the frequency if enabled.
+    pub fn pick_pitch(&self) -> f32 {
+        if !self.random_pitch {
+            return self.frequency as f32;
+        }
+        let low = self
+            .frequency
+            .saturating_sub(self.pitch_spread / 2)
+            .max(MIN_FREQUENCY);
+        let high = (low + self.pitch_spread).min(MAX_FREQUENCY);
+        let low = high.saturating_sub(self.pitch_spread).max(MIN_FREQUENCY);
+        fastrand::usize(low..=high) as f32
+    }
+
+    /// Time between metronome clicks at the speed, `None` when it's off.
+    pub fn metronome_interval(&self) -> Option<Duration> {
+        match self.metronome {
+            MetronomeMode::Off => None,
+            MetronomeMode::Dit => Some(self.dit_duration()),
+            MetronomeMode::Word => Some(self.dit_duration() * DITS_PER_WORD),
+        }
+    }
+
+    /// Loudness of the metronome compared to the tone, from 0 to 1.
+    pub fn metronome_level(&self) -> f32 {
+        self.metronome_volume as f32 / 100.
+    }
+
+    /// Generator of drill text for the chosen source.
+    pub fn text_generator(&self) -> TextGenerator {
+        TextGenerator {
+            source: self.text_source,
+            characters: self.unlocked_only.then(|| koch::unlocked(self.koch_lesson)),
+            table: self.characters.clone(),
+            regions: self.callsign_regions.clone(),
+        }
+    }
+
+    /// Make the tone match the frequency, volume and waveform settings.
+    pub fn apply_tone(&self, audio: &mut AudioManager) {
+        audio.set_frequency(self.frequency as f32);
+        audio.set_volume(self.gain());
+        audio.set_waveform(self.waveform);
+        audio.set_ramp_ms(self.rise_time, self.fall_time);
+        audio.set_pan(self.pan);
+    }
+
+    /// Restore every setting to its default, keeping the progress of the Koch trainer,
+    /// the records, the practice log and the accuracy, and the key bindings if asked to.
+    pub fn reset(&mut self, keep_bindings: bool) {
+        let bindings = std::mem::take(&mut self.bindings);
+        *self = Self {
+            records: std::mem::take(&mut self.records),
+            practice: std::mem::take(&mut self.practice),
+            accuracy: std::mem::take(&mut self.accuracy),
+            koch_lesson: self.koch_lesson,
+            language: self.language,
+            ..Self::default()
+        };
+        if keep_bindings {
+            self.bindings = bindings;
+        }
+    }
+
+    pub fn apply_scale(&self, ctx: &egui::Context) {
+        ctx.set_zoom_factor(self.ui_scale);
+    }
+
+    /// Rebuild the character table from the alphabet and character settings.
+    pub fn apply_characters(&mut self) {
+        let (custom, replace) = match &self.custom_alphabet {
+            Some(custom) => (custom.characters.as_slice(), custom.replace),
+            None => (&[][..], false),
+        };
+        self.characters =
+            CharacterTable::new(self.alphabet, self.extended_characters, custom, replace);
+    }
+
+    pub fn apply_language(&self) {
+        i18n::set_language(self.language);
+    }
+
+    /// Restore the settings saved by a previous launch, if any.
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        storage
+            .and_then(|storage| eframe::get_value::<Self>(storage, eframe::APP_KEY))
+            .map(|mut settings| {
+                settings.normalize();
+                settings
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, self);
+    }
+}
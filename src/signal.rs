@@ -0,0 +1,535 @@
+use std::f32::consts::{PI, TAU};
+use std::io::Read;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+use crate::decoder::{Decoded, Decoder};
+use crate::utils::{CharacterTable, wpm_to_dit_duration};
+
+/// Length of the window the signal energy is measured over
+pub const ENVELOPE_WINDOW: Duration = Duration::from_millis(5);
+
+/// Part of the peak energy above which the tone is considered on
+pub const DEFAULT_THRESHOLD: f32 = 0.3;
+
+/// Samples analysed by the spectrum, a power of two
+pub const SPECTRUM_LEN: usize = 2048;
+
+/// Gaps at least this long (in dits) end a character.
+/// Halfway between the one dit element gap and the three dit character gap.
+const CHARACTER_BOUNDARY: f32 = 2.0;
+
+/// Gaps at least this long (in dits) end a word.
+/// Halfway between the three dit character gap and the seven dit word gap.
+const WORD_BOUNDARY: f32 = 5.0;
+
+/// How quickly the speed estimate follows newly observed elements
+const ADAPTATION_RATE: f32 = 0.25;
+
+/// Period of the tone being on or off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub on: bool,
+    pub duration: Duration,
+}
+
+/// Text decoded from a recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcription {
+    pub text: String,
+    /// Sending speed estimated from element lengths
+    pub wpm: Option<f32>,
+}
+
+/// Root mean square of the signal over consecutive windows.
+pub fn envelope(samples: &[f32], sample_rate: u32, window: Duration) -> Vec<f32> {
+    let window_len = ((sample_rate as f32 * window.as_secs_f32()) as usize).max(1);
+    samples
+        .chunks(window_len)
+        .map(|chunk| (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt())
+        .collect()
+}
+
+/// Split the envelope into on and off segments.
+///
+/// The tone is on when the energy exceeds `threshold` times the peak energy.
+pub fn segments(envelope: &[f32], window: Duration, threshold: f32) -> Vec<Segment> {
+    let peak = envelope.iter().copied().fold(0., f32::max);
+    if peak <= 0. {
+        return Vec::new();
+    }
+    let level = peak * threshold;
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for value in envelope {
+        let on = *value >= level;
+        match segments.last_mut() {
+            Some(last) if last.on == on => last.duration += window,
+            _ => segments.push(Segment {
+                on,
+                duration: window,
+            }),
+        }
+    }
+    segments
+}
+
+/// Estimate the dit length by splitting tone durations into short and long ones.
+pub fn estimate_dit(segments: &[Segment]) -> Option<Duration> {
+    let durations = segments
+        .iter()
+        .filter(|segment| segment.on)
+        .map(|segment| segment.duration.as_secs_f32())
+        .collect::<Vec<_>>();
+    let shortest = durations.iter().copied().reduce(f32::min)?;
+    let longest = durations.iter().copied().reduce(f32::max)?;
+
+    // If all elements are about the same length, they are most likely dits.
+    let boundary = if longest < shortest * 2. {
+        longest
+    } else {
+        (shortest + longest) / 2.
+    };
+    let dits = durations
+        .iter()
+        .copied()
+        .filter(|duration| *duration <= boundary)
+        .collect::<Vec<_>>();
+    Some(Duration::from_secs_f32(
+        dits.iter().sum::<f32>() / dits.len() as f32,
+    ))
+}
+
+/// Words per minute for the given dit length, using the PARIS standard.
+pub fn dit_to_wpm(dit: Duration) -> f32 {
+    1.2 / dit.as_secs_f32()
+}
+
+/// Power of the samples at evenly spaced frequencies, from 0 up to half the sample rate,
+/// the step being the sample rate divided by the number of samples.
+///
+/// The samples are Hann windowed and their number must be a power of two.
+/// A full scale sine has a power of about 1 at its frequency.
+pub fn power_spectrum(samples: &[f32]) -> Vec<f32> {
+    let len = samples.len();
+    debug_assert!(len.is_power_of_two());
+    let mut re: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| sample * (0.5 - 0.5 * (TAU * i as f32 / len as f32).cos()))
+        .collect();
+    let mut im = vec![0.; len];
+    fft(&mut re, &mut im);
+    // The window halves the amplitude, which the transform then scales by half the length.
+    let scale = (4. / len as f32).powi(2);
+    (0..len / 2)
+        .map(|k| (re[k] * re[k] + im[k] * im[k]) * scale)
+        .collect()
+}
+
+/// In place radix 2 Fourier transform.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let len = re.len();
+    // Bit reversed order, so the butterflies can work on neighbours
+    let mut j = 0;
+    for i in 1..len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut size = 2;
+    while size <= len {
+        let half = size / 2;
+        for k in 0..half {
+            let (sin, cos) = (-PI * k as f32 / half as f32).sin_cos();
+            for a in (k..len).step_by(size) {
+                let b = a + half;
+                let (tr, ti) = (re[b] * cos - im[b] * sin, re[b] * sin + im[b] * cos);
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        size *= 2;
+    }
+}
+
+/// Frequency and power of the strongest component of the spectrum, leaving out the lowest bin.
+/// The frequency is interpolated between the bins around the peak.
+pub fn spectrum_peak(power: &[f32], bin_width: f32) -> Option<(f32, f32)> {
+    let peak = (1..power.len()).max_by(|&a, &b| power[a].total_cmp(&power[b]))?;
+    let offset = match (power.get(peak - 1), power.get(peak + 1)) {
+        (Some(&below), Some(&above)) if below > 0. && above > 0. => {
+            let (a, b, c) = (below.ln(), power[peak].ln(), above.ln());
+            let curve = a - 2. * b + c;
+            if curve < 0. {
+                0.5 * (a - c) / curve
+            } else {
+                0.
+            }
+        }
+        _ => 0.,
+    };
+    Some(((peak as f32 + offset) * bin_width, power[peak]))
+}
+
+/// Running estimate of the sender's dit length.
+///
+/// Every element is classified as a dit or a dah against the current estimate,
+/// and the estimate is moved towards the dit length that element implies,
+/// so it follows the sender speeding up or slowing down.
+#[derive(Debug, Clone, Copy)]
+pub struct DitEstimator {
+    dit: f32,
+}
+
+impl DitEstimator {
+    pub fn new(dit: Duration) -> Self {
+        Self {
+            dit: dit.as_secs_f32(),
+        }
+    }
+
+    pub fn dit(&self) -> Duration {
+        Duration::from_secs_f32(self.dit)
+    }
+
+    pub fn reset(&mut self, dit: Duration) {
+        self.dit = dit.as_secs_f32();
+    }
+
+    /// Length of the duration in dit units.
+    pub fn units(&self, duration: Duration) -> f32 {
+        duration.as_secs_f32() / self.dit
+    }
+
+    /// Classify a tone of the given length and adapt the estimate to it.
+    pub fn element(&mut self, duration: Duration) -> char {
+        let duration = duration.as_secs_f32();
+        let (element, dit) = if duration / self.dit < CHARACTER_BOUNDARY {
+            ('.', duration)
+        } else {
+            ('-', duration / 3.)
+        };
+        self.dit += (dit - self.dit) * ADAPTATION_RATE;
+        element
+    }
+}
+
+/// Decode on/off segments into text, starting from the given dit length.
+///
+/// The dit length is adapted as the segments are decoded; its final value is returned along with the text.
+pub fn decode_segments(
+    segments: &[Segment],
+    dit: Duration,
+    tolerance: usize,
+    characters: &CharacterTable,
+) -> (String, Duration) {
+    let mut estimator = DitEstimator::new(dit);
+    let mut decoder = Decoder::new(tolerance, characters.clone());
+    decoder.set_gaps(CHARACTER_BOUNDARY, WORD_BOUNDARY);
+    let mut text = String::new();
+
+    for segment in segments {
+        if segment.on {
+            decoder.on_element(estimator.element(segment.duration) == '-');
+        } else {
+            decoder.on_gap(estimator.units(segment.duration));
+        }
+    }
+    decoder.on_gap(f32::INFINITY);
+    push_decoded(&mut decoder, &mut text);
+    (text.trim_end().to_string(), estimator.dit())
+}
+
+/// Append what the decoder has completed to the text.
+fn push_decoded(decoder: &mut Decoder, text: &mut String) {
+    while let Some(decoded) = decoder.take_decoded() {
+        match decoded {
+            Decoded::Char(ch) | Decoded::Guess(ch) => text.push(ch),
+            Decoded::ProSign(prosign) => text.push_str(prosign.tag()),
+            Decoded::Space => text.push(' '),
+            // No custom prosigns are given to the decoder here
+            Decoded::Custom(_) | Decoded::Unknown => {}
+        }
+    }
+}
+
+/// Decode a mono signal into text.
+pub fn transcribe(
+    samples: &[f32],
+    sample_rate: u32,
+    threshold: f32,
+    characters: &CharacterTable,
+) -> Transcription {
+    let envelope = envelope(samples, sample_rate, ENVELOPE_WINDOW);
+    let segments = segments(&envelope, ENVELOPE_WINDOW, threshold);
+    let Some(dit) = estimate_dit(&segments) else {
+        return Transcription {
+            text: String::new(),
+            wpm: None,
+        };
+    };
+    let (text, dit) = decode_segments(&segments, dit, 1, characters);
+    Transcription {
+        text,
+        wpm: Some(dit_to_wpm(dit)),
+    }
+}
+
+/// Decode a WAV recording of morse code into text.
+pub fn decode_wav<R: Read>(
+    reader: R,
+    characters: &CharacterTable,
+) -> Result<Transcription, String> {
+    let reader = hound::WavReader::new(reader).map_err(|e| format!("Invalid WAV file: {}", e))?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read WAV samples: {}", e))?,
+        hound::SampleFormat::Int => {
+            let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Failed to read WAV samples: {}", e))?
+        }
+    };
+
+    // Mix all channels down to mono.
+    let channels = usize::from(spec.channels.max(1));
+    let mono = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect::<Vec<_>>();
+
+    Ok(transcribe(
+        &mono,
+        spec.sample_rate,
+        DEFAULT_THRESHOLD,
+        characters,
+    ))
+}
+
+/// Decodes a live signal as its samples arrive
+pub struct LiveDecoder {
+    window_len: usize,
+    window_duration: Duration,
+    window_energy: f32,
+    window_count: usize,
+    /// Energy of the last complete window
+    level: f32,
+    /// Energy above which the tone is considered on
+    threshold: f32,
+    estimator: DitEstimator,
+    on: bool,
+    /// How long the signal has been in its current state
+    run: Duration,
+    decoder: Decoder,
+    text: String,
+}
+
+impl LiveDecoder {
+    /// Create a decoder for a mono signal expected to be sent at `wpm`.
+    pub fn new(sample_rate: u32, wpm: u8, threshold: f32, characters: CharacterTable) -> Self {
+        let window_len = ((sample_rate as f32 * ENVELOPE_WINDOW.as_secs_f32()) as usize).max(1);
+        Self {
+            window_len,
+            window_duration: Duration::from_secs_f32(window_len as f32 / sample_rate as f32),
+            window_energy: 0.,
+            window_count: 0,
+            level: 0.,
+            threshold,
+            estimator: DitEstimator::new(wpm_to_dit_duration(wpm)),
+            on: false,
+            run: Duration::ZERO,
+            decoder: {
+                let mut decoder = Decoder::new(1, characters);
+                decoder.set_gaps(CHARACTER_BOUNDARY, WORD_BOUNDARY);
+                decoder
+            },
+            text: String::new(),
+        }
+    }
+
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Restart the speed estimate from the given speed.
+    pub fn set_wpm(&mut self, wpm: u8) {
+        self.estimator.reset(wpm_to_dit_duration(wpm));
+    }
+
+    /// Current estimate of the sender's speed.
+    pub fn wpm(&self) -> f32 {
+        dit_to_wpm(self.estimator.dit())
+    }
+
+    /// Energy of the most recent part of the signal.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    pub fn push_samples(&mut self, samples: &[f32]) {
+        for sample in samples {
+            self.window_energy += sample * sample;
+            self.window_count += 1;
+            if self.window_count == self.window_len {
+                self.level = (self.window_energy / self.window_count as f32).sqrt();
+                self.window_energy = 0.;
+                self.window_count = 0;
+                self.push_window();
+            }
+        }
+    }
+
+    fn push_window(&mut self) {
+        let on = self.level >= self.threshold;
+        if on != self.on {
+            if self.on {
+                self.decoder
+                    .on_element(self.estimator.element(self.run) == '-');
+            }
+            self.on = on;
+            self.run = Duration::ZERO;
+        }
+        self.run += self.window_duration;
+
+        if self.on {
+            return;
+        }
+        self.decoder.on_gap(self.estimator.units(self.run));
+        push_decoded(&mut self.decoder, &mut self.text);
+    }
+
+    /// Take the text decoded since the last call.
+    pub fn take_text(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::timings;
+    use crate::utils::wpm_to_dit_duration;
+
+    /// Segments of the text sent with standard timing at the speed.
+    fn keyed(text: &str, wpm: u8) -> Vec<Segment> {
+        let dit = wpm_to_dit_duration(wpm);
+        timings(text, false, &CharacterTable::default())
+            .unwrap()
+            .into_iter()
+            .map(|timing| Segment {
+                on: timing.on,
+                duration: dit * timing.units,
+            })
+            .collect()
+    }
+
+    fn word_gap(wpm: u8) -> Segment {
+        Segment {
+            on: false,
+            duration: wpm_to_dit_duration(wpm) * 7,
+        }
+    }
+
+    fn assert_close(actual: Duration, expected: Duration) {
+        let ratio = actual.as_secs_f32() / expected.as_secs_f32();
+        assert!(
+            (0.9..1.1).contains(&ratio),
+            "{:?} is not close to {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn estimator_follows_the_dits_of_a_new_speed() {
+        let mut estimator = DitEstimator::new(wpm_to_dit_duration(12));
+        let fast = wpm_to_dit_duration(24);
+        for _ in 0..20 {
+            assert_eq!(estimator.element(fast), '.');
+        }
+        assert_close(estimator.dit(), fast);
+        let slow = wpm_to_dit_duration(15);
+        for _ in 0..20 {
+            estimator.element(slow);
+            assert_eq!(estimator.element(slow * 3), '-');
+        }
+        assert_close(estimator.dit(), slow);
+    }
+
+    #[test]
+    fn decodes_at_a_steady_speed() {
+        let (text, dit) = decode_segments(
+            &keyed("PARIS PARIS", 20),
+            wpm_to_dit_duration(20),
+            0,
+            &CharacterTable::default(),
+        );
+        assert_eq!(text, "PARIS PARIS");
+        assert_close(dit, wpm_to_dit_duration(20));
+    }
+
+    #[test]
+    fn decodes_from_a_wrong_starting_speed() {
+        let (text, dit) = decode_segments(
+            &keyed("PARIS PARIS", 15),
+            wpm_to_dit_duration(20),
+            0,
+            &CharacterTable::default(),
+        );
+        assert_eq!(text, "PARIS PARIS");
+        assert_close(dit, wpm_to_dit_duration(15));
+    }
+
+    #[test]
+    fn decodes_through_speed_changes() {
+        let mut segments = keyed("CQ CQ DE", 15);
+        segments.push(word_gap(15));
+        segments.extend(keyed("TEST TEST", 20));
+        segments.push(word_gap(20));
+        segments.extend(keyed("PARIS", 17));
+        let (text, dit) = decode_segments(
+            &segments,
+            wpm_to_dit_duration(15),
+            0,
+            &CharacterTable::default(),
+        );
+        assert_eq!(text, "CQ CQ DE TEST TEST PARIS");
+        assert_close(dit, wpm_to_dit_duration(17));
+    }
+
+    #[test]
+    fn catches_up_within_a_word_after_a_jump_in_speed() {
+        let mut segments = keyed("CQ CQ", 15);
+        segments.push(word_gap(15));
+        segments.extend(keyed("TEST TEST TEST", 25));
+        let (text, dit) = decode_segments(
+            &segments,
+            wpm_to_dit_duration(15),
+            0,
+            &CharacterTable::default(),
+        );
+        // The first dahs at the new speed are still as short as dits at the old one.
+        assert!(text.starts_with("CQ CQ "), "{}", text);
+        assert!(text.ends_with(" TEST TEST"), "{}", text);
+        assert_close(dit, wpm_to_dit_duration(25));
+    }
+}
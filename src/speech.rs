@@ -0,0 +1,113 @@
+//! Characters spoken by name after their morse, to bind the sound to the character.
+//!
+//! Native builds speak through the system's speech engine,
+//! the web build through the browser's Web Speech API.
+
+/// How a character is spoken, punctuation by its name
+pub fn character_name(ch: char) -> String {
+    let name = match ch {
+        '.' => "period",
+        ',' => "comma",
+        '?' => "question mark",
+        '\'' => "apostrophe",
+        '!' => "exclamation mark",
+        '/' => "slash",
+        '(' => "open parenthesis",
+        ')' => "close parenthesis",
+        '&' => "ampersand",
+        ':' => "colon",
+        ';' => "semicolon",
+        '=' => "equals",
+        '+' => "plus",
+        '-' => "hyphen",
+        '_' => "underscore",
+        '"' => "quote",
+        '$' => "dollar",
+        '@' => "at sign",
+        _ => return ch.to_uppercase().collect(),
+    };
+    name.to_string()
+}
+
+/// Speaks text, interrupting whatever it was saying
+pub struct Speaker {
+    #[cfg(not(target_arch = "wasm32"))]
+    tts: Option<tts::Tts>,
+    error: Option<String>,
+}
+
+impl Speaker {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new() -> Self {
+        match tts::Tts::default() {
+            Ok(tts) => Self {
+                tts: Some(tts),
+                error: None,
+            },
+            Err(e) => {
+                let err = format!("Speech is unavailable: {}", e);
+                tracing::warn!("{}", err);
+                Self {
+                    tts: None,
+                    error: Some(err),
+                }
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn new() -> Self {
+        Self { error: None }
+    }
+
+    /// Why nothing can be spoken.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn say(&mut self, text: &str) {
+        if let Some(tts) = &mut self.tts
+            && let Err(e) = tts.speak(text, true)
+        {
+            tracing::warn!("Failed to speak \"{}\": {}", text, e);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn say(&mut self, text: &str) {
+        let spoken = web_sys::window()
+            .ok_or_else(|| "no window".to_string())
+            .and_then(|window| {
+                let synthesis = window.speech_synthesis().map_err(|e| format!("{:?}", e))?;
+                let utterance = web_sys::SpeechSynthesisUtterance::new_with_text(text)
+                    .map_err(|e| format!("{:?}", e))?;
+                synthesis.cancel();
+                synthesis.speak(&utterance);
+                Ok(())
+            });
+        if let Err(err) = spoken {
+            let err = format!("Speech is unavailable: {}", err);
+            tracing::warn!("{}", err);
+            self.error = Some(err);
+        }
+    }
+
+    /// Stop speaking right away.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stop(&mut self) {
+        if let Some(tts) = &mut self.tts {
+            // Engines that can't stop finish the short name on their own.
+            let _ = tts.stop();
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn stop(&mut self) {
+        if let Some(window) = web_sys::window()
+            && let Ok(synthesis) = window.speech_synthesis()
+        {
+            synthesis.cancel();
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::VecDeque;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+use morset::signal::dit_to_wpm;
+
+/// Number of recent elements and gaps the speed is averaged over,
+/// roughly the last few characters
+pub static SPEED_WINDOW: usize = 24;
+
+/// Measures how fast the user is actually keying
+pub struct SpeedMeter {
+    /// Measured duration of each element or gap along with its length in dit units
+    samples: VecDeque<(Duration, u32)>,
+}
+
+impl SpeedMeter {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record an element or gap that should have lasted `units` dits.
+    pub fn record(&mut self, duration: Duration, units: u32) {
+        self.samples.push_back((duration, units));
+        while self.samples.len() > SPEED_WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Words per minute the recent elements were sent at.
+    pub fn wpm(&self) -> Option<f32> {
+        let units = self.samples.iter().map(|(_, units)| units).sum::<u32>();
+        if units == 0 {
+            return None;
+        }
+        let duration = self
+            .samples
+            .iter()
+            .map(|(duration, _)| *duration)
+            .sum::<Duration>();
+        Some(dit_to_wpm(duration / units))
+    }
+}
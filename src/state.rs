@@ -3,4 +3,5 @@ pub enum AppState {
     MainMenu,
     Listening,
     Writing,
+    Settings,
 }
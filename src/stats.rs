@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::drill::{Grade, Mark};
+
+/// Number of most missed characters in the summary
+pub static MOST_MISSED_COUNT: usize = 5;
+
+/// Gaps between keying or copying longer than this count as idle
+pub static IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the user did during a visit to a practice screen
+pub struct SessionStats {
+    started: Instant,
+    /// Seconds since the Unix epoch when the session started, for the export
+    started_at: u64,
+    sent: usize,
+    received: usize,
+    /// Graded characters and how many of them were right
+    graded: usize,
+    correct: usize,
+    /// How often every expected character was missed or mistaken
+    missed: HashMap<char, u32>,
+    /// Every speed practiced at with the time it was reached, when it changed along the way
+    speeds: Vec<(Duration, u8)>,
+    /// Answers not yet added to the long-term accuracy of the characters
+    answers: Vec<(char, bool)>,
+    /// Time spent keying or copying, short pauses included
+    active: Duration,
+    last_activity: Option<Instant>,
+    goal_reached: bool,
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs()),
+            sent: 0,
+            received: 0,
+            graded: 0,
+            correct: 0,
+            missed: HashMap::new(),
+            speeds: Vec::new(),
+            answers: Vec::new(),
+            active: Duration::ZERO,
+            last_activity: None,
+            goal_reached: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sent == 0 && self.received == 0
+    }
+
+    /// Record that the user is keying or copying,
+    /// the time since the last activity counting as active unless it was idle.
+    pub fn record_activity(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_activity {
+            let since = now.duration_since(last);
+            if since < IDLE_TIMEOUT {
+                self.active += since;
+            }
+        }
+        self.last_activity = Some(now);
+    }
+
+    /// Record a character the user keyed.
+    pub fn record_sent(&mut self) {
+        self.sent += 1;
+        self.record_activity();
+    }
+
+    /// Record characters the user was sent to copy.
+    pub fn record_received(&mut self, count: usize) {
+        self.received += count;
+        self.record_activity();
+    }
+
+    /// Record the answer to a single character.
+    pub fn record_answer(&mut self, expected: char, correct: bool) {
+        self.record_activity();
+        self.answers.push((expected, correct));
+        self.graded += 1;
+        if correct {
+            self.correct += 1;
+        } else {
+            *self.missed.entry(expected).or_default() += 1;
+        }
+    }
+
+    /// Record every character of a graded attempt.
+    pub fn record_grade(&mut self, grade: &Grade) {
+        for mark in &grade.marks {
+            match *mark {
+                Mark::Correct(ch) => self.record_answer(ch, true),
+                Mark::Substituted { expected, .. } => self.record_answer(expected, false),
+                Mark::Missed(ch) => self.record_answer(ch, false),
+                Mark::Extra(_) => {}
+            }
+        }
+    }
+
+    /// Answers recorded since the last call, for the long-term accuracy.
+    pub fn take_answers(&mut self) -> Vec<(char, bool)> {
+        std::mem::take(&mut self.answers)
+    }
+
+    /// Date and time the session started, in UTC.
+    pub fn started_at_utc(&self) -> String {
+        format_utc(self.started_at)
+    }
+
+    /// Record the speed practiced at from now on.
+    pub fn record_speed(&mut self, wpm: u8) {
+        if self.speeds.last().is_none_or(|(_, last)| *last != wpm) {
+            self.speeds.push((self.started.elapsed(), wpm));
+        }
+    }
+
+    /// Speeds practiced at with the time they were reached.
+    pub fn speeds(&self) -> &[(Duration, u8)] {
+        &self.speeds
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn active(&self) -> Duration {
+        self.active
+    }
+
+    pub fn idle(&self) -> Duration {
+        self.duration().saturating_sub(self.active)
+    }
+
+    /// Day the session started on, in days since the Unix epoch in UTC.
+    pub fn day(&self) -> u64 {
+        self.started_at / 86400
+    }
+
+    /// Check the session against the goal, returning true only once when it's reached.
+    pub fn reach_goal(&mut self, goal: Option<Duration>) -> bool {
+        if self.goal_reached || goal.is_none_or(|goal| self.duration() < goal) {
+            return false;
+        }
+        self.goal_reached = true;
+        true
+    }
+
+    pub fn goal_reached(&self) -> bool {
+        self.goal_reached
+    }
+
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+
+    pub fn received(&self) -> usize {
+        self.received
+    }
+
+    /// Characters sent and received per minute over the session, five making a word.
+    pub fn effective_wpm(&self) -> Option<f32> {
+        let minutes = self.duration().as_secs_f32() / 60.;
+        (minutes > 0. && !self.is_empty())
+            .then(|| (self.sent + self.received) as f32 / 5. / minutes)
+    }
+
+    /// Part of the graded characters that were right.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.graded > 0).then(|| self.correct as f32 / self.graded as f32)
+    }
+
+    /// Characters missed most often with how many times, most first.
+    pub fn most_missed(&self) -> Vec<(char, u32)> {
+        let mut missed = self
+            .missed
+            .iter()
+            .map(|(ch, count)| (*ch, *count))
+            .collect::<Vec<_>>();
+        missed.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        missed.truncate(MOST_MISSED_COUNT);
+        missed
+    }
+
+    /// Header and a row with the summary, for tracking progress in a spreadsheet.
+    pub fn to_csv(&self) -> String {
+        let optional = |value: Option<f32>| value.map(|v| format!("{:.1}", v)).unwrap_or_default();
+        let missed = self
+            .most_missed()
+            .iter()
+            .map(|(ch, count)| format!("{}:{}", ch, count))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            "started,duration_s,sent,received,effective_wpm,accuracy_percent,most_missed\n\
+             {},{},{},{},{},{},\"{}\"\n",
+            self.started_at,
+            self.duration().as_secs(),
+            self.sent,
+            self.received,
+            optional(self.effective_wpm()),
+            optional(self.accuracy().map(|accuracy| accuracy * 100.)),
+            missed.replace('"', "\"\""),
+        )
+    }
+}
+
+/// Today in days since the Unix epoch in UTC.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / 86400)
+}
+
+/// Format seconds since the Unix epoch as `YYYY-MM-DD HH:MM UTC`.
+pub fn format_utc(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let minutes = secs % 86400 / 60;
+    // Civil date from days since the epoch, in eras of 400 years starting on March 1st.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+
+use morset::{
+    consts::{NUMBERS, WORD_LIST},
+    utils::CharacterTable,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::callsign::{Region, generate_callsign};
+
+/// Number of characters in a code group
+pub static CODE_GROUP_LENGTH: usize = 5;
+
+/// Attempts at picking an item other than the previous one before giving up
+static MAX_TRIES: usize = 10;
+
+/// Where drills take the text to send from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextSource {
+    Characters,
+    CodeGroups,
+    #[default]
+    Words,
+    Callsigns,
+}
+
+impl TextSource {
+    pub const ALL: [TextSource; 4] = [
+        TextSource::Characters,
+        TextSource::CodeGroups,
+        TextSource::Words,
+        TextSource::Callsigns,
+    ];
+}
+
+impl fmt::Display for TextSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextSource::Characters => write!(f, "Random characters"),
+            TextSource::CodeGroups => write!(f, "Code groups"),
+            TextSource::Words => write!(f, "Word list"),
+            TextSource::Callsigns => write!(f, "Callsigns"),
+        }
+    }
+}
+
+/// Picks drill items from a source
+#[derive(Debug, Clone)]
+pub struct TextGenerator {
+    pub source: TextSource,
+    /// Only these characters are used when given, e.g. the ones unlocked in the Koch trainer
+    pub characters: Option<&'static [char]>,
+    /// Characters that can be sent
+    pub table: CharacterTable,
+    /// Regions callsign prefixes are taken from, all of them if empty
+    pub regions: Vec<Region>,
+}
+
+impl TextGenerator {
+    /// Pick an item other than the previous one, when there's a choice.
+    pub fn next(&self, previous: &str) -> String {
+        let mut item = self.item();
+        for _ in 0..MAX_TRIES {
+            if item != previous {
+                break;
+            }
+            item = self.item();
+        }
+        item
+    }
+
+    fn item(&self) -> String {
+        let pool = self.pool();
+        match self.source {
+            TextSource::Characters => random_characters(&pool, 1),
+            TextSource::CodeGroups => random_characters(&pool, CODE_GROUP_LENGTH),
+            TextSource::Words => {
+                let words = WORD_LIST
+                    .iter()
+                    .filter(|word| word.chars().all(|ch| ch == ' ' || self.is_allowed(ch)))
+                    .collect::<Vec<_>>();
+                if words.is_empty() {
+                    // Too few characters unlocked for any word.
+                    return random_characters(&pool, CODE_GROUP_LENGTH);
+                }
+                words[fastrand::usize(..words.len())].to_string()
+            }
+            TextSource::Callsigns => generate_callsign(&self.regions, &pool)
+                // Too few characters unlocked, or an alphabet without latin letters.
+                .unwrap_or_else(|| random_characters(&pool, CODE_GROUP_LENGTH)),
+        }
+    }
+
+    fn is_allowed(&self, ch: char) -> bool {
+        self.table.char_to_morse(ch).is_some()
+            && self
+                .characters
+                .is_none_or(|characters| characters.contains(&ch))
+    }
+
+    /// Characters random items are built from, letters and digits of the alphabet by default.
+    fn pool(&self) -> Vec<char> {
+        let pool = self
+            .characters
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|ch| self.table.char_to_morse(*ch).is_some())
+            .collect::<Vec<_>>();
+        if !pool.is_empty() {
+            return pool;
+        }
+        self.table
+            .alphabet()
+            .letters()
+            .iter()
+            .chain(NUMBERS.iter())
+            .map(|(ch, _)| *ch)
+            .collect()
+    }
+}
+
+pub fn random_characters(pool: &[char], count: usize) -> String {
+    (0..count)
+        .map(|_| pool[fastrand::usize(..pool.len())])
+        .collect()
+}
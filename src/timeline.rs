@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+/// How far back the timeline reaches
+pub const TIMELINE_SPAN: Duration = Duration::from_secs(6);
+
+/// Tone keyed by the user, times measured from the start of the timeline
+#[derive(Debug, Clone, Copy)]
+pub struct Tone {
+    pub start: Duration,
+    /// `None` while the key is still down
+    pub end: Option<Duration>,
+    /// Element the tone was decoded as, once it ended
+    pub element: Option<char>,
+}
+
+/// Recent tones, for drawing the rhythm of the keying
+pub struct Timeline {
+    started: Instant,
+    tones: VecDeque<Tone>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            tones: VecDeque::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Time since the timeline started.
+    pub fn now(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn key_down(&mut self) {
+        let now = self.now();
+        self.tones.push_back(Tone {
+            start: now,
+            end: None,
+            element: None,
+        });
+        // Tones that scrolled out of view are forgotten.
+        while self
+            .tones
+            .front()
+            .and_then(|tone| tone.end)
+            .is_some_and(|end| end + TIMELINE_SPAN < now)
+        {
+            self.tones.pop_front();
+        }
+    }
+
+    pub fn key_up(&mut self, element: char) {
+        let now = self.now();
+        if let Some(tone) = self.tones.back_mut()
+            && tone.end.is_none()
+        {
+            tone.end = Some(now);
+            tone.element = Some(element);
+        }
+    }
+
+    /// Forget the tone being keyed, if the key is down.
+    pub fn discard_tone(&mut self) {
+        if self.tones.back().is_some_and(|tone| tone.end.is_none()) {
+            self.tones.pop_back();
+        }
+    }
+
+    /// Tones that are at least partly in view, oldest first.
+    pub fn tones(&self) -> impl Iterator<Item = &Tone> {
+        let now = self.now();
+        self.tones
+            .iter()
+            .filter(move |tone| tone.end.is_none_or(|end| end + TIMELINE_SPAN >= now))
+    }
+
+    /// Whether anything is in view, so the timeline has to keep scrolling.
+    pub fn is_moving(&self) -> bool {
+        self.tones().next().is_some()
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::VecDeque;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+pub static DEFAULT_HISTORY_LIMIT: usize = 2000;
+
+/// Most edits that can be undone
+pub static MAX_UNDO: usize = 500;
+
+/// Single decoded character
+#[derive(Debug, Clone, Copy)]
+pub struct TranscriptEntry {
+    pub time: Instant,
+    pub ch: char,
+    /// The character was guessed by the tolerant decoder
+    pub guessed: bool,
+}
+
+/// Change to the transcript, kept so it can be undone and redone
+#[derive(Debug, Clone)]
+enum Edit {
+    Push(TranscriptEntry),
+    Pop(TranscriptEntry),
+    Clear(VecDeque<TranscriptEntry>),
+}
+
+/// Decoded text along with the time every character was received
+pub struct Transcript {
+    entries: VecDeque<TranscriptEntry>,
+    limit: usize,
+    started: Instant,
+    /// Edits that can be undone, the latest last
+    undo: VecDeque<Edit>,
+    /// Undone edits that can be redone, the latest undone last
+    redo: Vec<Edit>,
+}
+
+impl Transcript {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            limit,
+            started: Instant::now(),
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Change the maximum number of characters kept, evicting the oldest ones.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.limit {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Keep the edit so it can be undone, dropping anything undone before.
+    fn record(&mut self, edit: Edit) {
+        self.redo.clear();
+        self.undo.push_back(edit);
+        while self.undo.len() > MAX_UNDO {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Make the edit, or make it again when redoing.
+    fn apply(&mut self, edit: &Edit) {
+        match edit {
+            Edit::Push(entry) => {
+                self.entries.push_back(*entry);
+                self.evict();
+            }
+            Edit::Pop(_) => {
+                self.entries.pop_back();
+            }
+            Edit::Clear(_) => self.entries.clear(),
+        }
+    }
+
+    /// Take the edit back.
+    fn revert(&mut self, edit: &Edit) {
+        match edit {
+            Edit::Push(_) => {
+                self.entries.pop_back();
+            }
+            Edit::Pop(entry) => {
+                self.entries.push_back(*entry);
+                self.evict();
+            }
+            Edit::Clear(entries) => {
+                self.entries = entries.clone();
+                self.evict();
+            }
+        }
+    }
+
+    pub fn push(&mut self, ch: char, guessed: bool) {
+        let entry = TranscriptEntry {
+            time: Instant::now(),
+            ch,
+            guessed,
+        };
+        self.apply(&Edit::Push(entry));
+        self.record(Edit::Push(entry));
+    }
+
+    /// Remove the last character.
+    pub fn pop(&mut self) -> Option<TranscriptEntry> {
+        let entry = self.entries.pop_back()?;
+        self.record(Edit::Pop(entry));
+        Some(entry)
+    }
+
+    /// Remove all characters, the clear can be undone.
+    pub fn clear(&mut self) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let entries = std::mem::take(&mut self.entries);
+        self.record(Edit::Clear(entries));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Take back the last edit.
+    pub fn undo(&mut self) {
+        if let Some(edit) = self.undo.pop_back() {
+            self.revert(&edit);
+            self.redo.push(edit);
+        }
+    }
+
+    /// Make the last undone edit again.
+    pub fn redo(&mut self) {
+        if let Some(edit) = self.redo.pop() {
+            self.apply(&edit);
+            self.undo.push_back(edit);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn last_char(&self) -> Option<char> {
+        self.entries.back().map(|entry| entry.ch)
+    }
+
+    /// Time between the creation of the transcript and the entry.
+    pub fn offset(&self, entry: &TranscriptEntry) -> Duration {
+        entry.time.duration_since(self.started)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &TranscriptEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(transcript: &Transcript) -> String {
+        transcript.entries().map(|entry| entry.ch).collect()
+    }
+
+    fn typed(text: &str) -> Transcript {
+        let mut transcript = Transcript::new(DEFAULT_HISTORY_LIMIT);
+        for ch in text.chars() {
+            transcript.push(ch, false);
+        }
+        transcript
+    }
+
+    #[test]
+    fn undoes_and_redoes_pushes() {
+        let mut transcript = typed("AB");
+        transcript.undo();
+        assert_eq!(text(&transcript), "A");
+        transcript.undo();
+        assert_eq!(text(&transcript), "");
+        assert!(!transcript.can_undo());
+        transcript.undo();
+        assert_eq!(text(&transcript), "");
+
+        transcript.redo();
+        assert_eq!(text(&transcript), "A");
+        transcript.redo();
+        assert_eq!(text(&transcript), "AB");
+        assert!(!transcript.can_redo());
+        transcript.redo();
+        assert_eq!(text(&transcript), "AB");
+    }
+
+    #[test]
+    fn undoes_and_redoes_pops_and_clears() {
+        let mut transcript = typed("ABC");
+        assert_eq!(transcript.pop().map(|entry| entry.ch), Some('C'));
+        transcript.clear();
+        assert!(transcript.is_empty());
+
+        transcript.undo();
+        assert_eq!(text(&transcript), "AB");
+        transcript.undo();
+        assert_eq!(text(&transcript), "ABC");
+        transcript.redo();
+        assert_eq!(text(&transcript), "AB");
+        transcript.redo();
+        assert_eq!(text(&transcript), "");
+    }
+
+    #[test]
+    fn new_edit_drops_what_was_undone() {
+        let mut transcript = typed("AB");
+        transcript.undo();
+        assert!(transcript.can_redo());
+        transcript.push('C', false);
+        assert!(!transcript.can_redo());
+        transcript.redo();
+        assert_eq!(text(&transcript), "AC");
+    }
+
+    #[test]
+    fn undone_pop_keeps_the_entry() {
+        let mut transcript = Transcript::new(DEFAULT_HISTORY_LIMIT);
+        transcript.push('X', true);
+        let popped = transcript.pop().unwrap();
+        transcript.undo();
+        let restored = transcript.entries().next().unwrap();
+        assert_eq!(restored.ch, 'X');
+        assert!(restored.guessed);
+        assert_eq!(restored.time, popped.time);
+    }
+
+    #[test]
+    fn nothing_to_undo_without_edits() {
+        let mut transcript = Transcript::new(DEFAULT_HISTORY_LIMIT);
+        transcript.clear();
+        assert_eq!(transcript.pop().map(|entry| entry.ch), None);
+        assert!(!transcript.can_undo());
+        assert!(!transcript.can_redo());
+    }
+
+    #[test]
+    fn keeps_at_most_max_undo_edits() {
+        let mut transcript = typed(&"E".repeat(MAX_UNDO + 10));
+        while transcript.can_undo() {
+            transcript.undo();
+        }
+        assert_eq!(text(&transcript), "E".repeat(10));
+    }
+}
@@ -1,4 +1,6 @@
-use crate::consts;
+use std::sync::Arc;
+
+use crate::consts::{self, Alphabet};
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Duration;
@@ -20,15 +22,343 @@ pub fn wpm_to_dit_duration(wpm: u8) -> std::time::Duration {
     Duration::from_millis((1.2 * (1000. / wpm as f64)).ceil() as u64)
 }
 
-pub fn morse_to_char(morse: &str) -> Option<char> {
-    for (c, code) in consts::ABC
+/// Unit of the gaps between characters and words when characters are sent at
+/// `character_wpm` but the text comes out at the slower `effective_wpm` (Farnsworth timing).
+///
+/// PARIS has 19 units of character and word gaps, the delay added to reach the
+/// effective speed is spread over them as in the ARRL formula.
+pub fn farnsworth_spacing(character_wpm: u8, effective_wpm: u8) -> std::time::Duration {
+    let c = character_wpm as f64;
+    let s = effective_wpm.min(character_wpm) as f64;
+    // Seconds of gaps in PARIS at the effective speed
+    let delay = (60. * c - 37.2 * s) / (c * s);
+    Duration::from_millis((delay / 19. * 1000.).ceil() as u64)
+}
+
+/// Silence after which a character is complete, in dit units.
+pub const CHARACTER_GAP: f32 = 3.0;
+
+/// Silence after which a word is complete, in dit units.
+pub const WORD_GAP: f32 = 7.0;
+
+/// Default boundary between a dot and a dash, in dit units.
+///
+/// A dash is nominally three dits long, so the decision point sits
+/// halfway between the two at two dits.
+pub const DEFAULT_DASH_THRESHOLD: f32 = 2.0;
+
+/// Classify a straight key press of the given duration as a dot or a dash.
+///
+/// `dash_threshold` is measured in dit units, so the classification
+/// stays correct at any WPM.
+pub fn classify_press(pressed: Duration, dit_duration: Duration, dash_threshold: f32) -> char {
+    if pressed.as_secs_f32() < dit_duration.as_secs_f32() * dash_threshold {
+        '.'
+    } else {
+        '-'
+    }
+}
+
+/// Characters that can be encoded and decoded, with their sequences
+///
+/// Built once from the alphabet, the accented letters and the custom characters
+/// in use, and cheap to clone after that.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharacterTable {
+    alphabet: Alphabet,
+    /// Custom characters first, then the built-in ones they don't replace
+    entries: Arc<[(char, String)]>,
+}
+
+impl CharacterTable {
+    /// Table of the alphabet's letters, the digits and the signs.
+    ///
+    /// The accented letters of [`consts::EXTENDED`] are added if `extended` is set, only to
+    /// the latin alphabet. They are off by default, since some of them take the sequences
+    /// of prosigns. The `custom` characters are looked up before the built-in ones,
+    /// or used alone if `replace` is set and there are any.
+    pub fn new(
+        alphabet: Alphabet,
+        extended: bool,
+        custom: &[(char, String)],
+        replace: bool,
+    ) -> Self {
+        let extended = if extended && alphabet == Alphabet::Latin {
+            consts::EXTENDED.len()
+        } else {
+            0
+        };
+        let builtin = (!replace || custom.is_empty()).then(|| {
+            alphabet
+                .letters()
+                .iter()
+                .chain(consts::NUMBERS.iter())
+                .chain(consts::SIGNS.iter())
+                .chain(consts::EXTENDED.iter().take(extended))
+                .filter(|(ch, _)| custom.iter().all(|(other, _)| other != ch))
+                .map(|(ch, seq)| (*ch, seq.to_string()))
+        });
+        Self {
+            alphabet,
+            entries: custom
+                .iter()
+                .cloned()
+                .chain(builtin.into_iter().flatten())
+                .collect(),
+        }
+    }
+
+    pub fn alphabet(&self) -> Alphabet {
+        self.alphabet
+    }
+
+    /// Every character with its sequence, custom ones replacing the built-in ones
+    /// for the same character.
+    pub fn iter(&self) -> impl Iterator<Item = (char, &str)> {
+        self.entries.iter().map(|(ch, seq)| (*ch, seq.as_str()))
+    }
+
+    pub fn morse_to_char(&self, morse: &str) -> Option<char> {
+        self.iter().find(|(_, code)| *code == morse).map(|(c, _)| c)
+    }
+
+    /// Find the sequence of dots and dashes for a character, ignoring case.
+    pub fn char_to_morse(&self, ch: char) -> Option<&str> {
+        let ch = ch.to_uppercase().next().unwrap_or(ch);
+        self.iter().find(|(c, _)| *c == ch).map(|(_, code)| code)
+    }
+
+    /// Find the character whose sequence is closest to `morse`, allowing up to
+    /// `tolerance` extra, missing or substituted elements.
+    ///
+    /// Returns `None` if nothing is close enough or if several characters
+    /// are equally close, since the intended one can't be told apart then.
+    pub fn closest_char(&self, morse: &str, tolerance: usize) -> Option<char> {
+        let mut best: Option<(char, usize)> = None;
+        let mut ambiguous = false;
+        for (c, code) in self.iter() {
+            let distance = edit_distance(morse, code);
+            if distance > tolerance {
+                continue;
+            }
+            match best {
+                Some((_, best_distance)) if distance > best_distance => {}
+                Some((_, best_distance)) if distance == best_distance => ambiguous = true,
+                _ => {
+                    best = Some((c, distance));
+                    ambiguous = false;
+                }
+            }
+        }
+        if ambiguous {
+            return None;
+        }
+        best.map(|(c, _)| c)
+    }
+}
+
+impl Default for CharacterTable {
+    /// The latin alphabet without accented letters.
+    fn default() -> Self {
+        Self::new(Alphabet::Latin, false, &[], false)
+    }
+}
+
+/// Levenshtein distance between two element sequences.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.bytes().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Letter sent in place of the digit in cut numbers, if it has one.
+pub fn cut_digit(digit: char) -> Option<char> {
+    consts::CUT_NUMBERS
+        .iter()
+        .find(|(other, _)| *other == digit)
+        .map(|(_, letter)| *letter)
+}
+
+/// Digit the letter stands for in cut numbers, if any.
+pub fn uncut_letter(letter: char) -> Option<char> {
+    let letter = letter.to_uppercase().next().unwrap_or(letter);
+    consts::CUT_NUMBERS
         .iter()
-        .chain(consts::NUMBERS.iter())
-        .chain(consts::SIGNS.iter())
-    {
-        if *code == morse {
-            return Some(*c);
+        .find(|(_, other)| *other == letter)
+        .map(|(digit, _)| *digit)
+}
+
+/// Send every digit that has a cut number as its letter.
+pub fn cut_numbers(text: &str) -> String {
+    text.chars().map(|ch| cut_digit(ch).unwrap_or(ch)).collect()
+}
+
+/// Read cut numbers back as digits.
+///
+/// Only words holding a digit and otherwise only cut letters are taken as numbers,
+/// so 5NN becomes 599 while TEN stays a word.
+pub fn uncut_numbers(text: &str) -> String {
+    text.split_inclusive(char::is_whitespace)
+        .map(|word| {
+            let trimmed = word.trim_end();
+            let numeric = trimmed.chars().any(|ch| ch.is_ascii_digit())
+                && trimmed
+                    .chars()
+                    .all(|ch| ch.is_ascii_digit() || uncut_letter(ch).is_some());
+            if numeric {
+                word.chars()
+                    .map(|ch| uncut_letter(ch).unwrap_or(ch))
+                    .collect()
+            } else {
+                word.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_press_splits_at_the_dash_threshold_at_any_speed() {
+        let margin = Duration::from_millis(1);
+        for wpm in [5, 13, 20, 30, 40] {
+            let dit = wpm_to_dit_duration(wpm);
+            let threshold = dit.mul_f32(DEFAULT_DASH_THRESHOLD);
+            for (pressed, expected) in [
+                (dit / 2, '.'),
+                (dit, '.'),
+                (threshold - margin, '.'),
+                (threshold + margin, '-'),
+                (dit * 3, '-'),
+                (dit * 6, '-'),
+            ] {
+                assert_eq!(
+                    classify_press(pressed, dit, DEFAULT_DASH_THRESHOLD),
+                    expected,
+                    "{:?} press at {} WPM",
+                    pressed,
+                    wpm
+                );
+            }
         }
     }
-    None
+
+    #[test]
+    fn classify_press_follows_a_custom_threshold() {
+        let dit = wpm_to_dit_duration(20);
+        let margin = Duration::from_millis(1);
+        assert_eq!(classify_press(dit * 2 + margin, dit, 2.5), '.');
+        assert_eq!(classify_press(dit.mul_f32(2.5) + margin, dit, 2.5), '-');
+        assert_eq!(classify_press(dit.mul_f32(1.5) - margin, dit, 1.5), '.');
+        assert_eq!(classify_press(dit.mul_f32(1.5) + margin, dit, 1.5), '-');
+    }
+
+    /// Table of only these letters of the latin alphabet.
+    fn only_letters(letters: &str) -> CharacterTable {
+        let custom = consts::ABC
+            .iter()
+            .filter(|(ch, _)| letters.contains(*ch))
+            .map(|(ch, seq)| (*ch, seq.to_string()))
+            .collect::<Vec<_>>();
+        CharacterTable::new(Alphabet::Latin, false, &custom, true)
+    }
+
+    #[test]
+    fn closest_char_corrects_a_single_insertion() {
+        let table = only_letters("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+        assert_eq!(table.closest_char(".....", 1), Some('H'));
+        assert_eq!(table.closest_char("..---", 1), Some('J'));
+        assert_eq!(table.closest_char("---.-", 1), Some('Q'));
+        assert_eq!(table.closest_char("...--", 1), Some('V'));
+        assert_eq!(table.closest_char("---..", 1), Some('Z'));
+    }
+
+    #[test]
+    fn closest_char_corrects_a_single_deletion() {
+        // Every shorter sequence is a letter of its own in the full alphabet.
+        let table = only_letters("SO");
+        assert_eq!(table.closest_char("..", 1), Some('S'));
+        assert_eq!(table.closest_char("--", 1), Some('O'));
+    }
+
+    #[test]
+    fn closest_char_corrects_a_single_substitution() {
+        let table = only_letters("SO");
+        assert_eq!(table.closest_char(".-.", 1), Some('S'));
+        assert_eq!(table.closest_char("-.-", 1), Some('O'));
+        assert_eq!(table.closest_char("--.", 1), Some('O'));
+    }
+
+    #[test]
+    fn closest_char_refuses_ambiguous_and_distant_sequences() {
+        let table = only_letters("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+        // One edit away from F, J and U alike
+        assert_eq!(table.closest_char("..--", 1), None);
+        let table = only_letters("SO");
+        assert_eq!(table.closest_char("-.-", 0), None);
+        assert_eq!(table.closest_char(".-.-.", 1), None);
+    }
+
+    #[test]
+    fn cuts_the_standard_digits() {
+        let standard = [
+            ('0', 'T'),
+            ('1', 'A'),
+            ('2', 'U'),
+            ('3', 'V'),
+            ('5', 'E'),
+            ('7', 'B'),
+            ('8', 'D'),
+            ('9', 'N'),
+        ];
+        for (digit, letter) in standard {
+            assert_eq!(cut_digit(digit), Some(letter), "{} isn't cut", digit);
+            assert_eq!(
+                uncut_letter(letter),
+                Some(digit),
+                "{} isn't read back",
+                letter
+            );
+            assert_eq!(
+                uncut_letter(letter.to_ascii_lowercase()),
+                Some(digit),
+                "{} isn't read back",
+                letter
+            );
+        }
+        // 4 and 6 are only ever sent in full.
+        assert_eq!(cut_digit('4'), None);
+        assert_eq!(cut_digit('6'), None);
+        assert_eq!(uncut_letter('H'), None);
+        assert_eq!(uncut_letter('6'), None);
+    }
+
+    #[test]
+    fn cuts_every_digit_of_a_text() {
+        assert_eq!(cut_numbers("RST 599 73"), "RST ENN BV");
+        assert_eq!(cut_numbers("1234567890"), "AUV4E6BDNT");
+        assert_eq!(cut_numbers("CQ DE W1AW"), "CQ DE WAAW");
+    }
+
+    #[test]
+    fn reads_back_only_numeric_words() {
+        assert_eq!(uncut_numbers("UR 5NN 4T"), "UR 599 40");
+        assert_eq!(uncut_numbers("TEN ANT"), "TEN ANT");
+        // Without a digit, a fully cut number can't be told from a word.
+        assert_eq!(uncut_numbers("ENN"), "ENN");
+        // Words mixing digits with letters that aren't cut numbers are callsigns.
+        assert_eq!(uncut_numbers("W1AW 1TT"), "W1AW 100");
+        assert_eq!(uncut_numbers("5nn\n"), "599\n");
+    }
 }
@@ -0,0 +1,216 @@
+//! Output to a K1EL WinKeyer on a serial port, so the practice keys a real transmitter.
+//!
+//! Only part of the host protocol is used:
+//! - `0x00 0x02` Host Open and `0x00 0x03` Host Close
+//! - `0x02 nn` Set WPM Speed, 5 to 99
+//! - `0x03 nn` Set Weighting, 10 to 90
+//! - `0x0A` Clear Buffer
+//! - `0x0B nn` Key Immediate, 1 keys down and 0 up
+//! - Plain ASCII letters, digits and punctuation, sent from the buffer at the set speed
+
+use std::time::Duration;
+
+use serialport::{DataBits, SerialPort, StopBits};
+
+const ADMIN: u8 = 0x00;
+const HOST_OPEN: u8 = 0x02;
+const HOST_CLOSE: u8 = 0x03;
+const SET_SPEED: u8 = 0x02;
+const SET_WEIGHTING: u8 = 0x03;
+const CLEAR_BUFFER: u8 = 0x0A;
+const KEY_IMMEDIATE: u8 = 0x0B;
+
+/// Punctuation the WinKeyer sends as it is
+const PUNCTUATION: &str = "\"$'()+,-./:;=?@";
+
+pub fn host_open() -> [u8; 2] {
+    [ADMIN, HOST_OPEN]
+}
+
+pub fn host_close() -> [u8; 2] {
+    [ADMIN, HOST_CLOSE]
+}
+
+/// Speed command, clamped to what the WinKeyer accepts.
+pub fn set_speed(wpm: u8) -> [u8; 2] {
+    [SET_SPEED, wpm.clamp(5, 99)]
+}
+
+/// Weighting command, 50 being standard, clamped to what the WinKeyer accepts.
+pub fn set_weighting(weight: u8) -> [u8; 2] {
+    [SET_WEIGHTING, weight.clamp(10, 90)]
+}
+
+pub fn clear_buffer() -> [u8; 1] {
+    [CLEAR_BUFFER]
+}
+
+pub fn key_immediate(down: bool) -> [u8; 2] {
+    [KEY_IMMEDIATE, u8::from(down)]
+}
+
+/// Bytes that make the WinKeyer send the text.
+///
+/// Letters are sent in uppercase, whitespace becomes a single word space
+/// and characters the WinKeyer can't send are left out.
+pub fn encode_text(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            if bytes.last().is_some_and(|last| *last != b' ') {
+                bytes.push(b' ');
+            }
+        } else if ch.is_ascii_alphanumeric() || PUNCTUATION.contains(ch) {
+            bytes.push(ch.to_ascii_uppercase() as u8);
+        }
+    }
+    bytes
+}
+
+/// WinKeyer on the serial port selected in the settings
+pub struct WinKeyer {
+    /// Port selected when it was last opened
+    device: Option<String>,
+    port: Option<Box<dyn SerialPort>>,
+    /// Speed and weight last sent
+    speed: Option<(u8, u8)>,
+    error: Option<String>,
+}
+
+impl WinKeyer {
+    pub fn new() -> Self {
+        Self {
+            device: None,
+            port: None,
+            speed: None,
+            error: None,
+        }
+    }
+
+    /// Why the WinKeyer couldn't be used.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Follow the port selected in the settings, opening it when it changes.
+    pub fn select(&mut self, device: Option<&str>) {
+        if self.device.as_deref() == device {
+            return;
+        }
+        self.close();
+        self.device = device.map(str::to_string);
+        self.error = None;
+        if let Some(name) = device {
+            match serialport::new(name, 1200)
+                .data_bits(DataBits::Eight)
+                .stop_bits(StopBits::Two)
+                .timeout(Duration::from_millis(100))
+                .open()
+            {
+                Ok(port) => {
+                    self.port = Some(port);
+                    self.write(&host_open());
+                }
+                Err(e) => {
+                    let err = format!("Failed to open WinKeyer on \"{}\": {}", name, e);
+                    tracing::warn!("{}", err);
+                    self.error = Some(err);
+                }
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if self.port.is_some() {
+            self.write(&host_close());
+        }
+        self.port = None;
+        self.speed = None;
+    }
+
+    /// Write to the WinKeyer, giving it up if the port fails.
+    fn write(&mut self, bytes: &[u8]) {
+        let Some(port) = &mut self.port else {
+            return;
+        };
+        if let Err(e) = port.write_all(bytes) {
+            let err = format!("Stopped writing to the WinKeyer: {}", e);
+            tracing::warn!("{}", err);
+            self.error = Some(err);
+            self.port = None;
+        }
+    }
+
+    /// Send the speed and weight, if they changed since they were last sent.
+    pub fn set_speed(&mut self, wpm: u8, weight: u8) {
+        if self.port.is_none() || self.speed == Some((wpm, weight)) {
+            return;
+        }
+        self.speed = Some((wpm, weight));
+        self.write(&set_speed(wpm));
+        self.write(&set_weighting(weight));
+    }
+
+    /// Key the transmitter down or up right away.
+    pub fn key(&mut self, down: bool) {
+        self.write(&key_immediate(down));
+    }
+
+    /// Send the text, dropping whatever hadn't been sent yet.
+    pub fn send_text(&mut self, text: &str) {
+        self.write(&clear_buffer());
+        self.queue_text(text);
+    }
+
+    /// Send the text after whatever is still being sent.
+    pub fn queue_text(&mut self, text: &str) {
+        self.write(&encode_text(text));
+    }
+}
+
+impl Drop for WinKeyer {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_words_in_uppercase() {
+        assert_eq!(encode_text("cq de w1aw"), b"CQ DE W1AW");
+        assert_eq!(encode_text("PARIS"), b"PARIS");
+        assert_eq!(encode_text("5nn tu 73"), b"5NN TU 73");
+        assert_eq!(encode_text("qth? rst 599."), b"QTH? RST 599.");
+        assert_eq!(encode_text("w1aw/3"), b"W1AW/3");
+    }
+
+    #[test]
+    fn collapses_whitespace_into_word_spaces() {
+        assert_eq!(encode_text("  CQ \n\t DE  "), b"CQ DE ");
+        assert_eq!(encode_text("   "), b"");
+    }
+
+    #[test]
+    fn leaves_out_what_the_winkeyer_cant_send() {
+        assert_eq!(encode_text("<AR> ÄB!"), b"AR B");
+        assert_eq!(encode_text("ПРИВЕТ"), b"");
+    }
+
+    #[test]
+    fn encodes_commands() {
+        assert_eq!(host_open(), [0x00, 0x02]);
+        assert_eq!(host_close(), [0x00, 0x03]);
+        assert_eq!(clear_buffer(), [0x0A]);
+        assert_eq!(key_immediate(true), [0x0B, 1]);
+        assert_eq!(key_immediate(false), [0x0B, 0]);
+        assert_eq!(set_speed(20), [0x02, 20]);
+        assert_eq!(set_speed(1), [0x02, 5]);
+        assert_eq!(set_speed(120), [0x02, 99]);
+        assert_eq!(set_weighting(50), [0x03, 50]);
+        assert_eq!(set_weighting(0), [0x03, 10]);
+        assert_eq!(set_weighting(100), [0x03, 90]);
+    }
+}